@@ -0,0 +1,526 @@
+//! A crate to generate C headers describing the adapter-facing ABI of a
+//! WebAssembly module using interface types.
+//!
+//! Adapters in the interface types section call into the core module with
+//! `call-core`, `defer-call-core` and `string-to-memory`, and core imports can
+//! be implemented by adapters with `implement`. The core functions involved
+//! are typically written by the module's author, so this crate emits a C
+//! header with prototypes for each of them, annotated with the interface-level
+//! signature of the adapters that use them.
+
+#![deny(missing_docs)]
+
+use anyhow::{anyhow, bail, Context, Result};
+use std::collections::HashSet;
+use std::fmt::Write;
+use wasmparser::{ExternalKind, FuncType, ImportSectionEntryType, Name, NameSectionReader};
+use wasmparser::{Payload, TypeDef};
+use wit_parser::*;
+
+/// Generates a C header for the core functions used by the adapters of the
+/// wasm module `wasm`.
+///
+/// The `wasm` given must be an entire WebAssembly module. The header contains
+/// a prototype for every core function referenced by `call-core`,
+/// `defer-call-core` or `implement`, and the allocators used by
+/// `string-to-memory` are listed separately.
+///
+/// # Errors
+///
+/// Returns an error if the module fails to parse, if the interface types
+/// section refers to items which don't exist, or if a core function which
+/// needs a prototype has more than one result, which C can't express. Note
+/// that full validation isn't performed here, so it's recommended to run
+/// `wit_validator::validate` first.
+pub fn generate(wasm: &[u8]) -> Result<String> {
+    let mut module = ModuleInfo::default();
+    for payload in wasmparser::Parser::new(0).parse_all(wasm) {
+        match payload? {
+            Payload::TypeSection(s) => {
+                for ty in s {
+                    if let TypeDef::Func(ty) = ty? {
+                        module.core_types.push(ty);
+                    }
+                }
+            }
+            Payload::ImportSection(s) => {
+                for import in s {
+                    let import = import?;
+                    if let ImportSectionEntryType::Function(ty) = import.ty {
+                        module.core_funcs.push(CoreFunc {
+                            ty,
+                            import: Some((import.module, import.field.unwrap_or(""))),
+                            export: None,
+                            name: None,
+                        });
+                    }
+                }
+            }
+            Payload::FunctionSection(s) => {
+                for ty in s {
+                    module.core_funcs.push(CoreFunc {
+                        ty: ty?,
+                        import: None,
+                        export: None,
+                        name: None,
+                    });
+                }
+            }
+            Payload::ExportSection(s) => {
+                for export in s {
+                    let export = export?;
+                    if let ExternalKind::Function = export.kind {
+                        if let Some(f) = module.core_funcs.get_mut(export.index as usize) {
+                            f.export = f.export.or(Some(export.field));
+                        }
+                    }
+                }
+            }
+            Payload::CustomSection {
+                name: "name",
+                data,
+                data_offset,
+            } => {
+                // Names are only used to make the header nicer to read, so a
+                // malformed name section is ignored.
+                let _ = module.parse_names(data_offset, data);
+            }
+            Payload::CustomSection {
+                name: wit_schema_version::SECTION_NAME,
+                data,
+                data_offset,
+            } => {
+                module
+                    .parse_wit(data_offset, data)
+                    .context("failed to parse interface types section")?;
+            }
+            _ => {}
+        }
+    }
+
+    let mut gen = Generator {
+        module: &module,
+        dst: String::new(),
+        idents: HashSet::new(),
+    };
+    gen.generate()?;
+    Ok(gen.dst)
+}
+
+#[derive(Default)]
+struct ModuleInfo<'a> {
+    core_types: Vec<FuncType>,
+    core_funcs: Vec<CoreFunc<'a>>,
+    types: Vec<Type>,
+    funcs: Vec<Adapter<'a>>,
+    exports: Vec<Export<'a>>,
+    implements: Vec<Implement>,
+}
+
+struct CoreFunc<'a> {
+    ty: u32,
+    import: Option<(&'a str, &'a str)>,
+    export: Option<&'a str>,
+    name: Option<&'a str>,
+}
+
+struct Adapter<'a> {
+    ty: u32,
+    import: Option<(&'a str, &'a str)>,
+    body: Option<Func<'a>>,
+}
+
+impl<'a> ModuleInfo<'a> {
+    fn parse_names(&mut self, offset: usize, data: &'a [u8]) -> Result<()> {
+        let mut reader = NameSectionReader::new(data, offset)?;
+        while !reader.eof() {
+            if let Name::Function(names) = reader.read()? {
+                let mut map = names.get_map()?;
+                for _ in 0..map.get_count() {
+                    let naming = map.read()?;
+                    if let Some(f) = self.core_funcs.get_mut(naming.index as usize) {
+                        f.name = Some(naming.name);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_wit(&mut self, offset: usize, bytes: &'a [u8]) -> Result<()> {
+        let mut parser = Parser::new(offset, bytes)?;
+        while !parser.is_empty() {
+            match parser.section()? {
+                Section::Type(types) => {
+                    for ty in types {
+                        self.types.push(ty?);
+                    }
+                }
+                Section::Import(imports) => {
+                    for import in imports {
+                        let import = import?;
+                        self.funcs.push(Adapter {
+                            ty: import.ty,
+                            import: Some((import.module, import.name)),
+                            body: None,
+                        });
+                    }
+                }
+                Section::Func(funcs) => {
+                    for func in funcs {
+                        let func = func?;
+                        self.funcs.push(Adapter {
+                            ty: func.ty,
+                            import: None,
+                            body: Some(func),
+                        });
+                    }
+                }
+                Section::Export(exports) => {
+                    for export in exports {
+                        self.exports.push(export?);
+                    }
+                }
+                Section::Implement(implements) => {
+                    for implement in implements {
+                        self.implements.push(implement?);
+                    }
+                }
+                Section::ParamNames(_) | Section::Unknown { .. } => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn core_func(&self, idx: u32) -> Result<&CoreFunc<'a>> {
+        self.core_funcs
+            .get(idx as usize)
+            .ok_or_else(|| anyhow!("func index out of bounds: {}", idx))
+    }
+
+    fn core_type(&self, idx: u32) -> Result<&FuncType> {
+        self.core_types
+            .get(idx as usize)
+            .ok_or_else(|| anyhow!("type index out of bounds: {}", idx))
+    }
+
+    /// Returns a human readable description of the adapter function `idx`,
+    /// including its interface-level signature.
+    fn describe_adapter(&self, idx: u32) -> Result<String> {
+        let adapter = self
+            .funcs
+            .get(idx as usize)
+            .ok_or_else(|| anyhow!("adapter func index out of bounds: {}", idx))?;
+        let ty = self
+            .types
+            .get(adapter.ty as usize)
+            .ok_or_else(|| anyhow!("adapter type index out of bounds: {}", adapter.ty))?;
+        let mut ret = match adapter.import {
+            Some((module, name)) => format!(
+                "adapter import `{}` `{}`",
+                comment_text(module),
+                comment_text(name)
+            ),
+            None => match self.exports.iter().find(|e| e.func == idx) {
+                Some(export) => format!("adapter export `{}`", comment_text(export.name)),
+                None => format!("adapter function {}", idx),
+            },
+        };
+        ret.push(':');
+        if ty.params.is_empty() && ty.results.is_empty() {
+            ret.push_str(" (func)");
+        }
+        for param in ty.params.iter() {
+            write!(ret, " (param {})", param)?;
+        }
+        for result in ty.results.iter() {
+            write!(ret, " (result {})", result)?;
+        }
+        Ok(ret)
+    }
+}
+
+/// A core function which shows up in the header, along with a list of
+/// descriptions of how adapters use it.
+struct Entry {
+    func: u32,
+    uses: Vec<String>,
+}
+
+impl Entry {
+    fn add(list: &mut Vec<Entry>, func: u32, use_: String) {
+        let entry = match list.iter_mut().position(|e| e.func == func) {
+            Some(i) => &mut list[i],
+            None => {
+                list.push(Entry {
+                    func,
+                    uses: Vec::new(),
+                });
+                list.last_mut().unwrap()
+            }
+        };
+        if !entry.uses.contains(&use_) {
+            entry.uses.push(use_);
+        }
+    }
+}
+
+struct Generator<'a, 'b> {
+    module: &'b ModuleInfo<'a>,
+    dst: String,
+    /// C identifiers of the prototypes emitted so far.
+    idents: HashSet<String>,
+}
+
+impl Generator<'_, '_> {
+    fn generate(&mut self) -> Result<()> {
+        let mut allocators = Vec::new();
+        let mut funcs = Vec::new();
+        for (i, adapter) in self.module.funcs.iter().enumerate() {
+            let body = match &adapter.body {
+                Some(body) => body,
+                None => continue,
+            };
+            let desc = self.module.describe_adapter(i as u32)?;
+            for instr in body.instrs() {
+                match instr? {
+                    Instruction::CallCore(f) => {
+                        self.module.core_func(f)?;
+                        Entry::add(&mut funcs, f, format!("Called by {}", desc));
+                    }
+                    Instruction::DeferCallCore(f) => {
+                        self.module.core_func(f)?;
+                        Entry::add(&mut funcs, f, format!("Deferred call in {}", desc));
+                    }
+                    Instruction::StringToMemory(args) => {
+                        self.module.core_func(args.malloc)?;
+                        Entry::add(
+                            &mut allocators,
+                            args.malloc,
+                            format!("Allocates strings in memory {} for {}", args.mem, desc),
+                        );
+                    }
+                    _ => {}
+                }
+            }
+        }
+        for implement in self.module.implements.iter() {
+            self.module.core_func(implement.core_func)?;
+            let desc = self.module.describe_adapter(implement.adapter_func)?;
+            Entry::add(
+                &mut funcs,
+                implement.core_func,
+                format!("Implemented by {}", desc),
+            );
+            // Mention what the implementation calls, since that's usually the
+            // interesting interface-level signature.
+            let adapter = &self.module.funcs[implement.adapter_func as usize];
+            if let Some(body) = &adapter.body {
+                for instr in body.instrs() {
+                    if let Instruction::CallAdapter(f) = instr? {
+                        let callee = self.module.describe_adapter(f)?;
+                        Entry::add(
+                            &mut funcs,
+                            implement.core_func,
+                            format!("  which calls {}", callee),
+                        );
+                    }
+                }
+            }
+        }
+        // Allocators get a dedicated section and aren't repeated.
+        funcs.retain(|f| allocators.iter().all(|a| a.func != f.func));
+        funcs.sort_by_key(|f| f.func);
+        allocators.sort_by_key(|f| f.func);
+
+        self.dst.push_str(PRELUDE);
+        if !allocators.is_empty() {
+            self.dst.push_str(
+                "\n// Allocators expected by `string-to-memory`. Each takes the size of a\n",
+            );
+            self.dst.push_str(
+                "// string in bytes and returns a pointer to that many bytes of memory.\n",
+            );
+        }
+        for entry in allocators.iter() {
+            self.entry(entry, true)?;
+        }
+        if !funcs.is_empty() {
+            self.dst.push_str("\n// Core functions used by adapters.\n");
+        }
+        for entry in funcs.iter() {
+            self.entry(entry, false)?;
+        }
+        self.dst.push_str(EPILOGUE);
+        Ok(())
+    }
+
+    fn entry(&mut self, entry: &Entry, allocator: bool) -> Result<()> {
+        let func = self.module.core_func(entry.func)?;
+        let ty = self.module.core_type(func.ty)?;
+        let name = self.claim(c_ident(
+            func.export
+                .or(func.name)
+                .or(func.import.map(|i| i.1))
+                .unwrap_or(""),
+            entry.func,
+        ));
+
+        self.dst.push_str("\n/*\n");
+        writeln!(self.dst, " * Core function {}.", entry.func)?;
+        self.dst.push_str(" *\n");
+        for use_ in entry.uses.iter() {
+            writeln!(self.dst, " * {}", use_)?;
+        }
+        self.dst.push_str(" */\n");
+        // Allocators are declared the same way as `malloc` from the standard
+        // library so they don't conflict with it, and this matches the
+        // `(param i32) (result i32)` signature on wasm32.
+        let ret = match &ty.returns[..] {
+            _ if allocator => "void *",
+            [] => "void",
+            [ty] => c_type(ty),
+            // A C function returning a struct or taking out-pointers wouldn't
+            // have the same core wasm signature.
+            _ => bail!(
+                "core function {} (`{}`) has multiple results, which can't be expressed in C",
+                entry.func,
+                name
+            ),
+        };
+        if let Some((module, name)) = func.import {
+            writeln!(
+                self.dst,
+                "__attribute__((import_module({}), import_name({})))",
+                c_string(module),
+                c_string(name)
+            )?;
+        }
+        if let Some(export) = func.export {
+            writeln!(
+                self.dst,
+                "__attribute__((export_name({})))",
+                c_string(export)
+            )?;
+        }
+
+        let params = if allocator {
+            "size_t size".to_string()
+        } else if ty.params.is_empty() {
+            "void".to_string()
+        } else {
+            ty.params
+                .iter()
+                .enumerate()
+                .map(|(i, p)| format!("{} arg{}", c_type(p), i))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        if ret.ends_with('*') {
+            writeln!(self.dst, "{}{}({});", ret, name, params)?;
+        } else {
+            writeln!(self.dst, "{} {}({});", ret, name, params)?;
+        }
+        Ok(())
+    }
+
+    /// Reserves `ident` for a prototype, adding a numeric suffix if a
+    /// previous prototype already uses it, such as for `env::log` and
+    /// `host::log`.
+    fn claim(&mut self, ident: String) -> String {
+        let mut ret = ident.clone();
+        let mut n = 1;
+        while !self.idents.insert(ret.clone()) {
+            n += 1;
+            ret = format!("{}_{}", ident, n);
+        }
+        ret
+    }
+}
+
+const PRELUDE: &str = "\
+// This file is automatically generated by `wit-bindgen-c`.
+
+#ifndef WIT_BINDINGS_H
+#define WIT_BINDINGS_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern \"C\" {
+#endif
+";
+
+const EPILOGUE: &str = "
+#ifdef __cplusplus
+}
+#endif
+
+#endif
+";
+
+fn c_type(ty: &wasmparser::Type) -> &'static str {
+    match ty {
+        wasmparser::Type::I32 => "int32_t",
+        wasmparser::Type::I64 => "int64_t",
+        wasmparser::Type::F32 => "float",
+        wasmparser::Type::F64 => "double",
+        _ => "void *",
+    }
+}
+
+/// Converts `name` into a valid C identifier, falling back to a name based on
+/// the function's index if it's empty.
+fn c_ident(name: &str, idx: u32) -> String {
+    let mut ret = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+    match ret.chars().next() {
+        None => ret = format!("core_func{}", idx),
+        Some(c) if c.is_ascii_digit() => ret.insert(0, '_'),
+        _ => {}
+    }
+    if KEYWORDS.contains(&&ret[..]) {
+        ret.push('_');
+    }
+    ret
+}
+
+/// Escapes `s` for use inside a `/* ... */` comment, so names can't end the
+/// comment early, nest another one, or break it across lines.
+fn comment_text(s: &str) -> String {
+    s.chars()
+        .map(|c| {
+            if c.is_control() {
+                c.escape_default().to_string()
+            } else {
+                c.to_string()
+            }
+        })
+        .collect::<String>()
+        .replace("*/", "*\\/")
+        .replace("/*", "/\\*")
+}
+
+fn c_string(s: &str) -> String {
+    let mut ret = String::from("\"");
+    for b in s.bytes() {
+        match b {
+            b'"' => ret.push_str("\\\""),
+            b'\\' => ret.push_str("\\\\"),
+            0x20..=0x7e => ret.push(b as char),
+            b => ret.push_str(&format!("\\{:03o}", b)),
+        }
+    }
+    ret.push('"');
+    ret
+}
+
+const KEYWORDS: &[&str] = &[
+    "auto", "break", "case", "char", "const", "continue", "default", "do", "double", "else",
+    "enum", "extern", "float", "for", "goto", "if", "inline", "int", "long", "register",
+    "restrict", "return", "short", "signed", "sizeof", "static", "struct", "switch", "typedef",
+    "union", "unsigned", "void", "volatile", "while",
+];
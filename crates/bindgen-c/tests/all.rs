@@ -0,0 +1,119 @@
+use anyhow::Result;
+
+fn generate(wat: &str) -> Result<String> {
+    let wasm = wit_text::parse_str(wat)?;
+    wit_validator::validate(&wasm)?;
+    wit_bindgen_c::generate(&wasm)
+}
+
+#[test]
+fn header() -> Result<()> {
+    let header = generate(
+        r#"
+            (module
+                (import "env" "log_raw" (func $log_raw (param i32 i32)))
+                (memory 1)
+                (func $alloc (param i32) (result i32)
+                    i32.const 0)
+                (func $greet (export "greet_raw") (param i32 i32) (result i32)
+                    local.get 0)
+                (@interface func (import "host" "log") (param string))
+                (@interface implement (import "env" "log_raw") (param i32) (param i32)
+                    arg.get 0
+                    arg.get 1
+                    memory-to-string
+                    call-adapter 0)
+                (@interface func (export "greet") (param string) (result s32)
+                    arg.get 0
+                    string-to-memory $alloc
+                    call-core $greet
+                    i32-to-s32)
+            )
+        "#,
+    )?;
+    assert!(header.contains(" * Allocates strings in memory 0 for adapter export `greet`: (param string) (result s32)\n"));
+    assert!(header.contains("\nvoid *alloc(size_t size);\n"));
+    assert!(header.contains(
+        "__attribute__((import_module(\"env\"), import_name(\"log_raw\")))\n\
+         void log_raw(int32_t arg0, int32_t arg1);\n"
+    ));
+    assert!(header.contains(" *   which calls adapter import `host` `log`: (param string)\n"));
+    assert!(header.contains(
+        "__attribute__((export_name(\"greet_raw\")))\n\
+         int32_t greet_raw(int32_t arg0, int32_t arg1);\n"
+    ));
+    Ok(())
+}
+
+#[test]
+fn multiple_results() -> Result<()> {
+    let err = generate(
+        r#"
+            (module
+                (func $pair (export "pair") (result i32 i32)
+                    i32.const 0
+                    i32.const 1)
+                (@interface func (export "pair") (result i32) (result i32)
+                    call-core $pair)
+            )
+        "#,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "core function 0 (`pair`) has multiple results, which can't be expressed in C"
+    );
+    Ok(())
+}
+
+#[test]
+fn name_collisions() -> Result<()> {
+    let header = generate(
+        r#"
+            (module
+                (import "env" "log" (func (param i32)))
+                (import "host" "log" (func (param i32)))
+                (func (export "a-b"))
+                (func (export "a_b"))
+                (@interface func (export "f") (param s32)
+                    arg.get 0
+                    s32-to-i32
+                    call-core 0
+                    arg.get 0
+                    s32-to-i32
+                    call-core 1
+                    call-core 2
+                    call-core 3)
+            )
+        "#,
+    )?;
+    assert!(header.contains("\nvoid log(int32_t arg0);\n"), "{}", header);
+    assert!(
+        header.contains("\nvoid log_2(int32_t arg0);\n"),
+        "{}",
+        header
+    );
+    assert!(header.contains("\nvoid a_b(void);\n"), "{}", header);
+    assert!(header.contains("\nvoid a_b_2(void);\n"), "{}", header);
+    Ok(())
+}
+
+#[test]
+fn names_in_comments() -> Result<()> {
+    let header = generate(
+        r#"
+            (module
+                (func $f)
+                (@interface func (export "*/ int x;\n/*")
+                    call-core $f)
+            )
+        "#,
+    )?;
+    assert!(
+        header.contains(" * Called by adapter export `*\\/ int x;\\n/\\*`: (func)\n"),
+        "{}",
+        header
+    );
+    assert_eq!(header.matches("*/").count(), 1, "{}", header);
+    Ok(())
+}
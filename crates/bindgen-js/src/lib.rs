@@ -0,0 +1,724 @@
+//! A crate to generate JavaScript glue and TypeScript typings for WebAssembly
+//! Interface Types.
+//!
+//! Engines don't natively support wasm interface types yet, so this crate
+//! compiles the adapters of a module into JavaScript which runs around the
+//! core `WebAssembly.Instance`. The generated JS module exports an
+//! `instantiate` function which takes a `WebAssembly.Module` along with the
+//! imports of the module, and returns the adapter exports of the module.
+//!
+//! Strings are passed to and from linear memory with `TextEncoder` and
+//! `TextDecoder`, 64-bit integers are represented with `BigInt`, and all other
+//! numeric types are represented with JS numbers.
+
+#![deny(missing_docs)]
+
+use anyhow::{anyhow, bail, Context, Result};
+use std::collections::HashSet;
+use std::fmt::Write;
+use wasmparser::{ExternalKind, FuncType, ImportSectionEntryType, Payload, TypeDef};
+use wit_parser::*;
+
+/// The output of [`generate`].
+#[derive(Debug, Clone, Default)]
+pub struct Bindings {
+    /// The generated JavaScript glue, an ES module.
+    pub js: String,
+    /// TypeScript typings for the generated JavaScript, suitable for a `.d.ts`
+    /// file.
+    pub ts: String,
+}
+
+/// Generates JavaScript glue and TypeScript typings for the wasm module `wasm`.
+///
+/// The `wasm` given must be an entire WebAssembly module. Core wasm indices in
+/// the interface types section are resolved the same way `wit-validator`
+/// resolves them, with imports coming first in each index space.
+///
+/// # Errors
+///
+/// Returns an error if the module fails to parse, if the interface types
+/// section is malformed, or if an adapter refers to a core function or memory
+/// which JavaScript can't reach. Core functions and memories used by adapters
+/// must either be imported or exported from the core module. Note that full
+/// validation isn't performed here, so it's recommended to run
+/// `wit_validator::validate` first.
+pub fn generate(wasm: &[u8]) -> Result<Bindings> {
+    let mut module = ModuleInfo::default();
+    for payload in wasmparser::Parser::new(0).parse_all(wasm) {
+        match payload? {
+            Payload::TypeSection(s) => {
+                for ty in s {
+                    if let TypeDef::Func(ty) = ty? {
+                        module.core_types.push(ty);
+                    }
+                }
+            }
+            Payload::ImportSection(s) => {
+                for import in s {
+                    let import = import?;
+                    let field = import.field.unwrap_or("");
+                    let kind = match import.ty {
+                        ImportSectionEntryType::Function(ty) => {
+                            module.core_funcs.push(CoreItem {
+                                ty,
+                                import: Some((import.module, field)),
+                                export: None,
+                            });
+                            CoreImportKind::Func(ty)
+                        }
+                        ImportSectionEntryType::Memory(_) => {
+                            module.memories.push(CoreItem {
+                                ty: 0,
+                                import: Some((import.module, field)),
+                                export: None,
+                            });
+                            CoreImportKind::Memory
+                        }
+                        ImportSectionEntryType::Table(_) => CoreImportKind::Table,
+                        ImportSectionEntryType::Global(_) => CoreImportKind::Global,
+                        _ => CoreImportKind::Other,
+                    };
+                    module.core_imports.push((import.module, field, kind));
+                }
+            }
+            Payload::FunctionSection(s) => {
+                for ty in s {
+                    module.core_funcs.push(CoreItem {
+                        ty: ty?,
+                        import: None,
+                        export: None,
+                    });
+                }
+            }
+            Payload::MemorySection(s) => {
+                for mem in s {
+                    mem?;
+                    module.memories.push(CoreItem {
+                        ty: 0,
+                        import: None,
+                        export: None,
+                    });
+                }
+            }
+            Payload::ExportSection(s) => {
+                for export in s {
+                    let export = export?;
+                    let item = match export.kind {
+                        ExternalKind::Function => module.core_funcs.get_mut(export.index as usize),
+                        ExternalKind::Memory => module.memories.get_mut(export.index as usize),
+                        _ => None,
+                    };
+                    if let Some(item) = item {
+                        if item.export.is_none() {
+                            item.export = Some(export.field);
+                        }
+                    }
+                }
+            }
+            Payload::CustomSection {
+                name: wit_schema_version::SECTION_NAME,
+                data,
+                data_offset,
+            } => {
+                module
+                    .parse_wit(data_offset, data)
+                    .context("failed to parse interface types section")?;
+            }
+            _ => {}
+        }
+    }
+
+    let mut gen = Generator {
+        module: &module,
+        bindings: Bindings::default(),
+    };
+    gen.generate()?;
+    Ok(gen.bindings)
+}
+
+/// The parts of a module that the generated glue needs to know about.
+#[derive(Default)]
+struct ModuleInfo<'a> {
+    core_types: Vec<FuncType>,
+    core_funcs: Vec<CoreItem<'a>>,
+    memories: Vec<CoreItem<'a>>,
+    core_imports: Vec<(&'a str, &'a str, CoreImportKind)>,
+    types: Vec<Type>,
+    funcs: Vec<Adapter<'a>>,
+    exports: Vec<Export<'a>>,
+    implements: Vec<Implement>,
+}
+
+/// A core wasm function or memory, along with how JS can reach it.
+struct CoreItem<'a> {
+    ty: u32,
+    import: Option<(&'a str, &'a str)>,
+    export: Option<&'a str>,
+}
+
+#[derive(Copy, Clone)]
+enum CoreImportKind {
+    Func(u32),
+    Memory,
+    Table,
+    Global,
+    Other,
+}
+
+struct Adapter<'a> {
+    ty: u32,
+    kind: AdapterKind<'a>,
+}
+
+enum AdapterKind<'a> {
+    Import(&'a str, &'a str),
+    Local(Func<'a>),
+}
+
+impl<'a> ModuleInfo<'a> {
+    fn parse_wit(&mut self, offset: usize, bytes: &'a [u8]) -> Result<()> {
+        let mut parser = Parser::new(offset, bytes)?;
+        while !parser.is_empty() {
+            match parser.section()? {
+                Section::Type(types) => {
+                    for ty in types {
+                        self.types.push(ty?);
+                    }
+                }
+                Section::Import(imports) => {
+                    for import in imports {
+                        let import = import?;
+                        self.funcs.push(Adapter {
+                            ty: import.ty,
+                            kind: AdapterKind::Import(import.module, import.name),
+                        });
+                    }
+                }
+                Section::Func(funcs) => {
+                    for func in funcs {
+                        let func = func?;
+                        self.funcs.push(Adapter {
+                            ty: func.ty,
+                            kind: AdapterKind::Local(func),
+                        });
+                    }
+                }
+                Section::Export(exports) => {
+                    for export in exports {
+                        self.exports.push(export?);
+                    }
+                }
+                Section::Implement(implements) => {
+                    for implement in implements {
+                        self.implements.push(implement?);
+                    }
+                }
+                Section::ParamNames(_) | Section::Unknown { .. } => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn ty(&self, idx: u32) -> Result<&Type> {
+        self.types
+            .get(idx as usize)
+            .ok_or_else(|| anyhow!("adapter type index out of bounds: {}", idx))
+    }
+
+    fn adapter(&self, idx: u32) -> Result<&Adapter<'a>> {
+        self.funcs
+            .get(idx as usize)
+            .ok_or_else(|| anyhow!("adapter func index out of bounds: {}", idx))
+    }
+
+    fn core_func(&self, idx: u32) -> Result<&CoreItem<'a>> {
+        self.core_funcs
+            .get(idx as usize)
+            .ok_or_else(|| anyhow!("func index out of bounds: {}", idx))
+    }
+
+    fn core_type(&self, idx: u32) -> Result<&FuncType> {
+        self.core_types
+            .get(idx as usize)
+            .ok_or_else(|| anyhow!("type index out of bounds: {}", idx))
+    }
+
+    /// Returns the adapter function implementing the core function import
+    /// `module`/`name`, if any.
+    fn implementation(&self, module: &str, name: &str) -> Option<u32> {
+        self.implements.iter().find_map(|i| {
+            match self.core_funcs.get(i.core_func as usize)?.import {
+                Some((m, n)) if m == module && n == name => Some(i.adapter_func),
+                _ => None,
+            }
+        })
+    }
+}
+
+struct Generator<'a, 'b> {
+    module: &'b ModuleInfo<'a>,
+    bindings: Bindings,
+}
+
+impl Generator<'_, '_> {
+    fn generate(&mut self) -> Result<()> {
+        self.bindings.js.push_str(JS_PRELUDE);
+        self.bindings
+            .js
+            .push_str("\nexport async function instantiate(module, imports) {\n  let instance;\n");
+        for (i, adapter) in self.module.funcs.iter().enumerate() {
+            match &adapter.kind {
+                AdapterKind::Import(module, name) => {
+                    writeln!(
+                        self.bindings.js,
+                        "  const adapter{} = imports[{}][{}];",
+                        i,
+                        js_string(module),
+                        js_string(name),
+                    )?;
+                }
+                AdapterKind::Local(func) => {
+                    let ty = self.module.ty(adapter.ty)?;
+                    self.adapter(i, ty, func)
+                        .with_context(|| format!("failed to generate adapter function {}", i))?;
+                }
+            }
+        }
+
+        // Core imports are either implemented by an adapter function or
+        // forwarded straight through from the imports given to `instantiate`.
+        self.bindings.js.push_str("  const coreImports = {\n");
+        for (module, imports) in group(&self.module.core_imports, |i| i.0) {
+            writeln!(self.bindings.js, "    {}: {{", js_string(module))?;
+            for (_, name, _) in imports {
+                match self.module.implementation(module, name) {
+                    Some(f) => {
+                        writeln!(self.bindings.js, "      {}: adapter{},", js_string(name), f)?
+                    }
+                    None => writeln!(
+                        self.bindings.js,
+                        "      {}: imports[{}][{}],",
+                        js_string(name),
+                        js_string(module),
+                        js_string(name),
+                    )?,
+                }
+            }
+            self.bindings.js.push_str("    },\n");
+        }
+        self.bindings.js.push_str("  };\n");
+        self.bindings
+            .js
+            .push_str("  instance = await WebAssembly.instantiate(module, coreImports);\n");
+        self.bindings.js.push_str("  return {\n");
+        for export in self.module.exports.iter() {
+            self.module.adapter(export.func)?;
+            writeln!(
+                self.bindings.js,
+                "    {}: adapter{},",
+                js_string(export.name),
+                export.func
+            )?;
+        }
+        self.bindings.js.push_str("  };\n}\n");
+
+        self.typings()
+    }
+
+    fn adapter(&mut self, idx: usize, ty: &Type, func: &Func<'_>) -> Result<()> {
+        let mut body = Body {
+            module: self.module,
+            js: String::new(),
+            stack: Vec::new(),
+            deferred: false,
+            tmp: 0,
+        };
+        for instr in func.instrs() {
+            body.instr(instr?)?;
+        }
+        if body.stack.len() != ty.results.len() {
+            bail!("value stack doesn't match the function's results on exit");
+        }
+
+        let params = (0..ty.params.len())
+            .map(|i| format!("arg{}", i))
+            .collect::<Vec<_>>();
+        writeln!(
+            self.bindings.js,
+            "  function adapter{}({}) {{",
+            idx,
+            params.join(", ")
+        )?;
+        match body.stack.len() {
+            0 => {}
+            1 => writeln!(body.js, "    return {};", body.stack[0])?,
+            _ => writeln!(body.js, "    return [{}];", body.stack.join(", "))?,
+        }
+        if body.deferred {
+            // Deferred calls, like freeing memory, must run even if the rest
+            // of the body throws.
+            self.bindings.js.push_str("    const deferred = [];\n");
+            self.bindings.js.push_str("    try {\n");
+            for line in body.js.lines() {
+                writeln!(self.bindings.js, "  {}", line)?;
+            }
+            self.bindings.js.push_str("    } finally {\n");
+            self.bindings
+                .js
+                .push_str("      for (const f of deferred) f();\n");
+            self.bindings.js.push_str("    }\n");
+        } else {
+            self.bindings.js.push_str(&body.js);
+        }
+        self.bindings.js.push_str("  }\n");
+        Ok(())
+    }
+
+    fn typings(&mut self) -> Result<()> {
+        let ts = &mut self.bindings.ts;
+        ts.push_str("// This file is automatically generated by `wit-bindgen-js`.\n\n");
+
+        // The imports of the module are its adapter imports plus the core
+        // imports which aren't implemented by an adapter.
+        let mut imports = Vec::new();
+        for adapter in self.module.funcs.iter() {
+            if let AdapterKind::Import(module, name) = adapter.kind {
+                let ty = self.module.ty(adapter.ty)?;
+                imports.push((module, name, signature(&ty.params, &ty.results, ts_type)));
+            }
+        }
+        for (module, name, kind) in self.module.core_imports.iter() {
+            if self.module.implementation(module, name).is_some() {
+                continue;
+            }
+            let ty = match kind {
+                CoreImportKind::Func(ty) => {
+                    let ty = self.module.core_type(*ty)?;
+                    signature(&ty.params, &ty.returns, core_ts_type)
+                }
+                CoreImportKind::Memory => ": WebAssembly.Memory".to_string(),
+                CoreImportKind::Table => ": WebAssembly.Table".to_string(),
+                CoreImportKind::Global => ": WebAssembly.Global".to_string(),
+                CoreImportKind::Other => ": any".to_string(),
+            };
+            imports.push((module, name, ty));
+        }
+
+        ts.push_str("export interface Imports {\n");
+        for (module, items) in group(&imports, |i| i.0) {
+            writeln!(ts, "  {}: {{", js_string(module))?;
+            let mut seen = HashSet::new();
+            for (_, name, ty) in items {
+                if seen.insert(*name) {
+                    writeln!(ts, "    {}{};", js_string(name), ty)?;
+                }
+            }
+            ts.push_str("  };\n");
+        }
+        ts.push_str("}\n\n");
+
+        ts.push_str("export interface Exports {\n");
+        for export in self.module.exports.iter() {
+            let ty = self.module.ty(self.module.adapter(export.func)?.ty)?;
+            writeln!(
+                ts,
+                "  {}{};",
+                js_string(export.name),
+                signature(&ty.params, &ty.results, ts_type)
+            )?;
+        }
+        ts.push_str("}\n\n");
+
+        ts.push_str(
+            "export function instantiate(module: WebAssembly.Module, imports: Imports): \
+             Promise<Exports>;\n",
+        );
+        Ok(())
+    }
+}
+
+/// Translation state for the body of one adapter function.
+///
+/// Adapter bodies are straight-line code, so each instruction is translated
+/// into at most a few JS statements, with the wasm value stack being tracked
+/// at compile time as a list of JS expressions.
+struct Body<'a, 'b> {
+    module: &'b ModuleInfo<'a>,
+    js: String,
+    stack: Vec<String>,
+    /// Whether any calls are deferred until the end of the body.
+    deferred: bool,
+    tmp: u32,
+}
+
+impl Body<'_, '_> {
+    fn instr(&mut self, instr: Instruction) -> Result<()> {
+        use Instruction::*;
+        match instr {
+            ArgGet(idx) => self.stack.push(format!("arg{}", idx)),
+            CallCore(idx) => {
+                let ty = self.module.core_type(self.module.core_func(idx)?.ty)?;
+                let (params, results) = (ty.params.len(), ty.returns.len());
+                let callee = self.core_func(idx)?;
+                self.call(callee, params, results)?;
+            }
+            CallAdapter(idx) => {
+                let ty = self.module.ty(self.module.adapter(idx)?.ty)?;
+                let (params, results) = (ty.params.len(), ty.results.len());
+                self.call(format!("adapter{}", idx), params, results)?;
+            }
+            DeferCallCore(idx) => {
+                let ty = self.module.core_type(self.module.core_func(idx)?.ty)?;
+                let n = ty.params.len();
+                if n > self.stack.len() {
+                    bail!("value stack underflow");
+                }
+                // The arguments to a deferred call stay on the stack.
+                let args = self.stack[self.stack.len() - n..].join(", ");
+                let callee = self.core_func(idx)?;
+                writeln!(self.js, "    deferred.push(() => {}({}));", callee, args)?;
+                self.deferred = true;
+            }
+            // Pointers and lengths are `i32`s, which JS sees as signed, so
+            // they're made unsigned to address memory above 2GiB.
+            MemoryToString(mem) => {
+                let mem = self.memory(mem)?;
+                let len = self.pop()?;
+                let ptr = self.pop()?;
+                self.push(format!(
+                    "utf8Decoder.decode(new Uint8Array({}.buffer, {} >>> 0, {} >>> 0))",
+                    mem, ptr, len
+                ))?;
+            }
+            StringToMemory(args) => {
+                let mem = self.memory(args.mem)?;
+                let malloc = self.core_func(args.malloc)?;
+                let s = self.pop()?;
+                let bytes = self.tmp();
+                let ptr = self.tmp();
+                writeln!(self.js, "    const {} = utf8Encoder.encode({});", bytes, s)?;
+                writeln!(self.js, "    const {} = {}({}.length);", ptr, malloc, bytes)?;
+                writeln!(
+                    self.js,
+                    "    new Uint8Array({}.buffer, {} >>> 0, {}.length).set({});",
+                    mem, ptr, bytes, bytes
+                )?;
+                self.stack.push(ptr);
+                self.stack.push(format!("{}.length", bytes));
+            }
+            End => bail!("extra `end` instruction found"),
+            Extension(ext) => bail!("unsupported extension instruction: {}", ext.opcode),
+
+            I32ToS8 => self.convert(|x| format!("({} << 24) >> 24", x))?,
+            I32ToS8X => self.convert(|x| format!("checkRange({}, -0x80, 0x7f)", x))?,
+            I32ToU8 => self.convert(|x| format!("{} & 0xff", x))?,
+            I32ToS16 => self.convert(|x| format!("({} << 16) >> 16", x))?,
+            I32ToS16X => self.convert(|x| format!("checkRange({}, -0x8000, 0x7fff)", x))?,
+            I32ToU16 => self.convert(|x| format!("{} & 0xffff", x))?,
+            I32ToS32 => self.convert(|x| x.to_string())?,
+            I32ToU32 => self.convert(|x| format!("{} >>> 0", x))?,
+            I32ToS64 => self.convert(|x| format!("BigInt({})", x))?,
+            I32ToU64 => self.convert(|x| format!("BigInt({} >>> 0)", x))?,
+
+            I64ToS8 => self.convert(|x| format!("Number(BigInt.asIntN(8, {}))", x))?,
+            I64ToS8X => self.convert(|x| format!("Number(checkRange({}, -0x80n, 0x7fn))", x))?,
+            I64ToU8 => self.convert(|x| format!("Number(BigInt.asUintN(8, {}))", x))?,
+            I64ToS16 => self.convert(|x| format!("Number(BigInt.asIntN(16, {}))", x))?,
+            I64ToS16X => {
+                self.convert(|x| format!("Number(checkRange({}, -0x8000n, 0x7fffn))", x))?
+            }
+            I64ToU16 => self.convert(|x| format!("Number(BigInt.asUintN(16, {}))", x))?,
+            I64ToS32 => self.convert(|x| format!("Number(BigInt.asIntN(32, {}))", x))?,
+            I64ToS32X => {
+                self.convert(|x| format!("Number(checkRange({}, -0x80000000n, 0x7fffffffn))", x))?
+            }
+            I64ToU32 => self.convert(|x| format!("Number(BigInt.asUintN(32, {}))", x))?,
+            I64ToS64 => self.convert(|x| x.to_string())?,
+            I64ToU64 => self.convert(|x| format!("BigInt.asUintN(64, {})", x))?,
+
+            S8ToI32 | U8ToI32 | S16ToI32 | U16ToI32 | S32ToI32 | U32ToI32 => {
+                self.convert(|x| format!("{} | 0", x))?
+            }
+            S64ToI32 | U64ToI32 => self.convert(|x| format!("Number(BigInt.asIntN(32, {}))", x))?,
+            S64ToI32X => {
+                self.convert(|x| format!("Number(checkRange({}, -0x80000000n, 0x7fffffffn))", x))?
+            }
+            U64ToI32X => self.convert(|x| {
+                format!(
+                    "Number(BigInt.asIntN(32, checkRange({}, 0n, 0xffffffffn)))",
+                    x
+                )
+            })?,
+
+            S8ToI64 | U8ToI64 | S16ToI64 | U16ToI64 | S32ToI64 | U32ToI64 => {
+                self.convert(|x| format!("BigInt({})", x))?
+            }
+            S64ToI64 | U64ToI64 => self.convert(|x| format!("BigInt.asIntN(64, {})", x))?,
+        }
+        Ok(())
+    }
+
+    fn call(&mut self, callee: String, params: usize, results: usize) -> Result<()> {
+        if params > self.stack.len() {
+            bail!("value stack underflow");
+        }
+        let args = self.stack.split_off(self.stack.len() - params).join(", ");
+        let call = format!("{}({})", callee, args);
+        match results {
+            0 => writeln!(self.js, "    {};", call)?,
+            1 => self.push(call)?,
+            n => {
+                let tmps = (0..n).map(|_| self.tmp()).collect::<Vec<_>>();
+                writeln!(self.js, "    const [{}] = {};", tmps.join(", "), call)?;
+                self.stack.extend(tmps);
+            }
+        }
+        Ok(())
+    }
+
+    fn convert(&mut self, f: impl FnOnce(&str) -> String) -> Result<()> {
+        let val = self.pop()?;
+        self.push(f(&val))
+    }
+
+    /// Evaluates `expr` into a fresh temporary and pushes it onto the stack,
+    /// preserving the order of side effects.
+    fn push(&mut self, expr: String) -> Result<()> {
+        let tmp = self.tmp();
+        writeln!(self.js, "    const {} = {};", tmp, expr)?;
+        self.stack.push(tmp);
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Result<String> {
+        self.stack
+            .pop()
+            .ok_or_else(|| anyhow!("value stack underflow"))
+    }
+
+    fn tmp(&mut self) -> String {
+        self.tmp += 1;
+        format!("t{}", self.tmp - 1)
+    }
+
+    fn core_func(&self, idx: u32) -> Result<String> {
+        let func = self.module.core_func(idx)?;
+        if let Some((module, name)) = func.import {
+            return Ok(match self.module.implementation(module, name) {
+                Some(f) => format!("adapter{}", f),
+                None => format!("imports[{}][{}]", js_string(module), js_string(name)),
+            });
+        }
+        match func.export {
+            Some(name) => Ok(format!("instance.exports[{}]", js_string(name))),
+            None => bail!(
+                "core function {} must be imported or exported to be called from JS",
+                idx
+            ),
+        }
+    }
+
+    fn memory(&self, idx: u32) -> Result<String> {
+        let mem = self
+            .module
+            .memories
+            .get(idx as usize)
+            .ok_or_else(|| anyhow!("memory index out of bounds: {}", idx))?;
+        if let Some((module, name)) = mem.import {
+            return Ok(format!(
+                "imports[{}][{}]",
+                js_string(module),
+                js_string(name)
+            ));
+        }
+        match mem.export {
+            Some(name) => Ok(format!("instance.exports[{}]", js_string(name))),
+            None => bail!(
+                "memory {} must be imported or exported to be used from JS",
+                idx
+            ),
+        }
+    }
+}
+
+const JS_PRELUDE: &str = "\
+// This file is automatically generated by `wit-bindgen-js`.
+
+const utf8Decoder = new TextDecoder('utf-8');
+const utf8Encoder = new TextEncoder();
+
+function checkRange(val, min, max) {
+  if (val < min || val > max)
+    throw new WebAssembly.RuntimeError('integer overflow');
+  return val;
+}
+";
+
+/// Groups `items` by the key returned from `key`, preserving the order in
+/// which each key first shows up.
+fn group<T>(items: &[T], key: impl Fn(&T) -> &str) -> Vec<(&str, Vec<&T>)> {
+    let mut ret = Vec::<(&str, Vec<&T>)>::new();
+    for item in items {
+        let k = key(item);
+        match ret.iter_mut().find(|(m, _)| *m == k) {
+            Some((_, list)) => list.push(item),
+            None => ret.push((key(item), vec![item])),
+        }
+    }
+    ret
+}
+
+fn signature<T>(params: &[T], results: &[T], ty: fn(&T) -> &'static str) -> String {
+    let params = params
+        .iter()
+        .enumerate()
+        .map(|(i, p)| format!("arg{}: {}", i, ty(p)))
+        .collect::<Vec<_>>();
+    let results = match results {
+        [] => "void".to_string(),
+        [r] => ty(r).to_string(),
+        rs => format!("[{}]", rs.iter().map(ty).collect::<Vec<_>>().join(", ")),
+    };
+    format!("({}): {}", params.join(", "), results)
+}
+
+fn ts_type(ty: &ValType) -> &'static str {
+    match ty {
+        ValType::S8
+        | ValType::S16
+        | ValType::S32
+        | ValType::U8
+        | ValType::U16
+        | ValType::U32
+        | ValType::F32
+        | ValType::F64
+        | ValType::I32 => "number",
+        ValType::S64 | ValType::U64 | ValType::I64 => "bigint",
+        ValType::String => "string",
+        ValType::Externref => "any",
+    }
+}
+
+fn core_ts_type(ty: &wasmparser::Type) -> &'static str {
+    match ty {
+        wasmparser::Type::I32 | wasmparser::Type::F32 | wasmparser::Type::F64 => "number",
+        wasmparser::Type::I64 => "bigint",
+        _ => "any",
+    }
+}
+
+/// Renders `s` as a double-quoted JS string literal.
+fn js_string(s: &str) -> String {
+    let mut ret = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => ret.push_str("\\\""),
+            '\\' => ret.push_str("\\\\"),
+            c if c.is_control() => ret.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            c => ret.push(c),
+        }
+    }
+    ret.push('"');
+    ret
+}
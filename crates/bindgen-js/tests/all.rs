@@ -0,0 +1,152 @@
+use anyhow::Result;
+
+fn generate(wat: &str) -> Result<wit_bindgen_js::Bindings> {
+    let wasm = wit_text::parse_str(wat)?;
+    wit_validator::validate(&wasm)?;
+    wit_bindgen_js::generate(&wasm)
+}
+
+#[test]
+fn strings() -> Result<()> {
+    let bindings = generate(
+        r#"
+            (module
+                (import "env" "log_raw" (func $log_raw (param i32 i32)))
+                (memory (export "memory") 1)
+                (func $malloc (export "malloc") (param i32) (result i32)
+                    i32.const 0)
+                (func $greet (export "greet_raw") (param i32 i32) (result i32 i32)
+                    local.get 0
+                    local.get 1)
+                (@interface func (import "host" "log") (param string))
+                (@interface implement (import "env" "log_raw") (param i32) (param i32)
+                    arg.get 0
+                    arg.get 1
+                    memory-to-string
+                    call-adapter 0)
+                (@interface func (export "greet") (param string) (result string)
+                    arg.get 0
+                    string-to-memory $malloc
+                    call-core $greet
+                    memory-to-string)
+            )
+        "#,
+    )?;
+    let js = &bindings.js;
+    assert!(js.contains("const adapter0 = imports[\"host\"][\"log\"];"));
+    assert!(js.contains("\"log_raw\": adapter1,"));
+    assert!(js.contains("const t1 = instance.exports[\"malloc\"](t0.length);"));
+    assert!(js.contains(
+        "const t4 = utf8Decoder.decode(new Uint8Array(instance.exports[\"memory\"].buffer, t2 >>> 0, t3 >>> 0));"
+    ));
+    assert!(js.contains("\"greet\": adapter2,"));
+
+    let ts = &bindings.ts;
+    assert!(ts.contains("    \"log\"(arg0: string): void;\n"));
+    assert!(!ts.contains("log_raw"));
+    assert!(ts.contains("  \"greet\"(arg0: string): string;\n"));
+    Ok(())
+}
+
+#[test]
+fn unexported_core_func() -> Result<()> {
+    let err = generate(
+        r#"
+            (module
+                (func $f)
+                (@interface func (export "f")
+                    call-core $f)
+            )
+        "#,
+    )
+    .unwrap_err();
+    assert!(format!("{:?}", err).contains("core function 0 must be imported or exported"));
+    Ok(())
+}
+
+#[test]
+fn run_in_node() -> Result<()> {
+    let wasm = wit_text::parse_str(
+        r#"
+            (module
+                (memory (export "memory") 32769)
+                (global $next (mut i32) (i32.const 0x80000000))
+                (global $freed (mut i32) (i32.const 0))
+                (func $malloc (export "malloc") (param i32) (result i32)
+                    global.get $next
+                    global.get $next
+                    local.get 0
+                    i32.add
+                    global.set $next)
+                (func $free (export "free") (param i32 i32)
+                    global.get $freed
+                    i32.const 1
+                    i32.add
+                    global.set $freed)
+                (func $echo (export "echo_raw") (param i32 i32) (result i32 i32)
+                    local.get 0
+                    local.get 1)
+                (func $fail (export "fail_raw") (param i32 i32)
+                    unreachable)
+                (func $freed (export "freed_raw") (result i32)
+                    global.get $freed)
+                (@interface func (export "echo") (param string) (result string)
+                    arg.get 0
+                    string-to-memory $malloc
+                    call-core $echo
+                    memory-to-string)
+                (@interface func (export "fail") (param string)
+                    arg.get 0
+                    string-to-memory $malloc
+                    defer-call-core $free
+                    call-core $fail)
+                (@interface func (export "freed") (result s32)
+                    call-core $freed
+                    i32-to-s32)
+            )
+        "#,
+    )?;
+    wit_validator::validate(&wasm)?;
+    let bindings = wit_bindgen_js::generate(&wasm)?;
+
+    let dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("run_in_node");
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(dir.join("module.wasm"), &wasm)?;
+    std::fs::write(dir.join("bindings.mjs"), &bindings.js)?;
+    std::fs::write(
+        dir.join("main.mjs"),
+        r#"
+            import { readFileSync } from 'fs';
+            import { instantiate } from './bindings.mjs';
+
+            const module = new WebAssembly.Module(readFileSync(new URL('./module.wasm', import.meta.url)));
+            const exports = await instantiate(module, {});
+            console.log(exports.echo('héllo'));
+            try {
+                exports.fail('x');
+            } catch (e) {
+                console.log(e instanceof WebAssembly.RuntimeError);
+            }
+            console.log(exports.freed());
+        "#,
+    )?;
+    let output = match std::process::Command::new("node")
+        .arg(dir.join("main.mjs"))
+        .output()
+    {
+        Ok(output) => output,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            eprintln!("skipping test, `node` wasn't found");
+            return Ok(());
+        }
+        Err(e) => return Err(e.into()),
+    };
+    assert!(
+        output.status.success(),
+        "node failed:\n{}\n{}",
+        String::from_utf8_lossy(&output.stderr),
+        bindings.js
+    );
+    assert_eq!(String::from_utf8(output.stdout)?, "h\u{e9}llo\ntrue\n1\n");
+    Ok(())
+}
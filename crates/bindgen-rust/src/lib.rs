@@ -0,0 +1,560 @@
+//! A crate to generate Rust host bindings for WebAssembly Interface Types.
+//!
+//! This crate reads the wasm interface types section of a module and generates
+//! Rust source code for the interface of that module. Adapter imports are
+//! turned into a trait per import module which the host must implement, along
+//! with a function to dispatch dynamic calls to that trait. Adapter exports are
+//! turned into typed wrapper functions which call an adapter export through
+//! the generated `Exports` trait.
+//!
+//! The generated code is self-contained and doesn't depend on any particular
+//! wasm runtime. Runtimes are expected to convert between the generated
+//! `Value` type and their own representation of interface values.
+
+#![deny(missing_docs)]
+
+use anyhow::{anyhow, bail, Context, Result};
+use std::collections::HashMap;
+use std::fmt::Write;
+use wasmparser::Payload;
+use wit_parser::*;
+
+/// Generates Rust source code for the interface of the wasm module `wasm`.
+///
+/// The `wasm` given must be an entire WebAssembly module, and the adapters of
+/// its `wasm-interface-types` custom section are used to generate bindings. If
+/// the module has no interface types section then only the supporting type
+/// definitions are generated.
+///
+/// # Errors
+///
+/// Returns an error if the module fails to parse, if the interface types
+/// section refers to adapter types or functions which don't exist, or if two
+/// distinct names map to the same Rust identifier. Note that
+/// full validation isn't performed here, so it's recommended to run
+/// `wit_validator::validate` first.
+pub fn generate(wasm: &[u8]) -> Result<String> {
+    let mut interface = Interface::default();
+    for payload in wasmparser::Parser::new(0).parse_all(wasm) {
+        if let Payload::CustomSection {
+            name: wit_schema_version::SECTION_NAME,
+            data,
+            data_offset,
+        } = payload?
+        {
+            interface
+                .parse(data_offset, data)
+                .context("failed to parse interface types section")?;
+        }
+    }
+
+    let mut gen = Generator::default();
+    gen.generate(&interface)?;
+    Ok(gen.dst)
+}
+
+/// The adapter-facing signatures of a module, read from its interface types
+/// section.
+#[derive(Default)]
+struct Interface<'a> {
+    types: Vec<Type>,
+    funcs: Vec<u32>,
+    imports: Vec<Import<'a>>,
+    exports: Vec<Export<'a>>,
+}
+
+impl<'a> Interface<'a> {
+    fn parse(&mut self, offset: usize, bytes: &'a [u8]) -> Result<()> {
+        let mut parser = Parser::new(offset, bytes)?;
+        while !parser.is_empty() {
+            match parser.section()? {
+                Section::Type(types) => {
+                    for ty in types {
+                        self.types.push(ty?);
+                    }
+                }
+                Section::Import(imports) => {
+                    for import in imports {
+                        let import = import?;
+                        self.funcs.push(import.ty);
+                        self.imports.push(import);
+                    }
+                }
+                Section::Func(funcs) => {
+                    for func in funcs {
+                        self.funcs.push(func?.ty);
+                    }
+                }
+                Section::Export(exports) => {
+                    for export in exports {
+                        self.exports.push(export?);
+                    }
+                }
+                Section::Implement(implements) => {
+                    for implement in implements {
+                        implement?;
+                    }
+                }
+                Section::ParamNames(_) | Section::Unknown { .. } => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn ty(&self, idx: u32) -> Result<&Type> {
+        self.types
+            .get(idx as usize)
+            .ok_or_else(|| anyhow!("adapter type index out of bounds: {}", idx))
+    }
+
+    fn func_ty(&self, idx: u32) -> Result<&Type> {
+        let ty = self
+            .funcs
+            .get(idx as usize)
+            .ok_or_else(|| anyhow!("adapter func index out of bounds: {}", idx))?;
+        self.ty(*ty)
+    }
+}
+
+#[derive(Default)]
+struct Generator {
+    dst: String,
+    /// The names of generated types and traits, and what they were generated
+    /// for.
+    types: HashMap<String, String>,
+    /// The names of generated functions, and what they were generated for.
+    funcs: HashMap<String, String>,
+}
+
+impl Generator {
+    fn generate(&mut self, interface: &Interface<'_>) -> Result<()> {
+        self.dst.push_str(PRELUDE);
+        claim(&mut self.types, "Value".to_string(), "the `Value` type")?;
+        if !interface.exports.is_empty() {
+            claim(
+                &mut self.types,
+                "Exports".to_string(),
+                "the `Exports` trait",
+            )?;
+        }
+
+        // Group imports by their module, preserving the order in which each
+        // module first shows up.
+        let mut modules = Vec::<(&str, Vec<&Import<'_>>)>::new();
+        for import in interface.imports.iter() {
+            match modules.iter_mut().find(|(m, _)| *m == import.module) {
+                Some((_, list)) => list.push(import),
+                None => modules.push((import.module, vec![import])),
+            }
+        }
+        for (module, imports) in modules {
+            self.import_module(interface, module, &imports)?;
+        }
+
+        if !interface.exports.is_empty() {
+            self.dst.push_str(EXPORTS);
+        }
+        for export in interface.exports.iter() {
+            let ty = interface.func_ty(export.func)?;
+            self.export(export.name, ty)?;
+        }
+        Ok(())
+    }
+
+    fn import_module(
+        &mut self,
+        interface: &Interface<'_>,
+        module: &str,
+        imports: &[&Import<'_>],
+    ) -> Result<()> {
+        let name = to_camel_case(module);
+        claim(
+            &mut self.types,
+            name.clone(),
+            format!("import module `{}`", module),
+        )?;
+        let dispatch = finish_ident(format!("dispatch_{}", snake_case(module)));
+        claim(
+            &mut self.funcs,
+            dispatch.clone(),
+            format!("import module `{}`", module),
+        )?;
+        // Names come from the binary and are escaped so they can't end the doc
+        // comments they're written into.
+        writeln!(self.dst)?;
+        writeln!(
+            self.dst,
+            "/// Adapter functions imported from the `{}` module, which the host must",
+            module.escape_debug()
+        )?;
+        writeln!(self.dst, "/// provide.")?;
+        writeln!(self.dst, "pub trait {} {{", name)?;
+        let mut methods = HashMap::new();
+        for import in imports {
+            let ty = interface.ty(import.ty)?;
+            let method = to_snake_case(import.name);
+            let what = format!("import `{}` of module `{}`", import.name, module);
+            claim(&mut methods, method.clone(), what)?;
+            writeln!(
+                self.dst,
+                "    /// The `{}` import.",
+                import.name.escape_debug()
+            )?;
+            write!(self.dst, "    fn {}(&mut self", method)?;
+            self.params(&ty.params);
+            self.dst.push(')');
+            self.results(&ty.results);
+            self.dst.push_str(";\n");
+        }
+        self.dst.push_str("}\n");
+
+        writeln!(self.dst)?;
+        writeln!(
+            self.dst,
+            "/// Dispatches a call of the import `name` from the `{}` module to `host`.",
+            module.escape_debug()
+        )?;
+        writeln!(self.dst, "///")?;
+        writeln!(
+            self.dst,
+            "/// Returns `None` if `name` isn't an import from this module."
+        )?;
+        writeln!(
+            self.dst,
+            "pub fn {}(host: &mut impl {}, name: &str, args: Vec<Value>) -> Option<Vec<Value>> {{",
+            dispatch, name,
+        )?;
+        // Only bind the arguments if they're used, to avoid warnings in the
+        // generated code.
+        let mut has_params = false;
+        for import in imports {
+            has_params |= !interface.ty(import.ty)?.params.is_empty();
+        }
+        if has_params {
+            self.dst.push_str("    let mut args = args.into_iter();\n");
+        } else {
+            self.dst.push_str("    drop(args);\n");
+        }
+        self.dst.push_str("    match name {\n");
+        for import in imports {
+            let ty = interface.ty(import.ty)?;
+            writeln!(self.dst, "        {:?} => {{", import.name)?;
+            for (i, param) in ty.params.iter().enumerate() {
+                writeln!(
+                    self.dst,
+                    "            let arg{} = match args.next() {{ Some(Value::{}(v)) => v, _ => panic!(\"invalid argument {} to `{}`\") }};",
+                    i,
+                    variant(param),
+                    i,
+                    import.name,
+                )?;
+            }
+            write!(
+                self.dst,
+                "            let ret = host.{}(",
+                to_snake_case(import.name)
+            )?;
+            for (i, param) in ty.params.iter().enumerate() {
+                if i > 0 {
+                    self.dst.push_str(", ");
+                }
+                if let ValType::String = param {
+                    self.dst.push('&');
+                }
+                write!(self.dst, "arg{}", i)?;
+            }
+            self.dst.push_str(");\n");
+            self.wrap_results("ret", &ty.results, "            ")?;
+            self.dst.push_str("        }\n");
+        }
+        self.dst.push_str("        _ => None,\n");
+        self.dst.push_str("    }\n");
+        self.dst.push_str("}\n");
+        Ok(())
+    }
+
+    fn wrap_results(&mut self, ret: &str, results: &[ValType], indent: &str) -> Result<()> {
+        match results {
+            [] => writeln!(
+                self.dst,
+                "{}let () = {};\n{}Some(Vec::new())",
+                indent, ret, indent
+            )?,
+            [ty] => writeln!(
+                self.dst,
+                "{}Some(vec![Value::{}({})])",
+                indent,
+                variant(ty),
+                ret
+            )?,
+            tys => {
+                write!(self.dst, "{}Some(vec![", indent)?;
+                for (i, ty) in tys.iter().enumerate() {
+                    if i > 0 {
+                        self.dst.push_str(", ");
+                    }
+                    write!(self.dst, "Value::{}({}.{})", variant(ty), ret, i)?;
+                }
+                self.dst.push_str("])\n");
+            }
+        }
+        Ok(())
+    }
+
+    fn export(&mut self, name: &str, ty: &Type) -> Result<()> {
+        let func = to_snake_case(name);
+        claim(&mut self.funcs, func.clone(), format!("export `{}`", name))?;
+        writeln!(self.dst)?;
+        writeln!(
+            self.dst,
+            "/// Calls the `{}` adapter export.",
+            name.escape_debug()
+        )?;
+        write!(self.dst, "pub fn {}(instance: &mut impl Exports", func)?;
+        self.params(&ty.params);
+        self.dst.push(')');
+        self.results(&ty.results);
+        self.dst.push_str(" {\n");
+
+        write!(self.dst, "    let args = vec![")?;
+        for (i, param) in ty.params.iter().enumerate() {
+            if i > 0 {
+                self.dst.push_str(", ");
+            }
+            match param {
+                ValType::String => write!(self.dst, "Value::String(arg{}.to_string())", i)?,
+                _ => write!(self.dst, "Value::{}(arg{})", variant(param), i)?,
+            }
+        }
+        self.dst.push_str("];\n");
+        if ty.results.is_empty() {
+            writeln!(self.dst, "    instance.call_export({:?}, args);", name)?;
+        } else {
+            writeln!(
+                self.dst,
+                "    let mut results = instance.call_export({:?}, args).into_iter();",
+                name
+            )?;
+        }
+        for (i, result) in ty.results.iter().enumerate() {
+            writeln!(
+                self.dst,
+                "    let ret{} = match results.next() {{ Some(Value::{}(v)) => v, _ => panic!(\"invalid result {} from `{}`\") }};",
+                i,
+                variant(result),
+                i,
+                name,
+            )?;
+        }
+        match ty.results.len() {
+            0 => {}
+            1 => self.dst.push_str("    ret0\n"),
+            n => {
+                self.dst.push_str("    (");
+                for i in 0..n {
+                    if i > 0 {
+                        self.dst.push_str(", ");
+                    }
+                    write!(self.dst, "ret{}", i)?;
+                }
+                self.dst.push_str(")\n");
+            }
+        }
+        self.dst.push_str("}\n");
+        Ok(())
+    }
+
+    fn params(&mut self, params: &[ValType]) {
+        for (i, param) in params.iter().enumerate() {
+            let ty = match param {
+                ValType::String => "&str",
+                other => rust_type(other),
+            };
+            self.dst.push_str(&format!(", arg{}: {}", i, ty));
+        }
+    }
+
+    fn results(&mut self, results: &[ValType]) {
+        match results {
+            [] => {}
+            [ty] => {
+                self.dst.push_str(" -> ");
+                self.dst.push_str(rust_type(ty));
+            }
+            tys => {
+                self.dst.push_str(" -> (");
+                for (i, ty) in tys.iter().enumerate() {
+                    if i > 0 {
+                        self.dst.push_str(", ");
+                    }
+                    self.dst.push_str(rust_type(ty));
+                }
+                self.dst.push(')');
+            }
+        }
+    }
+}
+
+const PRELUDE: &str = "\
+// This file is automatically generated by `wit-bindgen-rust`.
+
+/// A value passed to or returned from an adapter function.
+#[derive(Debug)]
+pub enum Value {
+    S8(i8),
+    S16(i16),
+    S32(i32),
+    S64(i64),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    F32(f32),
+    F64(f64),
+    String(String),
+    Externref(Box<dyn std::any::Any>),
+    I32(i32),
+    I64(i64),
+}
+";
+
+const EXPORTS: &str = "
+/// An instance of the module, used to call its adapter exports.
+pub trait Exports {
+    /// Calls the adapter export `name` with `args`, returning its results.
+    fn call_export(&mut self, name: &str, args: Vec<Value>) -> Vec<Value>;
+}
+";
+
+fn rust_type(ty: &ValType) -> &'static str {
+    match ty {
+        ValType::S8 => "i8",
+        ValType::S16 => "i16",
+        ValType::S32 => "i32",
+        ValType::S64 => "i64",
+        ValType::U8 => "u8",
+        ValType::U16 => "u16",
+        ValType::U32 => "u32",
+        ValType::U64 => "u64",
+        ValType::F32 => "f32",
+        ValType::F64 => "f64",
+        ValType::String => "String",
+        ValType::Externref => "Box<dyn std::any::Any>",
+        ValType::I32 => "i32",
+        ValType::I64 => "i64",
+    }
+}
+
+fn variant(ty: &ValType) -> &'static str {
+    match ty {
+        ValType::S8 => "S8",
+        ValType::S16 => "S16",
+        ValType::S32 => "S32",
+        ValType::S64 => "S64",
+        ValType::U8 => "U8",
+        ValType::U16 => "U16",
+        ValType::U32 => "U32",
+        ValType::U64 => "U64",
+        ValType::F32 => "F32",
+        ValType::F64 => "F64",
+        ValType::String => "String",
+        ValType::Externref => "Externref",
+        ValType::I32 => "I32",
+        ValType::I64 => "I64",
+    }
+}
+
+/// Converts an arbitrary import or export name into a valid Rust identifier in
+/// `snake_case`.
+fn to_snake_case(name: &str) -> String {
+    finish_ident(snake_case(name))
+}
+
+fn snake_case(name: &str) -> String {
+    let mut ret = String::new();
+    let mut prev_lower = false;
+    for c in name.chars() {
+        if c.is_ascii_uppercase() {
+            if prev_lower {
+                ret.push('_');
+            }
+            ret.push(c.to_ascii_lowercase());
+            prev_lower = false;
+        } else if c.is_ascii_alphanumeric() {
+            ret.push(c);
+            prev_lower = true;
+        } else {
+            // Runs of separators become a single `_`, since `__` in the
+            // middle of a name trips the `non_snake_case` lint.
+            if !ret.ends_with('_') {
+                ret.push('_');
+            }
+            prev_lower = false;
+        }
+    }
+    ret
+}
+
+/// Converts an arbitrary module name into a valid Rust identifier in
+/// `CamelCase`.
+fn to_camel_case(name: &str) -> String {
+    let mut ret = String::new();
+    let mut upper = true;
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            if upper {
+                ret.push(c.to_ascii_uppercase());
+            } else {
+                ret.push(c);
+            }
+            upper = false;
+        } else {
+            upper = true;
+        }
+    }
+    finish_ident(ret)
+}
+
+fn finish_ident(mut ret: String) -> String {
+    match ret.chars().next() {
+        None => ret.push_str("__"),
+        Some(c) if c.is_ascii_digit() => ret.insert(0, '_'),
+        _ if ret == "_" => ret.push('_'),
+        _ => {}
+    }
+    if KEYWORDS.contains(&&ret[..]) {
+        ret.insert_str(0, "r#");
+    } else if RESERVED.contains(&&ret[..]) {
+        ret.push('_');
+    }
+    ret
+}
+
+/// Records that the generated Rust name `ident` is used for `what`, failing if
+/// it's already used for something else in the same namespace.
+fn claim(
+    names: &mut HashMap<String, String>,
+    ident: String,
+    what: impl Into<String>,
+) -> Result<()> {
+    let what = what.into();
+    if let Some(prev) = names.get(&ident) {
+        bail!(
+            "{} and {} both map to the Rust name `{}`",
+            prev,
+            what,
+            ident
+        );
+    }
+    names.insert(ident, what);
+    Ok(())
+}
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern", "false",
+    "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref",
+    "return", "static", "struct", "trait", "true", "type", "unsafe", "use", "where", "while",
+];
+
+/// Keywords which can't be used as raw identifiers either.
+const RESERVED: &[&str] = &["crate", "self", "Self", "super"];
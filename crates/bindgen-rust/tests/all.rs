@@ -0,0 +1,125 @@
+use anyhow::Result;
+
+fn generate(wat: &str) -> Result<String> {
+    let wasm = wit_text::parse_str(wat)?;
+    wit_validator::validate(&wasm)?;
+    wit_bindgen_rust::generate(&wasm)
+}
+
+#[test]
+fn imports_and_exports() -> Result<()> {
+    let rust = generate(
+        r#"
+            (module
+                (@interface func (import "my-host" "log") (param string))
+                (@interface func (import "my-host" "now") (result u64) (result s32))
+                (@interface func (export "type") (param s8) (result s8)
+                    arg.get 0)
+            )
+        "#,
+    )?;
+    assert!(rust.contains("pub trait MyHost {"));
+    assert!(rust.contains("    fn log(&mut self, arg0: &str);\n"));
+    assert!(rust.contains("    fn now(&mut self) -> (u64, i32);\n"));
+    assert!(rust.contains(
+        "pub fn dispatch_my_host(host: &mut impl MyHost, name: &str, args: Vec<Value>) -> Option<Vec<Value>> {"
+    ));
+    assert!(rust.contains("pub trait Exports {"));
+    assert!(rust.contains("pub fn r#type(instance: &mut impl Exports, arg0: i8) -> i8 {"));
+    Ok(())
+}
+
+#[test]
+fn no_section() -> Result<()> {
+    let rust = generate("(module)")?;
+    assert!(rust.contains("pub enum Value {"));
+    assert!(!rust.contains("pub trait Exports"));
+    Ok(())
+}
+
+#[test]
+fn name_collisions() -> Result<()> {
+    let err = generate(
+        r#"
+            (module
+                (@interface func (import "my-host" "a"))
+                (@interface func (import "my_host" "b"))
+            )
+        "#,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "import module `my-host` and import module `my_host` both map to the Rust name `MyHost`"
+    );
+
+    let err = generate(
+        r#"
+            (module
+                (@interface func (import "host" "a"))
+                (@interface func (export "dispatch-host")
+                    call-adapter 0)
+            )
+        "#,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "import module `host` and export `dispatch-host` both map to the Rust name `dispatch_host`"
+    );
+
+    let err = generate(r#"(module (@interface func (import "value" "a")))"#).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "the `Value` type and import module `value` both map to the Rust name `Value`"
+    );
+    Ok(())
+}
+
+#[test]
+fn generated_code_compiles() -> Result<()> {
+    let rust = generate(
+        r#"
+            (module
+                (@interface func (import "self" "crate") (param string) (param externref))
+                (@interface func (import "self" "now") (result u64) (result s32))
+                (@interface func (import "super" "tick"))
+                (@interface func (export "Self"))
+                (@interface func (export "type") (param s8) (result s8)
+                    arg.get 0)
+                (@interface func (export "pair") (param f32) (param f64) (result f32) (result f64)
+                    arg.get 0
+                    arg.get 1)
+                (@interface func (import "a\nfn x() {}" "b\n}\nfn y() {"))
+                (@interface func (export "c\r\npub fn z() {}"))
+            )
+        "#,
+    )?;
+    assert!(rust.contains("pub trait Self_ {"));
+    assert!(rust.contains("    fn crate_(&mut self, arg0: &str, arg1: Box<dyn std::any::Any>);\n"));
+    assert!(rust.contains("pub fn dispatch_super(host: &mut impl Super, "));
+    assert!(rust.contains("    /// The `b\\n}\\nfn y() {` import.\n"));
+    assert!(rust.contains("/// Calls the `c\\r\\npub fn z() {}` adapter export.\n"));
+    assert!(!rust.contains("\nfn x()") && !rust.contains("\npub fn z()"));
+
+    let dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("generated_code_compiles");
+    std::fs::create_dir_all(&dir)?;
+    let src = dir.join("bindings.rs");
+    std::fs::write(&src, &rust)?;
+    let output = std::process::Command::new("rustc")
+        .arg("--crate-type=lib")
+        .arg("--edition=2018")
+        .arg("--emit=metadata")
+        .arg("-Dwarnings")
+        .arg("--out-dir")
+        .arg(&dir)
+        .arg(&src)
+        .output()?;
+    assert!(
+        output.status.success(),
+        "failed to compile generated code:\n{}\n{}",
+        String::from_utf8_lossy(&output.stderr),
+        rust
+    );
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use anyhow::{bail, Context};
+use getopts::Options;
+use std::env;
+use wasmparser::Payload;
+
+fn main() -> anyhow::Result<()> {
+    let mut opts = Options::new();
+    opts.optopt("o", "", "set output file name", "NAME");
+    opts.optflag("h", "help", "print this help menu");
+    opts.optopt(
+        "",
+        "encode",
+        "treat FILE as JSON and append it to this core module",
+        "CORE",
+    );
+    let matches = opts.parse(env::args_os().skip(1))?;
+    if matches.opt_present("h") {
+        print_usage(opts);
+        return Ok(());
+    }
+    let input = if matches.free.len() == 1 {
+        matches.free[0].clone()
+    } else {
+        print_usage(opts);
+        std::process::exit(1);
+    };
+
+    let output = match matches.opt_str("encode") {
+        Some(core) => {
+            let json = std::fs::read(&input).context(format!("failed to read `{}`", input))?;
+            let module: wit_ir::Module =
+                serde_json::from_slice(&json).context("failed to parse JSON")?;
+            let mut wasm = wit_text::parse_file(&core)?;
+            if section(&wasm)?.is_some() {
+                bail!("`{}` already has an interface types section", core);
+            }
+            wasm.extend_from_slice(&module.to_custom_section());
+            wasm
+        }
+        None => {
+            let wasm = wit_text::parse_file(&input)?;
+            let module = match section(&wasm)? {
+                Some(data) => wit_ir::Module::from_bytes(data)?,
+                None => wit_ir::Module::default(),
+            };
+            let mut json = serde_json::to_vec_pretty(&module)?;
+            json.push(b'\n');
+            json
+        }
+    };
+
+    match matches.opt_str("o") {
+        Some(path) => std::fs::write(&path, output).context(format!("failed to write `{}`", path)),
+        None if matches.opt_present("encode") => bail!("`-o` is required with `--encode`"),
+        None => {
+            print!("{}", String::from_utf8(output)?);
+            Ok(())
+        }
+    }
+}
+
+fn section(wasm: &[u8]) -> anyhow::Result<Option<&[u8]>> {
+    for payload in wasmparser::Parser::new(0).parse_all(wasm) {
+        if let Payload::CustomSection {
+            name: wit_schema_version::SECTION_NAME,
+            data,
+            ..
+        } = payload?
+        {
+            return Ok(Some(data));
+        }
+    }
+    Ok(None)
+}
+
+fn print_usage(opts: Options) {
+    let program = env::args().next().unwrap();
+    let brief = format!("Usage: {} FILE [options]", program);
+    print!("{}", opts.usage(&brief));
+}
@@ -0,0 +1,340 @@
+//! An owned, in-memory representation of the wasm interface types section.
+//!
+//! Unlike `wit-parser`, which is a streaming reader, and `wit-walrus`, which
+//! is integrated with all of `walrus`, this crate is a plain list of each
+//! subsection's items which refer to each other by index. It's intended for
+//! lightweight tools which want to inspect or edit adapters and then write
+//! them back out.
+//!
+//! With the `serde` feature enabled every type here can also be serialized,
+//! for example to JSON. Field names match this crate's, value types are
+//! lowercase strings like `"s32"`, and instructions are named as in the text
+//! format, like `"i32-to-s8"` or `{"arg.get": 0}`.
+//!
+//! Everything here uses the same index spaces as the binary format, so
+//! nothing is renumbered when items are added or removed; it's up to callers
+//! to keep indices consistent.
+
+#![deny(missing_docs)]
+
+use anyhow::{Context, Result};
+
+pub use wit_parser::{Extension, Instruction, StringToMemory, ValType};
+
+/// The contents of a wasm interface types section.
+///
+/// Subsections are listed in the order they're encoded in.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+pub struct Module {
+    /// Type signatures, referenced by index from imports and functions.
+    pub types: Vec<Type>,
+    /// Imported adapter functions, which come first in the function index
+    /// space.
+    pub imports: Vec<Import>,
+    /// Locally defined adapter functions, which come after imports in the
+    /// function index space.
+    pub funcs: Vec<Func>,
+    /// Adapter functions exported from the module.
+    pub exports: Vec<Export>,
+    /// Adapter functions used to implement core imports.
+    pub implements: Vec<Implement>,
+    /// Names of parameters of adapter functions.
+    pub param_names: Vec<ParamName>,
+    /// Subsections which aren't understood by this crate, in the order they
+    /// were read.
+    pub unknown: Vec<UnknownSection>,
+}
+
+/// A type signature of adapter functions.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(deny_unknown_fields)
+)]
+pub struct Type {
+    /// The types of each parameter.
+    pub params: Vec<ValType>,
+    /// The types of each result.
+    pub results: Vec<ValType>,
+}
+
+/// An adapter function imported from the host.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(deny_unknown_fields)
+)]
+pub struct Import {
+    /// The module being imported from.
+    pub module: String,
+    /// The name of the item being imported.
+    pub name: String,
+    /// Index of the type signature of this import.
+    pub ty: u32,
+}
+
+/// An adapter function defined in this module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(deny_unknown_fields)
+)]
+pub struct Func {
+    /// Index of the type signature of this function.
+    pub ty: u32,
+    /// The body of this function, not including the trailing `end`.
+    pub instrs: Vec<Instruction>,
+}
+
+/// An export of an adapter function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(deny_unknown_fields)
+)]
+pub struct Export {
+    /// The name this function is exported under.
+    pub name: String,
+    /// Index of the adapter function being exported.
+    pub func: u32,
+}
+
+/// A directive to implement a core import with an adapter function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(deny_unknown_fields)
+)]
+pub struct Implement {
+    /// Index of the imported core function being implemented.
+    pub core_func: u32,
+    /// Index of the adapter function implementing it.
+    pub adapter_func: u32,
+}
+
+/// The name of a parameter of an adapter function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(deny_unknown_fields)
+)]
+pub struct ParamName {
+    /// Index of the adapter function.
+    pub func: u32,
+    /// Index of the parameter within the function.
+    pub param: u32,
+    /// The name of the parameter, without a leading `$`.
+    pub name: String,
+}
+
+/// A subsection with an id that isn't known, preserved verbatim.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(deny_unknown_fields)
+)]
+pub struct UnknownSection {
+    /// The id of this subsection.
+    pub id: u8,
+    /// The raw contents of this subsection.
+    pub bytes: Vec<u8>,
+    /// The id of the known subsection this one was read before, or `None` if
+    /// it came after all of them.
+    ///
+    /// This subsection is written back out just before that subsection would
+    /// be, even if it's empty and omitted.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub before: Option<u8>,
+}
+
+impl Module {
+    /// Parses the payload of a `wasm-interface-types` custom section.
+    ///
+    /// A section is re-encoded exactly by [`Module::to_bytes`] so long as its
+    /// known subsections are non-empty, appear at most once, and are in order
+    /// of their ids, as is the case for sections returned from
+    /// [`Module::to_bytes`]. Unknown subsections keep their position relative
+    /// to known ones.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Module> {
+        let mut parser =
+            wit_parser::Parser::new(0, bytes).context("failed to parse interface types header")?;
+        let mut ret = Module::default();
+        let mut pending = 0;
+        while !parser.is_empty() {
+            let section = parser.section().context("failed to read section header")?;
+            let known = match &section {
+                wit_parser::Section::Type(_) => Some(0),
+                wit_parser::Section::Import(_) => Some(1),
+                wit_parser::Section::Func(_) => Some(2),
+                wit_parser::Section::Export(_) => Some(3),
+                wit_parser::Section::Implement(_) => Some(4),
+                wit_parser::Section::ParamNames(_) => Some(5),
+                wit_parser::Section::Unknown { .. } => None,
+            };
+            // Unknown subsections read since the last known one are anchored
+            // to this one.
+            if let Some(id) = known {
+                for unknown in ret.unknown[pending..].iter_mut() {
+                    unknown.before = Some(id);
+                }
+                pending = ret.unknown.len();
+            }
+            match section {
+                wit_parser::Section::Type(s) => {
+                    for ty in s {
+                        let ty = ty?;
+                        ret.types.push(Type {
+                            params: ty.params,
+                            results: ty.results,
+                        });
+                    }
+                }
+                wit_parser::Section::Import(s) => {
+                    for import in s {
+                        let import = import?;
+                        ret.imports.push(Import {
+                            module: import.module.to_string(),
+                            name: import.name.to_string(),
+                            ty: import.ty,
+                        });
+                    }
+                }
+                wit_parser::Section::Func(s) => {
+                    for func in s {
+                        let func = func?;
+                        ret.funcs.push(Func {
+                            ty: func.ty,
+                            instrs: func.instrs().collect::<Result<_, _>>()?,
+                        });
+                    }
+                }
+                wit_parser::Section::Export(s) => {
+                    for export in s {
+                        let export = export?;
+                        ret.exports.push(Export {
+                            name: export.name.to_string(),
+                            func: export.func,
+                        });
+                    }
+                }
+                wit_parser::Section::Implement(s) => {
+                    for implement in s {
+                        let implement = implement?;
+                        ret.implements.push(Implement {
+                            core_func: implement.core_func,
+                            adapter_func: implement.adapter_func,
+                        });
+                    }
+                }
+                wit_parser::Section::ParamNames(s) => {
+                    for name in s {
+                        let name = name?;
+                        ret.param_names.push(ParamName {
+                            func: name.func,
+                            param: name.param,
+                            name: name.name.to_string(),
+                        });
+                    }
+                }
+                wit_parser::Section::Unknown { id, bytes } => {
+                    ret.unknown.push(UnknownSection {
+                        id,
+                        bytes: bytes.to_vec(),
+                        before: None,
+                    });
+                }
+            }
+        }
+        Ok(ret)
+    }
+
+    /// Encodes this module as the payload of a `wasm-interface-types` custom
+    /// section.
+    ///
+    /// Empty subsections are omitted.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.write().into_payload()
+    }
+
+    /// Encodes this module as a full `wasm-interface-types` custom section,
+    /// suitable for appending to a core wasm module.
+    pub fn to_custom_section(&self) -> Vec<u8> {
+        self.write().into_custom_section()
+    }
+
+    fn write(&self) -> wit_writer::Writer {
+        let mut writer = wit_writer::Writer::new();
+
+        self.write_unknown(&mut writer, Some(0));
+        let mut w = writer.types(self.types.len() as u32);
+        for ty in self.types.iter() {
+            w.add(
+                ty.params.len() as u32,
+                |w| ty.params.iter().for_each(|t| w.val_type(t)),
+                ty.results.len() as u32,
+                |w| ty.results.iter().for_each(|t| w.val_type(t)),
+            );
+        }
+        drop(w);
+
+        self.write_unknown(&mut writer, Some(1));
+        let mut w = writer.imports(self.imports.len() as u32);
+        for import in self.imports.iter() {
+            w.add(&import.module, &import.name, import.ty);
+        }
+        drop(w);
+
+        self.write_unknown(&mut writer, Some(2));
+        let mut w = writer.funcs(self.funcs.len() as u32);
+        for func in self.funcs.iter() {
+            let mut w = w.add(func.ty);
+            for instr in func.instrs.iter() {
+                w.instr(instr);
+            }
+        }
+        drop(w);
+
+        self.write_unknown(&mut writer, Some(3));
+        let mut w = writer.exports(self.exports.len() as u32);
+        for export in self.exports.iter() {
+            w.add(&export.name, export.func);
+        }
+        drop(w);
+
+        self.write_unknown(&mut writer, Some(4));
+        let mut w = writer.implements(self.implements.len() as u32);
+        for implement in self.implements.iter() {
+            w.add(implement.core_func, implement.adapter_func);
+        }
+        drop(w);
+
+        self.write_unknown(&mut writer, Some(5));
+        let mut w = writer.param_names(self.param_names.len() as u32);
+        for name in self.param_names.iter() {
+            w.add(name.func, name.param, &name.name);
+        }
+        drop(w);
+
+        self.write_unknown(&mut writer, None);
+
+        writer
+    }
+
+    fn write_unknown(&self, writer: &mut wit_writer::Writer, before: Option<u8>) {
+        for section in self.unknown.iter().filter(|s| s.before == before) {
+            writer.raw_section(section.id, &section.bytes);
+        }
+    }
+}
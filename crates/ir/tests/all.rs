@@ -0,0 +1,124 @@
+use wasmparser::Payload;
+use wit_ir::*;
+
+/// Returns the payload of the interface types section of `wasm`, if any.
+fn section(wasm: &[u8]) -> Option<&[u8]> {
+    wasmparser::Parser::new(0)
+        .parse_all(wasm)
+        .find_map(|payload| match payload.unwrap() {
+            Payload::CustomSection {
+                name: wit_schema_version::SECTION_NAME,
+                data,
+                ..
+            } => Some(data),
+            _ => None,
+        })
+}
+
+/// Fixtures in the top-level `tests` directory whose interface types section
+/// should round-trip through a `Module`.
+const ROUND_TRIP: &[&str] = &[
+    "anyref.wat",
+    "bad-export.wat",
+    "bad-export2.wat",
+    "bad-extension.wat",
+    "bad-implement.wat",
+    "bad-implement2.wat",
+    "bad-implement3.wat",
+    "bad-implement4.wat",
+    "bad-implement5.wat",
+    "bad-implement6.wat",
+    "bad-implement7.wat",
+    "bad-memory-to-string2.wat",
+    "bad-memory-to-string3.wat",
+    "bad-memory-to-string4.wat",
+    "bad-memory-to-string5.wat",
+    "bad-section.wat",
+    "bad-string-to-memory.wat",
+    "call.wat",
+    "convert.wat",
+    "defer-call.wat",
+    "export.wat",
+    "extension.wat",
+    "folded.wat",
+    "func-bad-ty.wat",
+    "func.wat",
+    "i32-vs-s32.wat",
+    "implement.wat",
+    "import-bad-ty.wat",
+    "imports.wat",
+    "lots.wat",
+    "memory-and-string.wat",
+    "memory-import.wat",
+    "multi-value.wat",
+    "no-wrapping-module.wat",
+    "param-names.wat",
+    "string-to-memory.wat",
+    "strings-in-imports.wat",
+    "two-sections.wat",
+    "typeck1.wat",
+    "typeck2.wat",
+    "typeck3.wat",
+    "typeck4.wat",
+    "types.wat",
+];
+
+#[test]
+fn round_trip_test_suite() {
+    for file in ROUND_TRIP {
+        let path = std::path::Path::new("../../tests").join(file);
+        let wasm = wit_text::parse_file(&path).unwrap();
+        let data = section(&wasm).unwrap_or_else(|| panic!("no section in {:?}", path));
+        let module = Module::from_bytes(data).unwrap();
+        assert_eq!(module.to_bytes(), data, "failed to round-trip {:?}", path);
+        assert_eq!(Module::from_bytes(&module.to_bytes()).unwrap(), module);
+    }
+}
+
+#[test]
+fn unknown_section_order() {
+    let mut writer = wit_writer::Writer::new();
+    writer.raw_section(0x7f, &[1]);
+    writer.types(1).add(0, |_| {}, 0, |_| {});
+    writer.raw_section(0x7e, &[2]);
+    writer.funcs(1).add(0);
+    writer.raw_section(0x7d, &[3]);
+    let data = writer.into_payload();
+
+    let mut module = Module::from_bytes(&data).unwrap();
+    let before = module.unknown.iter().map(|s| s.before).collect::<Vec<_>>();
+    assert_eq!(before, [Some(0), Some(2), None]);
+    assert_eq!(module.to_bytes(), data);
+
+    // Unknown subsections stay put even if their neighbors are emptied.
+    module.funcs.clear();
+    let module2 = Module::from_bytes(&module.to_bytes()).unwrap();
+    let ids = module2.unknown.iter().map(|s| s.id).collect::<Vec<_>>();
+    assert_eq!(ids, [0x7f, 0x7e, 0x7d]);
+}
+
+#[test]
+fn edit_section() {
+    let wasm = wit_text::parse_str(
+        r#"
+            (module
+                (@interface func (export "a") (param $x s32) (result s32)
+                    arg.get $x))
+        "#,
+    )
+    .unwrap();
+    let mut module = Module::from_bytes(section(&wasm).unwrap()).unwrap();
+    assert_eq!(module.types.len(), 1);
+    assert_eq!(module.funcs[0].instrs, [Instruction::ArgGet(0)]);
+    assert_eq!(module.param_names[0].name, "x");
+
+    module.exports[0].name = "b".to_string();
+    module.funcs.push(Func {
+        ty: 0,
+        instrs: vec![Instruction::ArgGet(0), Instruction::CallAdapter(0)],
+    });
+
+    let module2 = Module::from_bytes(&module.to_bytes()).unwrap();
+    assert_eq!(module2.exports[0].name, "b");
+    assert_eq!(module2, module);
+}
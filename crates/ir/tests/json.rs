@@ -0,0 +1,87 @@
+#![cfg(feature = "serde")]
+
+use wit_ir::*;
+
+#[test]
+fn json_schema() {
+    let module = Module {
+        types: vec![Type {
+            params: vec![ValType::String],
+            results: vec![ValType::S32],
+        }],
+        imports: vec![Import {
+            module: "host".to_string(),
+            name: "len".to_string(),
+            ty: 0,
+        }],
+        funcs: vec![Func {
+            ty: 0,
+            instrs: vec![
+                Instruction::ArgGet(0),
+                Instruction::CallAdapter(0),
+                Instruction::StringToMemory(StringToMemory { malloc: 1, mem: 0 }),
+                Instruction::I32ToS8X,
+            ],
+        }],
+        exports: vec![Export {
+            name: "len".to_string(),
+            func: 1,
+        }],
+        implements: vec![Implement {
+            core_func: 0,
+            adapter_func: 1,
+        }],
+        param_names: vec![ParamName {
+            func: 1,
+            param: 0,
+            name: "s".to_string(),
+        }],
+        unknown: vec![UnknownSection {
+            id: 0x7f,
+            bytes: vec![1, 2],
+            before: Some(5),
+        }],
+    };
+    let json = serde_json::to_value(&module).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "types": [{ "params": ["string"], "results": ["s32"] }],
+            "imports": [{ "module": "host", "name": "len", "ty": 0 }],
+            "funcs": [{
+                "ty": 0,
+                "instrs": [
+                    { "arg.get": 0 },
+                    { "call-adapter": 0 },
+                    { "string-to-memory": { "malloc": 1, "mem": 0 } },
+                    "i32-to-s8x",
+                ],
+            }],
+            "exports": [{ "name": "len", "func": 1 }],
+            "implements": [{ "core_func": 0, "adapter_func": 1 }],
+            "param_names": [{ "func": 1, "param": 0, "name": "s" }],
+            "unknown": [{ "id": 127, "bytes": [1, 2], "before": 5 }],
+        })
+    );
+    let module2: Module = serde_json::from_value(json).unwrap();
+    assert_eq!(module2, module);
+    assert_eq!(Module::from_bytes(&module2.to_bytes()).unwrap(), module);
+}
+
+#[test]
+fn missing_sections_default_to_empty() {
+    let module: Module = serde_json::from_str(r#"{ "types": [] }"#).unwrap();
+    assert_eq!(module, Module::default());
+    assert!(serde_json::from_str::<Module>(r#"{ "typos": [] }"#).is_err());
+}
+
+#[test]
+fn end_is_not_an_instruction() {
+    assert_eq!(
+        serde_json::to_value(&Instruction::End).unwrap(),
+        serde_json::json!("end")
+    );
+    assert!(serde_json::from_str::<Instruction>(r#""end""#).is_err());
+    let func = r#"{ "ty": 0, "instrs": ["end"] }"#;
+    assert!(serde_json::from_str::<Func>(func).is_err());
+}
@@ -20,6 +20,41 @@ use std::str;
 pub struct Parser<'a> {
     bytes: &'a [u8],
     pos: usize,
+    config: ParserConfig,
+    subsections: u32,
+}
+
+/// Limits on the size of what a [`Parser`] will accept, used to bound the
+/// resources spent parsing untrusted input.
+///
+/// Limits are opt-in: a [`Parser`] created with [`Parser::new`] has none, and
+/// one created with [`ParserConfig::parser`] enforces these.
+///
+/// Exceeding any of these limits produces an [`Error`] for which
+/// [`Error::limit`] returns the [`Limit`] that was hit.
+#[derive(Clone, Copy, Debug)]
+pub struct ParserConfig {
+    max_subsections: u32,
+    max_items: u32,
+    max_type_arity: u32,
+    max_instructions: u32,
+    max_string_len: u32,
+}
+
+/// The limits of a [`ParserConfig`], one of which is returned from
+/// [`Error::limit`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Limit {
+    /// The number of subsections in a section.
+    Subsections,
+    /// The number of items in a single subsection.
+    Items,
+    /// The number of parameters or results of a type signature.
+    TypeArity,
+    /// The number of instructions in a single function.
+    Instructions,
+    /// The length, in bytes, of a string.
+    StringLen,
 }
 
 /// Errors that can happen during parsing.
@@ -44,11 +79,83 @@ enum ErrorKind {
     UlebInvalid,
     UnexpectedEof,
     InvalidUtf8,
-    InvalidSection(u8),
     InvalidValType(u8),
     InvalidInstruction(u8),
     Expected(usize),
     TrailingBytes,
+    LimitExceeded(Limit, u32),
+}
+
+impl Default for ParserConfig {
+    fn default() -> ParserConfig {
+        ParserConfig {
+            max_subsections: 100,
+            max_items: 1_000_000,
+            max_type_arity: 1_000,
+            max_instructions: 1_000_000,
+            max_string_len: 100_000,
+        }
+    }
+}
+
+impl ParserConfig {
+    /// Creates a new configuration with the default limits.
+    pub fn new() -> ParserConfig {
+        ParserConfig::default()
+    }
+
+    /// The maximum number of subsections in a section, including unknown
+    /// subsections. Defaults to 100.
+    pub fn max_subsections(&mut self, max: u32) -> &mut ParserConfig {
+        self.max_subsections = max;
+        self
+    }
+
+    /// The maximum number of items, such as types or functions, listed in a
+    /// single subsection. Defaults to 1,000,000.
+    pub fn max_items(&mut self, max: u32) -> &mut ParserConfig {
+        self.max_items = max;
+        self
+    }
+
+    /// The maximum number of parameters, and separately of results, in a type
+    /// signature. Defaults to 1,000.
+    pub fn max_type_arity(&mut self, max: u32) -> &mut ParserConfig {
+        self.max_type_arity = max;
+        self
+    }
+
+    /// The maximum number of instructions in a single function, not counting
+    /// the trailing `end`. Defaults to 1,000,000.
+    pub fn max_instructions(&mut self, max: u32) -> &mut ParserConfig {
+        self.max_instructions = max;
+        self
+    }
+
+    /// The maximum length in bytes of strings such as import and export names.
+    /// Defaults to 100,000.
+    pub fn max_string_len(&mut self, max: u32) -> &mut ParserConfig {
+        self.max_string_len = max;
+        self
+    }
+
+    /// Creates a new [`Parser`] with these limits.
+    ///
+    /// See [`Parser::new`] for more information.
+    pub fn parser<'a>(&self, offset: usize, bytes: &'a [u8]) -> Result<Parser<'a>> {
+        let mut parser = Parser {
+            bytes,
+            pos: offset,
+            config: *self,
+            subsections: 0,
+        };
+        let version = <&str as Parse>::parse(&mut parser)?;
+        if version != wit_schema_version::VERSION {
+            parser.pos = 0;
+            return Err(parser.error(ErrorKind::InvalidVersion(version.to_string())));
+        }
+        Ok(parser)
+    }
 }
 
 impl<'a> Parser<'a> {
@@ -60,14 +167,18 @@ impl<'a> Parser<'a> {
     ///
     /// The `offset` argument is the offset in which `bytes` was found in the
     /// original binary file, used to generate error messages.
+    ///
+    /// No limits are placed on the size of what's parsed, see
+    /// [`ParserConfig::parser`] to configure them.
     pub fn new(offset: usize, bytes: &'a [u8]) -> Result<Parser<'a>> {
-        let mut parser = Parser { bytes, pos: offset };
-        let version = <&str as Parse>::parse(&mut parser)?;
-        if version != wit_schema_version::VERSION {
-            parser.pos = 0;
-            return Err(parser.error(ErrorKind::InvalidVersion(version.to_string())));
+        ParserConfig {
+            max_subsections: u32::MAX,
+            max_items: u32::MAX,
+            max_type_arity: u32::MAX,
+            max_instructions: u32::MAX,
+            max_string_len: u32::MAX,
         }
-        Ok(parser)
+        .parser(offset, bytes)
     }
 
     /// Returns if there are no more bytes to parse in this `Parser`, and all
@@ -76,6 +187,12 @@ impl<'a> Parser<'a> {
         self.bytes.is_empty()
     }
 
+    /// Returns the offset in the original wasm file of the next section in
+    /// this `Parser`.
+    pub fn original_position(&self) -> usize {
+        self.pos
+    }
+
     /// Attempts to parse the next [`Section`] from this [`Parser`].
     ///
     /// Returns an error if this parser cannot be advanced to parse another
@@ -83,6 +200,8 @@ impl<'a> Parser<'a> {
     /// rather it will return a placeholder which be further used to parse the
     /// contents of the section.
     pub fn section(&mut self) -> Result<Section<'a>> {
+        self.subsections = self.subsections.saturating_add(1);
+        self.check_limit(Limit::Subsections, self.subsections)?;
         self.parse()
     }
 
@@ -90,9 +209,44 @@ impl<'a> Parser<'a> {
         T::parse(self)
     }
 
+    /// Creates a parser for `bytes`, which were just parsed by this parser.
+    fn nested(&self, bytes: &'a [u8]) -> Parser<'a> {
+        Parser {
+            bytes,
+            pos: self.pos - bytes.len(),
+            config: self.config,
+            subsections: 0,
+        }
+    }
+
+    /// Parses the number of items in a subsection.
+    fn parse_count(&mut self) -> Result<u32> {
+        let pos = self.pos;
+        let cnt = self.parse()?;
+        if let Err(mut e) = self.check_limit(Limit::Items, cnt) {
+            e.inner.at = pos;
+            return Err(e);
+        }
+        Ok(cnt)
+    }
+
+    fn check_limit(&self, limit: Limit, n: u32) -> Result<()> {
+        let max = match limit {
+            Limit::Subsections => self.config.max_subsections,
+            Limit::Items => self.config.max_items,
+            Limit::TypeArity => self.config.max_type_arity,
+            Limit::Instructions => self.config.max_instructions,
+            Limit::StringLen => self.config.max_string_len,
+        };
+        if n > max {
+            return Err(self.error(ErrorKind::LimitExceeded(limit, max)));
+        }
+        Ok(())
+    }
+
     fn parse_next_in_section<T: Parse<'a>>(&mut self, cnt: &mut u32) -> Option<Result<T>> {
         if *cnt == 0 {
-            if self.bytes.len() != 0 {
+            if !self.bytes.is_empty() {
                 return Some(Err(self.error(ErrorKind::TrailingBytes)));
             }
             None
@@ -122,49 +276,53 @@ pub enum Section<'a> {
     Export(Exports<'a>),
     Func(Funcs<'a>),
     Implement(Implements<'a>),
+    ParamNames(ParamNames<'a>),
+    /// A subsection with an id that isn't known to this parser, such as an
+    /// experimental extension, along with its contents.
+    Unknown {
+        id: u8,
+        bytes: &'a [u8],
+    },
 }
 
 impl<'a> Parse<'a> for Section<'a> {
     fn parse(parser: &mut Parser<'a>) -> Result<Self> {
-        let id_pos = parser.pos;
         let id = u8::parse(parser)?;
         let bytes = <&[u8]>::parse(parser)?;
-        let mut parser = Parser {
-            bytes,
-            pos: parser.pos - bytes.len(),
-        };
+        let mut parser = parser.nested(bytes);
         match id {
             0 => {
-                let cnt = parser.parse()?;
+                let cnt = parser.parse_count()?;
                 Ok(Section::Type(Types { parser, cnt }))
             }
             1 => {
-                let cnt = parser.parse()?;
+                let cnt = parser.parse_count()?;
                 Ok(Section::Import(Imports { parser, cnt }))
             }
             2 => {
-                let cnt = parser.parse()?;
+                let cnt = parser.parse_count()?;
                 Ok(Section::Func(Funcs { parser, cnt }))
             }
             3 => {
-                let cnt = parser.parse()?;
+                let cnt = parser.parse_count()?;
                 Ok(Section::Export(Exports { parser, cnt }))
             }
             4 => {
-                let cnt = parser.parse()?;
+                let cnt = parser.parse_count()?;
                 Ok(Section::Implement(Implements { parser, cnt }))
             }
-            n => {
-                parser.pos = id_pos;
-                Err(parser.error(ErrorKind::InvalidSection(n)))
+            5 => {
+                let cnt = parser.parse_count()?;
+                Ok(Section::ParamNames(ParamNames { parser, cnt }))
             }
+            id => Ok(Section::Unknown { id, bytes }),
         }
     }
 }
 
 impl<'a> Parse<'a> for u8 {
     fn parse(parser: &mut Parser<'a>) -> Result<Self> {
-        match parser.bytes.get(0).cloned() {
+        match parser.bytes.first().cloned() {
             Some(byte) => {
                 parser.pos += 1;
                 parser.bytes = &parser.bytes[1..];
@@ -192,7 +350,13 @@ impl<'a> Parse<'a> for &'a [u8] {
 impl<'a> Parse<'a> for &'a str {
     fn parse(parser: &mut Parser<'a>) -> Result<Self> {
         let pos = parser.pos;
-        match str::from_utf8(parser.parse()?) {
+        let bytes = parser.parse::<&[u8]>()?;
+        if bytes.len() > parser.config.max_string_len as usize {
+            parser.pos = pos;
+            let max = parser.config.max_string_len;
+            return Err(parser.error(ErrorKind::LimitExceeded(Limit::StringLen, max)));
+        }
+        match str::from_utf8(bytes) {
             Ok(s) => Ok(s),
             Err(_) => {
                 parser.pos = pos;
@@ -206,7 +370,7 @@ impl<'a> Parse<'a> for u32 {
     fn parse(parser: &mut Parser<'a>) -> Result<Self> {
         let mut bytes = parser.bytes;
         match leb128::read::unsigned(&mut bytes) {
-            Ok(n) if n <= u32::max_value() as u64 => {
+            Ok(n) if n <= u32::MAX as u64 => {
                 parser.pos += parser.bytes.len() - bytes.len();
                 parser.bytes = bytes;
                 Ok(n as u32)
@@ -232,6 +396,14 @@ impl<'a> Iterator for Types<'a> {
     }
 }
 
+impl Types<'_> {
+    /// Returns the offset in the original wasm file of the next item in this
+    /// subsection.
+    pub fn original_position(&self) -> usize {
+        self.parser.pos
+    }
+}
+
 /// A type signatured reference by imports/functions, very similar to a wasm
 /// type declaration.
 pub struct Type {
@@ -244,7 +416,12 @@ pub struct Type {
 impl<'a> Parse<'a> for Type {
     fn parse(parser: &mut Parser<'a>) -> Result<Type> {
         let mut types = || -> Result<Vec<ValType>> {
+            let pos = parser.pos;
             let cnt = parser.parse::<u32>()?;
+            if let Err(mut e) = parser.check_limit(Limit::TypeArity, cnt) {
+                e.inner.at = pos;
+                return Err(e);
+            }
             (0..cnt).map(|_| parser.parse()).collect()
         };
         Ok(Type {
@@ -254,9 +431,15 @@ impl<'a> Parse<'a> for Type {
     }
 }
 
-/// List of value types supported in wasm interface types
+/// List of value types supported in wasm interface types, displayed with their
+/// text format names like `s32`.
 #[allow(missing_docs)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
 pub enum ValType {
     S8,
     S16,
@@ -296,6 +479,27 @@ impl<'a> Parse<'a> for ValType {
     }
 }
 
+impl fmt::Display for ValType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ValType::S8 => "s8",
+            ValType::S16 => "s16",
+            ValType::S32 => "s32",
+            ValType::S64 => "s64",
+            ValType::U8 => "u8",
+            ValType::U16 => "u16",
+            ValType::U32 => "u32",
+            ValType::U64 => "u64",
+            ValType::F32 => "f32",
+            ValType::F64 => "f64",
+            ValType::String => "string",
+            ValType::Externref => "externref",
+            ValType::I32 => "i32",
+            ValType::I64 => "i64",
+        })
+    }
+}
+
 /// An iterator over instances of [`Import`] in the import subsection of a wasm
 /// interface types section.
 pub struct Imports<'a> {
@@ -311,6 +515,14 @@ impl<'a> Iterator for Imports<'a> {
     }
 }
 
+impl Imports<'_> {
+    /// Returns the offset in the original wasm file of the next item in this
+    /// subsection.
+    pub fn original_position(&self) -> usize {
+        self.parser.pos
+    }
+}
+
 /// An element of the [`Imports`] subsection which lists where the import comes
 /// from as well as the type signature of the function.
 ///
@@ -349,6 +561,14 @@ impl<'a> Iterator for Exports<'a> {
     }
 }
 
+impl Exports<'_> {
+    /// Returns the offset in the original wasm file of the next item in this
+    /// subsection.
+    pub fn original_position(&self) -> usize {
+        self.parser.pos
+    }
+}
+
 /// An element of the [`Exports`] subsection which lists all exports from wasm
 /// interface types, used to export interface adapters from a module.
 pub struct Export<'a> {
@@ -382,6 +602,14 @@ impl<'a> Iterator for Implements<'a> {
     }
 }
 
+impl Implements<'_> {
+    /// Returns the offset in the original wasm file of the next item in this
+    /// subsection.
+    pub fn original_position(&self) -> usize {
+        self.parser.pos
+    }
+}
+
 /// An element of the [`Implements`] subsection which is a mapping that connects
 /// a core wasm func to being implemented by an adapter function.
 pub struct Implement {
@@ -400,6 +628,51 @@ impl<'a> Parse<'a> for Implement {
     }
 }
 
+/// An iterator over instances of [`ParamName`] in the parameter names
+/// subsection of a wasm interface types section.
+pub struct ParamNames<'a> {
+    parser: Parser<'a>,
+    cnt: u32,
+}
+
+impl<'a> Iterator for ParamNames<'a> {
+    type Item = Result<ParamName<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.parser.parse_next_in_section(&mut self.cnt)
+    }
+}
+
+impl ParamNames<'_> {
+    /// Returns the offset in the original wasm file of the next item in this
+    /// subsection.
+    pub fn original_position(&self) -> usize {
+        self.parser.pos
+    }
+}
+
+/// An element of the [`ParamNames`] subsection which gives a name to a
+/// parameter of an adapter function, similar to local names in the core wasm
+/// `name` section.
+pub struct ParamName<'a> {
+    /// The adapter function whose parameter is named.
+    pub func: u32,
+    /// The index of the parameter within the function's parameters.
+    pub param: u32,
+    /// The name of the parameter, without a leading `$`.
+    pub name: &'a str,
+}
+
+impl<'a> Parse<'a> for ParamName<'a> {
+    fn parse(parser: &mut Parser<'a>) -> Result<ParamName<'a>> {
+        Ok(ParamName {
+            func: parser.parse()?,
+            param: parser.parse()?,
+            name: parser.parse()?,
+        })
+    }
+}
+
 /// An iterator over instances of [`Func`] in the function subsection of a wasm
 /// interface types section.
 pub struct Funcs<'a> {
@@ -415,6 +688,14 @@ impl<'a> Iterator for Funcs<'a> {
     }
 }
 
+impl Funcs<'_> {
+    /// Returns the offset in the original wasm file of the next item in this
+    /// subsection.
+    pub fn original_position(&self) -> usize {
+        self.parser.pos
+    }
+}
+
 /// A element of the [`Funcs`] subsection which represents the body of an
 /// interface adapter, working with wasm interface types rather than core wasm
 /// types.
@@ -427,10 +708,7 @@ pub struct Func<'a> {
 impl<'a> Parse<'a> for Func<'a> {
     fn parse(parser: &mut Parser<'a>) -> Result<Func<'a>> {
         let bytes = parser.parse::<&[u8]>()?;
-        let mut parser = Parser {
-            bytes,
-            pos: parser.pos - bytes.len(),
-        };
+        let mut parser = parser.nested(bytes);
         Ok(Func {
             ty: parser.parse()?,
             parser,
@@ -443,6 +721,7 @@ impl<'a> Func<'a> {
     pub fn instrs(&self) -> Instructions<'a> {
         Instructions {
             parser: self.parser.clone(),
+            cnt: 0,
         }
     }
 }
@@ -450,12 +729,14 @@ impl<'a> Func<'a> {
 /// A parser for each [`Instruction`] contained within a [`Func`]
 pub struct Instructions<'a> {
     parser: Parser<'a>,
+    cnt: u32,
 }
 
 impl<'a> Iterator for Instructions<'a> {
     type Item = Result<Instruction>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        let pos = self.parser.pos;
         match self.parser.parse() {
             Ok(Instruction::End) => {
                 if self.parser.is_empty() {
@@ -464,27 +745,61 @@ impl<'a> Iterator for Instructions<'a> {
                     Some(Err(self.parser.error(ErrorKind::TrailingBytes)))
                 }
             }
+            Ok(instr) => {
+                self.cnt = self.cnt.saturating_add(1);
+                if let Err(mut e) = self.parser.check_limit(Limit::Instructions, self.cnt) {
+                    e.inner.at = pos;
+                    return Some(Err(e));
+                }
+                Some(Ok(instr))
+            }
             other => Some(other),
         }
     }
 }
 
+impl Instructions<'_> {
+    /// Returns the offset in the original wasm file of the next instruction.
+    pub fn original_position(&self) -> usize {
+        self.parser.pos
+    }
+}
+
 macro_rules! instructions {
     (pub enum Instruction {
         $(
-            $name:ident $(($($arg:tt)*))? = $binary:tt,
+            $(#[$attr:meta])*
+            $name:ident $(($($arg:tt)*))? = $binary:tt $text:tt,
         )*
     }) => (
         /// Operators that can be found in the body of a function in a wasm
         /// interface types section.
+        ///
+        /// With the `serde` feature enabled instructions are named as in the
+        /// text format, like `"i32-to-s8x"` or `{"arg.get": 0}`.
         #[allow(missing_docs)]
-        #[derive(Debug)]
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
         pub enum Instruction {
             $(
+                $(#[$attr])*
+                #[cfg_attr(feature = "serde", serde(rename = $text))]
                 $name $(( $($arg)* ))?,
             )*
         }
 
+        impl Instruction {
+            /// Returns the byte that this instruction is encoded with, which
+            /// is followed by any immediates.
+            pub fn opcode(&self) -> u8 {
+                match self {
+                    $(
+                        Instruction::$name { .. } => $binary,
+                    )*
+                }
+            }
+        }
+
         #[allow(non_snake_case)]
         impl<'a> Parse<'a> for Instruction {
             fn parse(parser: &mut Parser<'a>) -> Result<Self> {
@@ -512,61 +827,66 @@ macro_rules! instructions {
 
 instructions! {
     pub enum Instruction {
-        ArgGet(u32) = 0x00,
-        CallCore(u32) = 0x01,
-        End = 0x02,
-        MemoryToString(u32) = 0x03,
-        StringToMemory(StringToMemory) = 0x04,
-        CallAdapter(u32) = 0x05,
-        DeferCallCore(u32) = 0x06,
-
-        I32ToS8 = 0x07,
-        I32ToS8X = 0x08,
-        I32ToU8 = 0x09,
-        I32ToS16 = 0x0a,
-        I32ToS16X = 0x0b,
-        I32ToU16 = 0x0c,
-        I32ToS32 = 0x0d,
-        I32ToU32 = 0x0e,
-        I32ToS64 = 0x0f,
-        I32ToU64 = 0x10,
-
-        I64ToS8 = 0x11,
-        I64ToS8X = 0x12,
-        I64ToU8 = 0x13,
-        I64ToS16 = 0x14,
-        I64ToS16X = 0x15,
-        I64ToU16 = 0x16,
-        I64ToS32 = 0x17,
-        I64ToS32X = 0x18,
-        I64ToU32 = 0x19,
-        I64ToS64 = 0x1a,
-        I64ToU64 = 0x1b,
-
-        S8ToI32 = 0x1c,
-        U8ToI32 = 0x1d,
-        S16ToI32 = 0x1e,
-        U16ToI32 = 0x1f,
-        S32ToI32 = 0x20,
-        U32ToI32 = 0x21,
-        S64ToI32 = 0x22,
-        S64ToI32X = 0x23,
-        U64ToI32 = 0x24,
-        U64ToI32X = 0x25,
-
-        S8ToI64 = 0x26,
-        U8ToI64 = 0x27,
-        S16ToI64 = 0x28,
-        U16ToI64 = 0x29,
-        S32ToI64 = 0x2a,
-        U32ToI64 = 0x2b,
-        S64ToI64 = 0x2c,
-        U64ToI64 = 0x2d,
+        ArgGet(u32) = 0x00 "arg.get",
+        CallCore(u32) = 0x01 "call-core",
+        /// Not part of function bodies, so it can't be deserialized.
+        #[cfg_attr(feature = "serde", serde(skip_deserializing))]
+        End = 0x02 "end",
+        MemoryToString(u32) = 0x03 "memory-to-string",
+        StringToMemory(StringToMemory) = 0x04 "string-to-memory",
+        CallAdapter(u32) = 0x05 "call-adapter",
+        DeferCallCore(u32) = 0x06 "defer-call-core",
+
+        I32ToS8 = 0x07 "i32-to-s8",
+        I32ToS8X = 0x08 "i32-to-s8x",
+        I32ToU8 = 0x09 "i32-to-u8",
+        I32ToS16 = 0x0a "i32-to-s16",
+        I32ToS16X = 0x0b "i32-to-s16x",
+        I32ToU16 = 0x0c "i32-to-u16",
+        I32ToS32 = 0x0d "i32-to-s32",
+        I32ToU32 = 0x0e "i32-to-u32",
+        I32ToS64 = 0x0f "i32-to-s64",
+        I32ToU64 = 0x10 "i32-to-u64",
+
+        I64ToS8 = 0x11 "i64-to-s8",
+        I64ToS8X = 0x12 "i64-to-s8x",
+        I64ToU8 = 0x13 "i64-to-u8",
+        I64ToS16 = 0x14 "i64-to-s16",
+        I64ToS16X = 0x15 "i64-to-s16x",
+        I64ToU16 = 0x16 "i64-to-u16",
+        I64ToS32 = 0x17 "i64-to-s32",
+        I64ToS32X = 0x18 "i64-to-s32x",
+        I64ToU32 = 0x19 "i64-to-u32",
+        I64ToS64 = 0x1a "i64-to-s64",
+        I64ToU64 = 0x1b "i64-to-u64",
+
+        S8ToI32 = 0x1c "s8-to-i32",
+        U8ToI32 = 0x1d "u8-to-i32",
+        S16ToI32 = 0x1e "s16-to-i32",
+        U16ToI32 = 0x1f "u16-to-i32",
+        S32ToI32 = 0x20 "s32-to-i32",
+        U32ToI32 = 0x21 "u32-to-i32",
+        S64ToI32 = 0x22 "s64-to-i32",
+        S64ToI32X = 0x23 "s64-to-i32x",
+        U64ToI32 = 0x24 "u64-to-i32",
+        U64ToI32X = 0x25 "u64-to-i32x",
+
+        S8ToI64 = 0x26 "s8-to-i64",
+        U8ToI64 = 0x27 "u8-to-i64",
+        S16ToI64 = 0x28 "s16-to-i64",
+        U16ToI64 = 0x29 "u16-to-i64",
+        S32ToI64 = 0x2a "s32-to-i64",
+        U32ToI64 = 0x2b "u32-to-i64",
+        S64ToI64 = 0x2c "s64-to-i64",
+        U64ToI64 = 0x2d "u64-to-i64",
+
+        Extension(Extension) = 0xff "extension",
     }
 }
 
 /// Payload of the `string-to-memory` instruction
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StringToMemory {
     /// Function in the core module being used to allocate memory in `mem` to
     /// place a string into. Must take one `i32` parameter and return one `i32`
@@ -585,6 +905,30 @@ impl<'a> Parse<'a> for StringToMemory {
     }
 }
 
+/// An instruction which isn't known to this parser, such as an experimental
+/// extension.
+///
+/// Extension instructions are encoded with the `0xff` opcode followed by the
+/// extension's own opcode and its length-prefixed immediates, so they can be
+/// skipped or preserved by tools which don't understand them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Extension {
+    /// The opcode of this extension instruction.
+    pub opcode: u32,
+    /// The encoded immediates of this instruction.
+    pub immediates: Vec<u8>,
+}
+
+impl<'a> Parse<'a> for Extension {
+    fn parse(parser: &mut Parser<'a>) -> Result<Self> {
+        Ok(Extension {
+            opcode: parser.parse()?,
+            immediates: <&[u8]>::parse(parser)?.to_vec(),
+        })
+    }
+}
+
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "failed to parse at byte {}: ", self.inner.at)?;
@@ -595,15 +939,35 @@ impl fmt::Display for Error {
                 s,
                 wit_schema_version::VERSION
             ),
-            ErrorKind::UlebTooBig(_) => write!(f, "uleb encoded integer too big"),
+            ErrorKind::UlebTooBig(n) => write!(f, "uleb encoded integer too big: {}", n),
             ErrorKind::UlebInvalid => write!(f, "failed to parse uleb integer"),
             ErrorKind::UnexpectedEof => write!(f, "unexpected end-of-file"),
             ErrorKind::InvalidUtf8 => write!(f, "invalid utf-8 string"),
-            ErrorKind::InvalidSection(n) => write!(f, "invalid section id: {}", n),
             ErrorKind::InvalidValType(n) => write!(f, "invalid value type: {}", n),
             ErrorKind::InvalidInstruction(n) => write!(f, "invalid instruction: 0x{:02x}", n),
             ErrorKind::Expected(n) => write!(f, "expected {} more bytes but hit eof", n),
             ErrorKind::TrailingBytes => write!(f, "trailing bytes at the end of the section"),
+            ErrorKind::LimitExceeded(limit, max) => {
+                let what = match limit {
+                    Limit::Subsections => "subsections",
+                    Limit::Items => "items in a subsection",
+                    Limit::TypeArity => "parameters or results in a type",
+                    Limit::Instructions => "instructions in a function",
+                    Limit::StringLen => "bytes in a string",
+                };
+                write!(f, "exceeded the limit of {} {}", max, what)
+            }
+        }
+    }
+}
+
+impl Error {
+    /// Returns the limit of the [`ParserConfig`] which was exceeded, if that's
+    /// what caused this error.
+    pub fn limit(&self) -> Option<Limit> {
+        match self.inner.kind {
+            ErrorKind::LimitExceeded(limit, _) => Some(limit),
+            _ => None,
         }
     }
 }
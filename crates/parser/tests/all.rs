@@ -0,0 +1,70 @@
+use wit_parser::{Error, Limit, Parser, ParserConfig, Section};
+
+/// Parses every item of every known subsection in `parser`.
+fn parse(mut parser: Parser<'_>) -> Result<(), Error> {
+    while !parser.is_empty() {
+        match parser.section()? {
+            Section::Type(mut s) => s.try_for_each(|t| t.map(drop)),
+            Section::Import(mut s) => s.try_for_each(|t| t.map(drop)),
+            Section::Func(mut s) => s.try_for_each(|f| f?.instrs().try_for_each(|i| i.map(drop))),
+            _ => Ok(()),
+        }?;
+    }
+    Ok(())
+}
+
+#[test]
+fn parser_limits() {
+    let mut writer = wit_writer::Writer::new();
+    let mut w = writer.types(1);
+    w.add(
+        2,
+        |w| {
+            w.s32();
+            w.s32();
+        },
+        0,
+        |_| {},
+    );
+    drop(w);
+    let mut w = writer.imports(1);
+    w.add("host", "log", 0);
+    drop(w);
+    let mut w = writer.funcs(1);
+    w.add(0).arg_get(0);
+    drop(w);
+    let payload = writer.into_payload();
+
+    let check = |config: &ParserConfig| parse(config.parser(0, &payload)?);
+    let limit = |config: &mut ParserConfig| check(config).unwrap_err().limit();
+
+    check(&ParserConfig::new()).unwrap();
+    let c = ParserConfig::new;
+    assert_eq!(limit(c().max_subsections(2)), Some(Limit::Subsections));
+    assert_eq!(limit(c().max_items(0)), Some(Limit::Items));
+    assert_eq!(limit(c().max_type_arity(1)), Some(Limit::TypeArity));
+    assert_eq!(limit(c().max_instructions(0)), Some(Limit::Instructions));
+    assert_eq!(limit(c().max_string_len(3)), Some(Limit::StringLen));
+    check(c().max_instructions(1).max_string_len(5)).unwrap();
+
+    let err = check(c().max_type_arity(1)).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("exceeded the limit of 1 parameters or results in a type"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn no_limits_by_default() {
+    let mut writer = wit_writer::Writer::new();
+    for _ in 0..101 {
+        writer.raw_section(0x7f, &[]);
+    }
+    let payload = writer.into_payload();
+
+    let err = parse(ParserConfig::new().parser(0, &payload).unwrap()).unwrap_err();
+    assert_eq!(err.limit(), Some(Limit::Subsections));
+    parse(Parser::new(0, &payload).unwrap()).unwrap();
+}
@@ -8,6 +8,36 @@ fn main() -> anyhow::Result<()> {
     opts.optopt("o", "", "set output file name", "NAME");
     opts.optflag("h", "help", "print this help menu");
     opts.optflag("", "no-validate", "don't validate the module");
+    opts.optflag(
+        "",
+        "interface",
+        "only print the adapter imports and exports of the module",
+    );
+    opts.optflag(
+        "",
+        "adapters",
+        "only print the adapters of the module, without the core module",
+    );
+    opts.optflag(
+        "",
+        "dump",
+        "dump the interface types section with byte offsets, even if malformed",
+    );
+    opts.optflag(
+        "",
+        "fold",
+        "print adapter instructions in the folded s-expression form",
+    );
+    opts.optflag(
+        "",
+        "core-indices",
+        "refer to core functions by index rather than by name",
+    );
+    opts.optflag(
+        "",
+        "type-comments",
+        "print adapter signatures in comments where their types are used",
+    );
     let matches = opts.parse(env::args_os().skip(1))?;
     if matches.opt_present("h") {
         print_usage(opts);
@@ -21,10 +51,24 @@ fn main() -> anyhow::Result<()> {
     };
 
     let bytes = fs::read(&input).context(format!("failed to read: {}", input))?;
-    if !matches.opt_present("no-validate") {
+    // Bare interface types sections can't be validated without their core
+    // module.
+    let validate = bytes.starts_with(b"\0asm") && !matches.opt_present("dump");
+    if validate && !matches.opt_present("no-validate") {
         wit_validator::validate(&bytes)?;
     }
-    let wit = wit_printer::print_bytes(&bytes)?;
+    let wit = if matches.opt_present("dump") {
+        wit_printer::dump_bytes(&bytes)?
+    } else if matches.opt_present("interface") {
+        wit_printer::print_interface(&bytes)?
+    } else {
+        wit_printer::Config::new()
+            .fold_instructions(matches.opt_present("fold"))
+            .adapters_only(matches.opt_present("adapters"))
+            .core_indices(matches.opt_present("core-indices"))
+            .type_comments(matches.opt_present("type-comments"))
+            .print_bytes(&bytes)?
+    };
     if let Some(output) = matches.opt_str("o") {
         std::fs::write(&output, wit).context(format!("failed to write `{}`", output))?;
     } else {
@@ -0,0 +1,275 @@
+use anyhow::{Context, Result};
+use std::fmt::{Display, Write};
+use std::path::Path;
+use wasmparser::Payload;
+use wit_parser::*;
+
+/// Dumps the wasm interface types sections of a wasm file on the filesystem,
+/// annotated with byte offsets.
+///
+/// See [`dump_bytes`] for more information.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or it wasn't a valid wasm file.
+pub fn dump_file(file: impl AsRef<Path>) -> Result<String> {
+    let file = file.as_ref();
+    let contents = std::fs::read(file).context(format!("failed to read `{}`", file.display()))?;
+    dump_bytes(&contents)
+}
+
+/// Dumps the wasm interface types sections of an in-memory wasm module,
+/// annotated with byte offsets, in the style of `wasm-tools dump`.
+///
+/// Each subsection header, count, item, instruction and immediate is printed
+/// on its own line along with its offset in `wasm` and its raw bytes. This is
+/// intended for debugging binaries which fail to parse, so a malformed section
+/// is dumped up to where parsing failed followed by the parse error, rather
+/// than returning an error.
+///
+/// Like [`Config::print_bytes`](crate::Config::print_bytes), if `wasm` isn't
+/// a wasm module it's treated as the bare payload of a `wasm-interface-types`
+/// custom section, with offsets relative to the start of the payload.
+///
+/// The output looks like:
+///
+/// ```text
+/// 0x00001f | 05 30 2e 31 2e 30       | version "0.1.0"
+/// 0x000025 | 00 03                   | type subsection, 3 bytes
+/// 0x000027 | 01                      | count 1
+/// 0x000028 | 00 00                   | type 0: (func)
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if the bytes weren't a valid wasm blob.
+pub fn dump_bytes(wasm: impl AsRef<[u8]>) -> Result<String> {
+    _dump_bytes(wasm.as_ref())
+}
+
+fn _dump_bytes(wasm: &[u8]) -> Result<String> {
+    let mut dump = Dump {
+        wasm,
+        ret: String::new(),
+        func: 0,
+    };
+    if !wasm.starts_with(b"\0asm") {
+        if let Err(e) = dump.section(0, wasm) {
+            writeln!(dump.ret, "error: {}", e)?;
+        }
+        return Ok(dump.ret);
+    }
+    for payload in wasmparser::Parser::new(0).parse_all(wasm) {
+        if let Payload::CustomSection {
+            name: wit_schema_version::SECTION_NAME,
+            data,
+            data_offset,
+        } = payload?
+        {
+            if !dump.ret.is_empty() {
+                dump.ret.push('\n');
+            }
+            dump.func = 0;
+            if let Err(e) = dump.section(data_offset, data) {
+                writeln!(dump.ret, "error: {}", e)?;
+            }
+        }
+    }
+    Ok(dump.ret)
+}
+
+struct Dump<'a> {
+    wasm: &'a [u8],
+    ret: String,
+    /// The index of the next adapter function.
+    func: u32,
+}
+
+impl Dump<'_> {
+    fn section(&mut self, offset: usize, bytes: &[u8]) -> Result<()> {
+        let mut parser = Parser::new(offset, bytes)?;
+        let version = format!("version \"{}\"", wit_schema_version::VERSION);
+        self.line(offset, parser.original_position(), version);
+
+        while !parser.is_empty() {
+            let start = parser.original_position();
+            let section = parser.section()?;
+            let end = parser.original_position();
+            let (size, mut pos) = read_leb(self.wasm, start + 1);
+            let name = match &section {
+                Section::Type(_) => "type".to_string(),
+                Section::Import(_) => "import".to_string(),
+                Section::Func(_) => "func".to_string(),
+                Section::Export(_) => "export".to_string(),
+                Section::Implement(_) => "implement".to_string(),
+                Section::ParamNames(_) => "param names".to_string(),
+                Section::Unknown { id, .. } => format!("unknown {}", id),
+            };
+            self.line(start, pos, format!("{} subsection, {} bytes", name, size));
+
+            match section {
+                Section::Type(mut s) => {
+                    self.leb(&mut pos, "count");
+                    let mut i = 0;
+                    while let Some(ty) = s.next() {
+                        let ty = ty?;
+                        let mut text = format!("type {}: (func", i);
+                        for param in ty.params.iter() {
+                            write!(text, " (param {})", param)?;
+                        }
+                        for result in ty.results.iter() {
+                            write!(text, " (result {})", result)?;
+                        }
+                        text.push(')');
+                        pos = self.item(pos, s.original_position(), text);
+                        i += 1;
+                    }
+                }
+                Section::Import(mut s) => {
+                    self.leb(&mut pos, "count");
+                    while let Some(import) = s.next() {
+                        let import = import?;
+                        let mut text = String::from("import ");
+                        crate::push_str(&mut text, import.module);
+                        text.push(' ');
+                        crate::push_str(&mut text, import.name);
+                        write!(text, " (func {}) (type {})", self.func, import.ty)?;
+                        pos = self.item(pos, s.original_position(), text);
+                        self.func += 1;
+                    }
+                }
+                Section::Func(mut s) => {
+                    self.leb(&mut pos, "count");
+                    while let Some(func) = s.next() {
+                        let func = func?;
+                        let end = s.original_position();
+                        let text = format!("func {}: body size", self.func);
+                        self.leb(&mut pos, text);
+                        self.leb(&mut pos, "type");
+                        let mut instrs = func.instrs();
+                        while let Some(instr) = instrs.next() {
+                            self.instr(pos, &instr?)?;
+                            pos = instrs.original_position();
+                        }
+                        pos = self.item(pos, end, "end");
+                        self.func += 1;
+                    }
+                }
+                Section::Export(mut s) => {
+                    self.leb(&mut pos, "count");
+                    while let Some(export) = s.next() {
+                        let export = export?;
+                        let mut text = String::from("export ");
+                        crate::push_str(&mut text, export.name);
+                        write!(text, " (func {})", export.func)?;
+                        pos = self.item(pos, s.original_position(), text);
+                    }
+                }
+                Section::Implement(mut s) => {
+                    self.leb(&mut pos, "count");
+                    while let Some(i) = s.next() {
+                        let i = i?;
+                        let text = format!(
+                            "implement (core func {}) (func {})",
+                            i.core_func, i.adapter_func
+                        );
+                        pos = self.item(pos, s.original_position(), text);
+                    }
+                }
+                Section::ParamNames(mut s) => {
+                    self.leb(&mut pos, "count");
+                    while let Some(name) = s.next() {
+                        let name = name?;
+                        let mut text =
+                            format!("param name (func {}) (param {}) ", name.func, name.param);
+                        crate::push_str(&mut text, name.name);
+                        pos = self.item(pos, s.original_position(), text);
+                    }
+                }
+                Section::Unknown { .. } => {
+                    let text = format!("{} bytes of unknown contents", end - pos);
+                    self.item(pos, end, text);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Prints an instruction starting at `pos` followed by each of its
+    /// immediates.
+    fn instr(&mut self, mut pos: usize, instr: &Instruction) -> Result<()> {
+        self.line(pos, pos + 1, crate::instr_name(instr));
+        pos += 1;
+        match instr {
+            Instruction::ArgGet(_) => self.leb(&mut pos, "  index"),
+            Instruction::CallCore(_) | Instruction::DeferCallCore(_) => {
+                self.leb(&mut pos, "  core func")
+            }
+            Instruction::CallAdapter(_) => self.leb(&mut pos, "  func"),
+            Instruction::MemoryToString(_) => self.leb(&mut pos, "  memory"),
+            Instruction::StringToMemory(_) => {
+                self.leb(&mut pos, "  malloc");
+                self.leb(&mut pos, "  memory");
+            }
+            Instruction::Extension(ext) => {
+                self.leb(&mut pos, "  opcode");
+                let (len, end) = read_leb(self.wasm, pos);
+                let text = format!("  {} bytes of immediates", ext.immediates.len());
+                self.line(pos, end + len as usize, text);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Prints the LEB-encoded integer at `pos` as `{text} {value}`.
+    fn leb(&mut self, pos: &mut usize, text: impl Display) {
+        let (n, end) = read_leb(self.wasm, *pos);
+        self.line(*pos, end, format!("{} {}", text, n));
+        *pos = end;
+    }
+
+    /// Prints an item spanning `start..end`, returning `end`.
+    fn item(&mut self, start: usize, end: usize, text: impl Display) -> usize {
+        self.line(start, end, text);
+        end
+    }
+
+    /// Prints the bytes `start..end` with `text`, wrapping long runs of bytes
+    /// onto following lines.
+    fn line(&mut self, start: usize, end: usize, text: impl Display) {
+        for (i, chunk) in self.wasm[start..end].chunks(8).enumerate() {
+            let mut bytes = String::new();
+            for byte in chunk {
+                write!(bytes, "{:02x} ", byte).unwrap();
+            }
+            write!(
+                self.ret,
+                "{:#08x} | {:<23} |",
+                start + i * 8,
+                bytes.trim_end()
+            )
+            .unwrap();
+            if i == 0 {
+                write!(self.ret, " {}", text).unwrap();
+            }
+            self.ret.push('\n');
+        }
+    }
+}
+
+/// Reads an unsigned LEB128 integer at `pos` which the parser has already
+/// validated, returning its value and the offset just after it.
+fn read_leb(bytes: &[u8], mut pos: usize) -> (u64, usize) {
+    let mut ret = 0;
+    let mut shift = 0;
+    while let Some(byte) = bytes.get(pos) {
+        pos += 1;
+        ret |= u64::from(byte & 0x7f).checked_shl(shift).unwrap_or(0);
+        shift += 7;
+        if byte & 0x80 == 0 {
+            break;
+        }
+    }
+    (ret, pos)
+}
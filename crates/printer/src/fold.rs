@@ -0,0 +1,148 @@
+//! Support for printing adapter bodies in the folded s-expression form.
+//!
+//! Folding only changes how instructions are laid out, not their order, so
+//! parsing folded output always produces the original sequence of
+//! instructions. Arities are used to decide which preceding instructions are
+//! grouped as the operands of an instruction, and where they don't line up
+//! instructions are simply left as siblings.
+
+use anyhow::Context;
+use std::collections::HashMap;
+use wasmparser::{ImportSectionEntryType, Payload, TypeDef};
+use wasmprinter::Printer;
+use wit_parser::Instruction;
+
+/// The number of values a function pops from and pushes onto the stack.
+#[derive(Copy, Clone, Debug)]
+pub struct Arity {
+    params: usize,
+    results: usize,
+}
+
+impl Arity {
+    pub fn new<T>(params: &[T], results: &[T]) -> Arity {
+        Arity {
+            params: params.len(),
+            results: results.len(),
+        }
+    }
+}
+
+/// Returns the arity of each function in the core module `wasm`, or `None`
+/// for functions whose type isn't a function type.
+pub fn core_arities(wasm: &[u8]) -> anyhow::Result<Vec<Option<Arity>>> {
+    let mut types = Vec::new();
+    let mut funcs = Vec::new();
+    for payload in wasmparser::Parser::new(0).parse_all(wasm) {
+        match payload.context("failed to parse core module")? {
+            Payload::TypeSection(s) => {
+                for ty in s {
+                    types.push(match ty? {
+                        TypeDef::Func(f) => Some(Arity::new(&f.params, &f.returns)),
+                        _ => None,
+                    });
+                }
+            }
+            Payload::ImportSection(s) => {
+                for import in s {
+                    if let ImportSectionEntryType::Function(ty) = import?.ty {
+                        funcs.push(types.get(ty as usize).copied().flatten());
+                    }
+                }
+            }
+            Payload::FunctionSection(s) => {
+                for ty in s {
+                    funcs.push(types.get(ty? as usize).copied().flatten());
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(funcs)
+}
+
+/// An instruction along with the instructions producing its operands.
+pub struct Expr<'a> {
+    instr: &'a Instruction,
+    results: usize,
+    operands: Vec<Expr<'a>>,
+}
+
+/// Folds `instrs` into a list of expressions.
+///
+/// Returns `None` if the arity of an instruction couldn't be determined, in
+/// which case the instructions should be printed flat.
+pub fn fold<'a>(
+    instrs: &'a [Instruction],
+    core: &[Option<Arity>],
+    adapters: &[Option<Arity>],
+) -> Option<Vec<Expr<'a>>> {
+    let func = |funcs: &[Option<Arity>], idx: u32| funcs.get(idx as usize).copied().flatten();
+    let mut stack: Vec<Expr<'a>> = Vec::new();
+    for instr in instrs {
+        let (params, results) = match instr {
+            Instruction::ArgGet(_) => (0, 1),
+            Instruction::CallCore(f) => {
+                let a = func(core, *f)?;
+                (a.params, a.results)
+            }
+            // Deferred calls check their operands but leave them on the
+            // stack, so they can't take any operands of their own.
+            Instruction::DeferCallCore(_) => (0, 0),
+            Instruction::CallAdapter(f) => {
+                let a = func(adapters, *f)?;
+                (a.params, a.results)
+            }
+            Instruction::MemoryToString(_) => (2, 1),
+            Instruction::StringToMemory(_) => (1, 2),
+            Instruction::End => (0, 0),
+            // The effect of an unknown instruction on the stack isn't known.
+            Instruction::Extension(_) => return None,
+            // Everything else is a conversion of one value to another.
+            _ => (1, 1),
+        };
+
+        // Find the shortest run of expressions on the top of the stack which
+        // produces exactly the operands of this instruction.
+        let mut start = stack.len();
+        let mut found = 0;
+        while found < params && start > 0 {
+            start -= 1;
+            found += stack[start].results;
+        }
+        if found != params {
+            start = stack.len();
+        }
+        let operands = stack.split_off(start);
+        stack.push(Expr {
+            instr,
+            results,
+            operands,
+        });
+    }
+    Some(stack)
+}
+
+impl Expr<'_> {
+    /// Prints this expression, where the expression itself is already
+    /// indented by `indent` spaces.
+    pub fn print(
+        &self,
+        printer: &mut Printer,
+        core_names: &HashMap<u32, String>,
+        params: &HashMap<u32, &str>,
+        indent: usize,
+    ) -> anyhow::Result<()> {
+        printer.result_mut().push('(');
+        crate::push_instr(printer, core_names, params, self.instr)?;
+        for operand in self.operands.iter() {
+            printer.result_mut().push('\n');
+            for _ in 0..indent + 2 {
+                printer.result_mut().push(' ');
+            }
+            operand.print(printer, core_names, params, indent + 2)?;
+        }
+        printer.result_mut().push(')');
+        Ok(())
+    }
+}
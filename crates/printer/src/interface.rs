@@ -0,0 +1,142 @@
+use anyhow::{anyhow, Context, Result};
+use std::fmt::Write;
+use wasmparser::Payload;
+use wit_parser::*;
+
+/// Prints only the interface surface of an in-memory wasm module: its adapter
+/// imports grouped by module, its adapter exports, and their signatures.
+///
+/// Unlike [`print_bytes`](crate::print_bytes) this doesn't print the core
+/// module or the bodies of adapters, and is intended to be used as a
+/// human-readable API contract for a module. Imports and exports are sorted by
+/// name so the output is stable across compilations and diffs well between
+/// releases of a module.
+///
+/// The output looks like:
+///
+/// ```text
+/// import "host" {
+///   "log": func(string)
+/// }
+///
+/// export {
+///   "greet": func(string, u32) -> string
+/// }
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if the bytes weren't a valid wasm blob or if the interface
+/// types section is malformed.
+pub fn print_interface(wasm: impl AsRef<[u8]>) -> Result<String> {
+    _print_interface(wasm.as_ref())
+}
+
+fn _print_interface(wasm: &[u8]) -> Result<String> {
+    let mut types = Vec::new();
+    let mut funcs = Vec::new();
+    let mut imports = Vec::new();
+    let mut exports = Vec::new();
+    for payload in wasmparser::Parser::new(0).parse_all(wasm) {
+        let (data, data_offset) = match payload? {
+            Payload::CustomSection {
+                name: wit_schema_version::SECTION_NAME,
+                data,
+                data_offset,
+            } => (data, data_offset),
+            _ => continue,
+        };
+        let mut parser = Parser::new(data_offset, data).context("failed to parse header")?;
+        while !parser.is_empty() {
+            match parser.section().context("failed to parse section")? {
+                Section::Type(s) => {
+                    for ty in s {
+                        types.push(ty.context("failed to parse type")?);
+                    }
+                }
+                Section::Import(s) => {
+                    for import in s {
+                        let import = import.context("failed to parse import")?;
+                        funcs.push(import.ty);
+                        imports.push(import);
+                    }
+                }
+                Section::Func(s) => {
+                    for func in s {
+                        funcs.push(func.context("failed to parse func")?.ty);
+                    }
+                }
+                Section::Export(s) => {
+                    for export in s {
+                        exports.push(export.context("failed to parse export")?);
+                    }
+                }
+                Section::Implement(_) | Section::ParamNames(_) | Section::Unknown { .. } => {}
+            }
+        }
+    }
+
+    let ty = |idx: u32| {
+        types
+            .get(idx as usize)
+            .ok_or_else(|| anyhow!("adapter type index out of bounds: {}", idx))
+    };
+
+    let mut ret = String::new();
+    imports.sort_by_key(|i| (i.module, i.name));
+    let mut prev = None;
+    for import in imports.iter() {
+        if prev != Some(import.module) {
+            if prev.is_some() {
+                ret.push_str("}\n\n");
+            }
+            ret.push_str("import ");
+            crate::push_str(&mut ret, import.module);
+            ret.push_str(" {\n");
+            prev = Some(import.module);
+        }
+        ret.push_str("  ");
+        crate::push_str(&mut ret, import.name);
+        writeln!(ret, ": {}", signature_of(ty(import.ty)?))?;
+    }
+    if prev.is_some() {
+        ret.push_str("}\n");
+    }
+
+    exports.sort_by_key(|e| e.name);
+    if !exports.is_empty() {
+        if !ret.is_empty() {
+            ret.push('\n');
+        }
+        ret.push_str("export {\n");
+        for export in exports.iter() {
+            let idx = funcs
+                .get(export.func as usize)
+                .ok_or_else(|| anyhow!("adapter func index out of bounds: {}", export.func))?;
+            ret.push_str("  ");
+            crate::push_str(&mut ret, export.name);
+            writeln!(ret, ": {}", signature_of(ty(*idx)?))?;
+        }
+        ret.push_str("}\n");
+    }
+    Ok(ret)
+}
+
+fn signature_of(ty: &Type) -> String {
+    signature(&ty.params, &ty.results)
+}
+
+/// Formats a signature like `func(string, u32) -> string`.
+pub(crate) fn signature(params: &[ValType], results: &[ValType]) -> String {
+    let params = params.iter().map(ValType::to_string).collect::<Vec<_>>();
+    let mut ret = format!("func({})", params.join(", "));
+    match results {
+        [] => {}
+        [result] => write!(ret, " -> {}", result).unwrap(),
+        results => {
+            let results = results.iter().map(ValType::to_string).collect::<Vec<_>>();
+            write!(ret, " -> ({})", results.join(", ")).unwrap();
+        }
+    }
+    ret
+}
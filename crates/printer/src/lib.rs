@@ -7,11 +7,20 @@
 #![deny(missing_docs)]
 
 use anyhow::Context;
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::fmt::Write;
 use std::path::Path;
+use std::rc::Rc;
 use wasmprinter::Printer;
 use wit_parser::*;
 
+mod dump;
+mod fold;
+mod interface;
+pub use self::dump::{dump_bytes, dump_file};
+pub use self::interface::print_interface;
+
 /// Prints an entire wasm file to its textual representation, returning the
 /// in-memory `String` of the textual representation.
 ///
@@ -19,49 +28,219 @@ use wit_parser::*;
 ///
 /// Returns an error if the file cannot be read or it wasn't a valid wasm file.
 pub fn print_file(file: impl AsRef<Path>) -> anyhow::Result<String> {
-    _print_file(file.as_ref())
-}
-
-fn _print_file(file: &Path) -> anyhow::Result<String> {
-    let contents = std::fs::read(file).context(format!("failed to read `{}`", file.display()))?;
-    _print_bytes(&contents)
+    Config::new().print_file(file)
 }
 
 /// Prints an entire in-memory wasm module to its textual representation,
 /// returning the in-memory `String` of the textual representation.
 ///
+/// See [`Config::print_bytes`] for how a bare interface types section is
+/// printed.
+///
 /// # Errors
 ///
 /// Returns an error if the bytes weren't a valid wasm blob.
 pub fn print_bytes(wasm: impl AsRef<[u8]>) -> anyhow::Result<String> {
-    _print_bytes(wasm.as_ref())
+    Config::new().print_bytes(wasm)
+}
+
+/// Configuration for how the textual representation of a module is printed.
+///
+/// The free functions [`print_file`] and [`print_bytes`] use the default
+/// configuration.
+#[derive(Clone, Debug, Default)]
+pub struct Config {
+    fold_instructions: bool,
+    adapters_only: bool,
+    core_indices: bool,
+    type_comments: bool,
+}
+
+impl Config {
+    /// Creates a new configuration with default settings.
+    pub fn new() -> Config {
+        Config::default()
+    }
+
+    /// Whether the bodies of adapter functions are printed in the folded
+    /// s-expression form, for example `(call-core $f (arg.get 0))`.
+    ///
+    /// Instructions are only folded where their operands can be determined
+    /// from the types of functions they call, and everything else is printed
+    /// flat. Defaults to `false`.
+    pub fn fold_instructions(&mut self, fold: bool) -> &mut Config {
+        self.fold_instructions = fold;
+        self
+    }
+
+    /// Whether only the `@interface` adapter items are printed, leaving out
+    /// the core module.
+    ///
+    /// The output is then a standalone `*.wit` file, which can be combined
+    /// with the core module again by `wit_text::parse_str_with_core`. Core
+    /// functions are still referred to by their names in the core module.
+    /// Defaults to `false`.
+    pub fn adapters_only(&mut self, only: bool) -> &mut Config {
+        self.adapters_only = only;
+        self
+    }
+
+    /// Whether adapters always refer to core functions by index, for example
+    /// `call-core 3`, rather than by their names.
+    ///
+    /// Names come from the `name` section of the core module, and are only
+    /// used where they're valid and unique text format identifiers so the
+    /// output can be parsed back in. Core memories are always referred to by
+    /// index: `wasmparser` 0.61 can't read memory names from the `name`
+    /// section, and `wasmprinter` 0.2.8 doesn't print them for the core
+    /// module either. Defaults to `false`.
+    pub fn core_indices(&mut self, indices: bool) -> &mut Config {
+        self.core_indices = indices;
+        self
+    }
+
+    /// Whether each `(type N)` reference of an adapter import or function is
+    /// followed by a comment with the signature it refers to, for example
+    /// `(type 0) (; func(string) -> u32 ;)`.
+    ///
+    /// The comment is left out where the signature is already printed inline
+    /// to name parameters. Defaults to `false`.
+    pub fn type_comments(&mut self, comments: bool) -> &mut Config {
+        self.type_comments = comments;
+        self
+    }
+
+    /// Prints an entire wasm file to its textual representation with this
+    /// configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or it wasn't a valid wasm
+    /// file.
+    pub fn print_file(&self, file: impl AsRef<Path>) -> anyhow::Result<String> {
+        self._print_file(file.as_ref())
+    }
+
+    fn _print_file(&self, file: &Path) -> anyhow::Result<String> {
+        let contents =
+            std::fs::read(file).context(format!("failed to read `{}`", file.display()))?;
+        self._print_bytes(&contents)
+    }
+
+    /// Prints an entire in-memory wasm module to its textual representation
+    /// with this configuration.
+    ///
+    /// If `wasm` doesn't start with the `\0asm` header of a wasm module it's
+    /// instead treated as the bare payload of a `wasm-interface-types` custom
+    /// section. Only its adapters are printed then, as if
+    /// [`Config::adapters_only`] were set, and core functions are referred to
+    /// by index since there's no core module to name them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bytes weren't a valid wasm blob or interface
+    /// types section.
+    pub fn print_bytes(&self, wasm: impl AsRef<[u8]>) -> anyhow::Result<String> {
+        self._print_bytes(wasm.as_ref())
+    }
+
+    fn _print_bytes(&self, wasm: &[u8]) -> anyhow::Result<String> {
+        if !wasm.starts_with(b"\0asm") {
+            // Without a core module the arities and names of core functions
+            // aren't known, so calls to them are never folded and refer to
+            // them by index.
+            let core = Core {
+                arities: if self.fold_instructions {
+                    Some(Vec::new())
+                } else {
+                    None
+                },
+                names: HashMap::new(),
+            };
+            let mut printer = Printer::new();
+            print_wit(&mut printer, 0, wasm, &core, self.type_comments)?;
+            return Ok(dedent(printer.result_mut()));
+        }
+
+        let core = Core {
+            arities: if self.fold_instructions {
+                Some(fold::core_arities(wasm)?)
+            } else {
+                None
+            },
+            names: if self.core_indices {
+                HashMap::new()
+            } else {
+                core_names(wasm)?
+            },
+        };
+        let type_comments = self.type_comments;
+        let adapters = if self.adapters_only {
+            Some(Rc::new(RefCell::new(String::new())))
+        } else {
+            None
+        };
+        let mut printer = Printer::new();
+        let section_adapters = adapters.clone();
+        printer.add_custom_section_printer(
+            wit_schema_version::SECTION_NAME,
+            move |printer, offset, bytes| {
+                let start = printer.result_mut().len();
+                print_wit(printer, offset, bytes, &core, type_comments)?;
+                if let Some(adapters) = &section_adapters {
+                    adapters
+                        .borrow_mut()
+                        .push_str(&printer.result_mut()[start..]);
+                }
+                Ok(())
+            },
+        );
+        let module = printer.print(wasm)?;
+        match adapters {
+            Some(adapters) => Ok(dedent(&adapters.borrow())),
+            None => Ok(module),
+        }
+    }
 }
 
-fn _print_bytes(wasm: &[u8]) -> anyhow::Result<String> {
-    let mut printer = Printer::new();
-    printer.add_custom_section_printer(wit_schema_version::SECTION_NAME, print_wit);
-    printer.print(wasm)
+/// What's known about the core module when printing its adapters.
+struct Core {
+    /// The arity of each core function, if adapter bodies are folded.
+    arities: Option<Vec<Option<fold::Arity>>>,
+    /// The names to refer to core functions with, keyed by index.
+    names: HashMap<u32, String>,
 }
 
-fn print_wit(printer: &mut Printer, offset: usize, bytes: &[u8]) -> anyhow::Result<()> {
+/// Prints the wasm interface types section, folding adapter bodies if the
+/// arities of core functions are known.
+fn print_wit(
+    printer: &mut Printer,
+    offset: usize,
+    bytes: &[u8],
+    core: &Core,
+    type_comments: bool,
+) -> anyhow::Result<()> {
     let mut parser = Parser::new(offset, bytes).context("failed to parse header")?;
+    let param_names = read_param_names(parser.clone())?;
+    let no_names = HashMap::new();
     let mut func = 0;
+    let mut types = Vec::new();
+    let mut type_sigs = Vec::new();
+    let mut funcs = Vec::new();
     while !parser.is_empty() {
         match parser.section().context("failed to parse section")? {
-            Section::Type(types) => {
+            Section::Type(section) => {
                 let ret = printer.result_mut();
-                for (i, ty) in types.into_iter().enumerate() {
+                for (i, ty) in section.into_iter().enumerate() {
                     let ty = ty.context("failed to parse type")?;
+                    types.push(fold::Arity::new(&ty.params, &ty.results));
+                    type_sigs.push((ty.params.clone(), ty.results.clone()));
                     write!(ret, "\n  (@interface type (;{};) (func", i)?;
                     for param in ty.params.iter() {
-                        ret.push_str(" (param ");
-                        push_ty(ret, param);
-                        ret.push_str(")");
+                        write!(ret, " (param {})", param)?;
                     }
                     for result in ty.results.iter() {
-                        ret.push_str(" (result ");
-                        push_ty(ret, result);
-                        ret.push_str(")");
+                        write!(ret, " (result {})", result)?;
                     }
                     ret.push_str("))");
                 }
@@ -70,12 +249,18 @@ fn print_wit(printer: &mut Printer, offset: usize, bytes: &[u8]) -> anyhow::Resu
                 let ret = printer.result_mut();
                 for i in imports {
                     let i = i.context("failed to parse import")?;
-                    write!(
-                        ret,
-                        "\n  (@interface import \"{}\" \"{}\" \
-                         (func (;{};) (type {})))",
-                        i.module, i.name, func, i.ty,
-                    )?;
+                    funcs.push(types.get(i.ty as usize).copied());
+                    ret.push_str("\n  (@interface import ");
+                    push_str(ret, i.module);
+                    ret.push(' ');
+                    push_str(ret, i.name);
+                    write!(ret, " (func (;{};) (type {})", func, i.ty)?;
+                    if !push_named_params(ret, &type_sigs, i.ty, param_names.get(&func))
+                        && type_comments
+                    {
+                        push_type_comment(ret, &type_sigs, i.ty);
+                    }
+                    ret.push_str("))");
                     func += 1;
                 }
             }
@@ -84,141 +269,321 @@ fn print_wit(printer: &mut Printer, offset: usize, bytes: &[u8]) -> anyhow::Resu
                 for e in exports {
                     let e = e.context("failed to parse export")?;
                     ret.push_str("\n  (@interface export ");
-                    ret.push_str("\"");
-                    ret.push_str(e.name);
-                    ret.push_str("\" (func ");
+                    push_str(ret, e.name);
+                    ret.push_str(" (func ");
                     ret.push_str(&format!("{}", e.func));
                     ret.push_str("))");
                 }
             }
-            Section::Func(funcs) => {
-                for f in funcs {
-                    let f = f.context("failed to parse func")?;
-                    write!(
-                        printer.result_mut(),
-                        "\n  (@interface func (;{};) (type {})",
-                        func,
-                        f.ty
-                    )?;
-                    for instr in f.instrs() {
-                        let instr = instr.context("failed to parse instruction")?;
-                        printer.result_mut().push_str("\n    ");
-                        push_instr(printer, &instr)?;
+            Section::Func(section) => {
+                // All adapter signatures are needed up front to fold calls to
+                // adapters defined later in the section.
+                let section = section
+                    .into_iter()
+                    .collect::<Result<Vec<_>, _>>()
+                    .context("failed to parse func")?;
+                funcs.extend(section.iter().map(|f| types.get(f.ty as usize).copied()));
+                for f in section {
+                    let names = param_names.get(&func).unwrap_or(&no_names);
+                    let ret = printer.result_mut();
+                    write!(ret, "\n  (@interface func (;{};) (type {})", func, f.ty)?;
+                    if !push_named_params(ret, &type_sigs, f.ty, Some(names)) && type_comments {
+                        push_type_comment(ret, &type_sigs, f.ty);
+                    }
+                    let instrs = f
+                        .instrs()
+                        .collect::<Result<Vec<_>, _>>()
+                        .context("failed to parse instruction")?;
+                    let folded = match &core.arities {
+                        Some(arities) => fold::fold(&instrs, arities, &funcs),
+                        None => None,
+                    };
+                    match folded {
+                        Some(exprs) => {
+                            for expr in exprs.iter() {
+                                printer.result_mut().push_str("\n    ");
+                                expr.print(printer, &core.names, names, 4)?;
+                            }
+                        }
+                        None => {
+                            for instr in instrs.iter() {
+                                printer.result_mut().push_str("\n    ");
+                                push_instr(printer, &core.names, names, instr)?;
+                            }
+                        }
                     }
-                    printer.result_mut().push_str(")");
+                    printer.result_mut().push(')');
                     func += 1;
                 }
             }
             Section::Implement(implements) => {
+                let ret = printer.result_mut();
                 for i in implements {
                     let i = i.context("failed to parse implement")?;
-                    printer
-                        .result_mut()
-                        .push_str("\n  (@interface implement (func ");
-                    printer.print_func_idx(i.core_func)?;
-                    printer.result_mut().push_str(") (func ");
-                    printer
-                        .result_mut()
-                        .push_str(&format!("{}", i.adapter_func));
-                    printer.result_mut().push_str("))");
+                    ret.push_str("\n  (@interface implement (func ");
+                    push_core_func(ret, &core.names, i.core_func);
+                    write!(ret, ") (func {}))", i.adapter_func)?;
                 }
             }
+            // Names were already read before printing anything else.
+            Section::ParamNames(_) => {}
+            // Subsections that aren't understood can't be printed, but note
+            // that they were there.
+            Section::Unknown { id, bytes } => {
+                write!(
+                    printer.result_mut(),
+                    "\n  (; unknown subsection {}: {} bytes ;)",
+                    id,
+                    bytes.len()
+                )?;
+            }
         }
     }
-    return Ok(());
-
-    fn push_ty(ret: &mut String, param: &ValType) {
-        match param {
-            ValType::S8 => ret.push_str("s8"),
-            ValType::S16 => ret.push_str("s16"),
-            ValType::S32 => ret.push_str("s32"),
-            ValType::S64 => ret.push_str("s64"),
-            ValType::U8 => ret.push_str("u8"),
-            ValType::U16 => ret.push_str("u16"),
-            ValType::U32 => ret.push_str("u32"),
-            ValType::U64 => ret.push_str("u64"),
-            ValType::F32 => ret.push_str("f32"),
-            ValType::F64 => ret.push_str("f64"),
-            ValType::String => ret.push_str("string"),
-            ValType::Externref => ret.push_str("externref"),
-            ValType::I32 => ret.push_str("i32"),
-            ValType::I64 => ret.push_str("i64"),
-        }
-    }
+    Ok(())
+}
 
-    fn push_instr(ret: &mut Printer, instr: &Instruction) -> anyhow::Result<()> {
-        use Instruction::*;
+/// Converts adapters printed by [`print_wit`], which are indented to be within
+/// a module, to the top level of a file.
+fn dedent(adapters: &str) -> String {
+    let mut ret = String::new();
+    for line in adapters.lines().filter(|l| !l.is_empty()) {
+        ret.push_str(line.strip_prefix("  ").unwrap_or(line));
+        ret.push('\n');
+    }
+    ret
+}
 
-        match instr {
-            ArgGet(i) => write!(ret.result_mut(), "arg.get {}", i)?,
-            CallCore(i) => {
-                ret.result_mut().push_str("call-core ");
-                ret.print_func_idx(*i)?;
-            }
-            End => ret.result_mut().push_str("end"),
-            MemoryToString(mem) => {
-                ret.result_mut().push_str("memory-to-string");
-                if *mem != 0 {
-                    write!(ret.result_mut(), " {}", mem)?;
+/// Reads the names of adapter function parameters, keyed by function index
+/// and then parameter index.
+///
+/// Names which can't be printed as a text format identifier are skipped, and
+/// those parameters are referred to by index instead.
+fn read_param_names(mut parser: Parser<'_>) -> anyhow::Result<HashMap<u32, HashMap<u32, &str>>> {
+    let mut ret = HashMap::new();
+    while !parser.is_empty() {
+        if let Section::ParamNames(names) = parser.section().context("failed to parse section")? {
+            for name in names {
+                let name = name.context("failed to parse param name")?;
+                if is_id(name.name) {
+                    ret.entry(name.func)
+                        .or_insert_with(HashMap::new)
+                        .insert(name.param, name.name);
                 }
             }
-            StringToMemory(payload) => {
-                ret.result_mut().push_str("string-to-memory ");
-                ret.print_func_idx(payload.malloc)?;
-                if payload.mem != 0 {
-                    write!(ret.result_mut(), " {}", payload.mem)?;
+        }
+    }
+    Ok(ret)
+}
+
+/// Reads the names of core functions from the `name` section of `wasm`.
+///
+/// Names which can't be printed as a text format identifier, or which are
+/// shared by several functions, are skipped and those functions are referred
+/// to by index instead.
+fn core_names(wasm: &[u8]) -> anyhow::Result<HashMap<u32, String>> {
+    let mut names = HashMap::new();
+    for payload in wasmparser::Parser::new(0).parse_all(wasm) {
+        let reader = match payload.context("failed to parse core module")? {
+            wasmparser::Payload::CustomSection {
+                name: "name",
+                data,
+                data_offset,
+            } => wasmparser::NameSectionReader::new(data, data_offset)?,
+            _ => continue,
+        };
+        for name in reader {
+            if let wasmparser::Name::Function(n) = name? {
+                let mut map = n.get_map()?;
+                for _ in 0..map.get_count() {
+                    let naming = map.read()?;
+                    names.insert(naming.index, naming.name);
                 }
             }
-            CallAdapter(f) => write!(ret.result_mut(), "call-adapter {}", f)?,
-            DeferCallCore(f) => {
-                ret.result_mut().push_str("defer-call-core ");
-                ret.print_func_idx(*f)?;
+        }
+    }
+    let mut uses = HashMap::new();
+    for name in names.values() {
+        *uses.entry(*name).or_insert(0) += 1;
+    }
+    Ok(names
+        .iter()
+        .filter(|(_, name)| is_id(name) && uses[*name] == 1)
+        .map(|(idx, name)| (*idx, name.to_string()))
+        .collect())
+}
+
+fn is_id(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-./:<=>?@\\^_`|~".contains(c))
+}
+
+/// Prints the parameters of the type `ty` inline after a `(type N)` reference
+/// if any of them are named, so the names can be parsed back in. The results
+/// are printed too so the inline type matches `ty`.
+///
+/// Returns whether the type was printed.
+fn push_named_params(
+    ret: &mut String,
+    type_sigs: &[(Vec<ValType>, Vec<ValType>)],
+    ty: u32,
+    names: Option<&HashMap<u32, &str>>,
+) -> bool {
+    let ((params, results), names) = match (type_sigs.get(ty as usize), names) {
+        (Some(sig), Some(names)) if !names.is_empty() => (sig, names),
+        _ => return false,
+    };
+    for (i, param) in params.iter().enumerate() {
+        ret.push_str(" (param ");
+        if let Some(name) = names.get(&(i as u32)) {
+            write!(ret, "${} ", name).unwrap();
+        }
+        write!(ret, "{})", param).unwrap();
+    }
+    for result in results.iter() {
+        write!(ret, " (result {})", result).unwrap();
+    }
+    true
+}
+
+/// Prints the signature of the type `ty` as a comment after a `(type N)`
+/// reference.
+fn push_type_comment(ret: &mut String, type_sigs: &[(Vec<ValType>, Vec<ValType>)], ty: u32) {
+    if let Some((params, results)) = type_sigs.get(ty as usize) {
+        write!(ret, " (; {} ;)", interface::signature(params, results)).unwrap();
+    }
+}
+
+/// Pushes a reference to the core function `idx`, by name if it has one.
+fn push_core_func(ret: &mut String, names: &HashMap<u32, String>, idx: u32) {
+    match names.get(&idx) {
+        Some(name) => write!(ret, "${}", name).unwrap(),
+        None => write!(ret, "{}", idx).unwrap(),
+    }
+}
+
+/// Pushes `s` as a quoted string, escaping everything other than printable
+/// ASCII characters.
+fn push_str(ret: &mut String, s: &str) {
+    ret.push('"');
+    for byte in s.bytes() {
+        match byte {
+            b'"' | b'\\' => {
+                ret.push('\\');
+                ret.push(byte as char);
             }
+            b' '..=b'~' => ret.push(byte as char),
+            _ => ret.push_str(&format!("\\{:02x}", byte)),
+        }
+    }
+    ret.push('"');
+}
+fn push_instr(
+    ret: &mut Printer,
+    core_names: &HashMap<u32, String>,
+    params: &HashMap<u32, &str>,
+    instr: &Instruction,
+) -> anyhow::Result<()> {
+    use Instruction::*;
 
-            I32ToS8 => ret.result_mut().push_str("i32-to-s8"),
-            I32ToS8X => ret.result_mut().push_str("i32-to-s8x"),
-            I32ToU8 => ret.result_mut().push_str("i32-to-u8"),
-            I32ToS16 => ret.result_mut().push_str("i32-to-s16"),
-            I32ToS16X => ret.result_mut().push_str("i32-to-s16x"),
-            I32ToU16 => ret.result_mut().push_str("i32-to-u16"),
-            I32ToS32 => ret.result_mut().push_str("i32-to-s32"),
-            I32ToU32 => ret.result_mut().push_str("i32-to-u32"),
-            I32ToS64 => ret.result_mut().push_str("i32-to-s64"),
-            I32ToU64 => ret.result_mut().push_str("i32-to-u64"),
-
-            I64ToS8 => ret.result_mut().push_str("i64-to-s8"),
-            I64ToS8X => ret.result_mut().push_str("i64-to-s8x"),
-            I64ToU8 => ret.result_mut().push_str("i64-to-u8"),
-            I64ToS16 => ret.result_mut().push_str("i64-to-s16"),
-            I64ToS16X => ret.result_mut().push_str("i64-to-s16x"),
-            I64ToU16 => ret.result_mut().push_str("i64-to-u16"),
-            I64ToS32 => ret.result_mut().push_str("i64-to-s32"),
-            I64ToS32X => ret.result_mut().push_str("i64-to-s32x"),
-            I64ToU32 => ret.result_mut().push_str("i64-to-u32"),
-            I64ToS64 => ret.result_mut().push_str("i64-to-s64"),
-            I64ToU64 => ret.result_mut().push_str("i64-to-u64"),
-
-            S8ToI32 => ret.result_mut().push_str("s8-to-i32"),
-            U8ToI32 => ret.result_mut().push_str("u8-to-i32"),
-            S16ToI32 => ret.result_mut().push_str("s16-to-i32"),
-            U16ToI32 => ret.result_mut().push_str("u16-to-i32"),
-            S32ToI32 => ret.result_mut().push_str("s32-to-i32"),
-            U32ToI32 => ret.result_mut().push_str("u32-to-i32"),
-            S64ToI32 => ret.result_mut().push_str("s64-to-i32"),
-            S64ToI32X => ret.result_mut().push_str("s64-to-i32x"),
-            U64ToI32 => ret.result_mut().push_str("u64-to-i32"),
-            U64ToI32X => ret.result_mut().push_str("u64-to-i32x"),
-
-            S8ToI64 => ret.result_mut().push_str("s8-to-i64"),
-            U8ToI64 => ret.result_mut().push_str("u8-to-i64"),
-            S16ToI64 => ret.result_mut().push_str("s16-to-i64"),
-            U16ToI64 => ret.result_mut().push_str("u16-to-i64"),
-            S32ToI64 => ret.result_mut().push_str("s32-to-i64"),
-            U32ToI64 => ret.result_mut().push_str("u32-to-i64"),
-            S64ToI64 => ret.result_mut().push_str("s64-to-i64"),
-            U64ToI64 => ret.result_mut().push_str("u64-to-i64"),
+    match instr {
+        ArgGet(i) => match params.get(i) {
+            Some(name) => write!(ret.result_mut(), "arg.get ${}", name)?,
+            None => write!(ret.result_mut(), "arg.get {}", i)?,
+        },
+        CallCore(i) => {
+            ret.result_mut().push_str("call-core ");
+            push_core_func(ret.result_mut(), core_names, *i);
+        }
+        MemoryToString(mem) => {
+            ret.result_mut().push_str("memory-to-string");
+            if *mem != 0 {
+                write!(ret.result_mut(), " {}", mem)?;
+            }
         }
+        StringToMemory(payload) => {
+            ret.result_mut().push_str("string-to-memory ");
+            push_core_func(ret.result_mut(), core_names, payload.malloc);
+            if payload.mem != 0 {
+                write!(ret.result_mut(), " {}", payload.mem)?;
+            }
+        }
+        CallAdapter(f) => write!(ret.result_mut(), "call-adapter {}", f)?,
+        DeferCallCore(f) => {
+            ret.result_mut().push_str("defer-call-core ");
+            push_core_func(ret.result_mut(), core_names, *f);
+        }
+
+        Extension(ext) => {
+            write!(ret.result_mut(), "extension {} \"", ext.opcode)?;
+            for byte in ext.immediates.iter() {
+                write!(ret.result_mut(), "\\{:02x}", byte)?;
+            }
+            ret.result_mut().push('"');
+        }
+        other => ret.result_mut().push_str(instr_name(other)),
+    }
+
+    Ok(())
+}
+
+/// Returns the text format name of `instr`, without any of its immediates.
+fn instr_name(instr: &Instruction) -> &'static str {
+    use Instruction::*;
+
+    match instr {
+        ArgGet(_) => "arg.get",
+        CallCore(_) => "call-core",
+        End => "end",
+        MemoryToString(_) => "memory-to-string",
+        StringToMemory(_) => "string-to-memory",
+        CallAdapter(_) => "call-adapter",
+        DeferCallCore(_) => "defer-call-core",
+
+        I32ToS8 => "i32-to-s8",
+        I32ToS8X => "i32-to-s8x",
+        I32ToU8 => "i32-to-u8",
+        I32ToS16 => "i32-to-s16",
+        I32ToS16X => "i32-to-s16x",
+        I32ToU16 => "i32-to-u16",
+        I32ToS32 => "i32-to-s32",
+        I32ToU32 => "i32-to-u32",
+        I32ToS64 => "i32-to-s64",
+        I32ToU64 => "i32-to-u64",
+
+        I64ToS8 => "i64-to-s8",
+        I64ToS8X => "i64-to-s8x",
+        I64ToU8 => "i64-to-u8",
+        I64ToS16 => "i64-to-s16",
+        I64ToS16X => "i64-to-s16x",
+        I64ToU16 => "i64-to-u16",
+        I64ToS32 => "i64-to-s32",
+        I64ToS32X => "i64-to-s32x",
+        I64ToU32 => "i64-to-u32",
+        I64ToS64 => "i64-to-s64",
+        I64ToU64 => "i64-to-u64",
+
+        S8ToI32 => "s8-to-i32",
+        U8ToI32 => "u8-to-i32",
+        S16ToI32 => "s16-to-i32",
+        U16ToI32 => "u16-to-i32",
+        S32ToI32 => "s32-to-i32",
+        U32ToI32 => "u32-to-i32",
+        S64ToI32 => "s64-to-i32",
+        S64ToI32X => "s64-to-i32x",
+        U64ToI32 => "u64-to-i32",
+        U64ToI32X => "u64-to-i32x",
+
+        S8ToI64 => "s8-to-i64",
+        U8ToI64 => "u8-to-i64",
+        S16ToI64 => "s16-to-i64",
+        U16ToI64 => "u16-to-i64",
+        S32ToI64 => "s32-to-i64",
+        U32ToI64 => "u32-to-i64",
+        S64ToI64 => "s64-to-i64",
+        U64ToI64 => "u64-to-i64",
 
-        Ok(())
+        Extension(_) => "extension",
     }
 }
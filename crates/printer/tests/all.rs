@@ -0,0 +1,250 @@
+use anyhow::Result;
+
+#[test]
+fn print_interface() -> Result<()> {
+    let wasm = wit_text::parse_str(
+        r#"
+            (module
+                (@interface func (import "b" "x") (param string) (result u64) (result s32))
+                (@interface func (import "a" "z"))
+                (@interface func (import "a" "y") (param f32))
+                (@interface func (export "greet") (param string) (param u32) (result string)
+                    arg.get 0)
+                (@interface func (export "add") (param s8) (result s8)
+                    arg.get 0)
+                (@interface func (export "a\"b\7f\c3\a9") (param s8) (result s8)
+                    arg.get 0)
+            )
+        "#,
+    )?;
+    assert_eq!(
+        wit_printer::print_interface(&wasm)?,
+        r#"import "a" {
+  "y": func(f32)
+  "z": func()
+}
+
+import "b" {
+  "x": func(string) -> (u64, s32)
+}
+
+export {
+  "a\"b\7f\c3\a9": func(s8) -> s8
+  "add": func(s8) -> s8
+  "greet": func(string, u32) -> string
+}
+"#
+    );
+
+    assert_eq!(
+        wit_printer::print_interface(&wit_text::parse_str("(module)")?)?,
+        ""
+    );
+    Ok(())
+}
+
+#[test]
+fn fold_instructions() -> Result<()> {
+    let wasm = wit_text::parse_str(
+        r#"
+            (module
+                (func $add (param i32 i32) (result i32)
+                    local.get 0)
+                (@interface func (export "add") (param s32) (param s32) (result s32)
+                    arg.get 0
+                    s32-to-i32
+                    arg.get 1
+                    s32-to-i32
+                    call-core $add
+                    i32-to-s32)
+            )
+        "#,
+    )?;
+    let text = wit_printer::Config::new()
+        .fold_instructions(true)
+        .print_bytes(&wasm)?;
+    assert!(
+        text.contains(
+            "  (@interface func (;0;) (type 0)
+    (i32-to-s32
+      (call-core $add
+        (s32-to-i32
+          (arg.get 0))
+        (s32-to-i32
+          (arg.get 1)))))"
+        ),
+        "{}",
+        text
+    );
+    assert_eq!(wit_text::parse_str(&text)?, wasm);
+    Ok(())
+}
+
+#[test]
+fn dump() -> Result<()> {
+    let wasm = wit_text::parse_str(
+        r#"
+            (module
+                (func $f (param i32))
+                (@interface func (export "f") (param s32)
+                    arg.get 0
+                    s32-to-i32
+                    call-core $f)
+            )
+        "#,
+    )?;
+    assert_eq!(
+        wit_printer::dump_bytes(&wasm)?,
+        r#"0x00003d | 05 30 2e 31 2e 30       | version "0.1.0"
+0x000043 | 00 04                   | type subsection, 4 bytes
+0x000045 | 01                      | count 1
+0x000046 | 01 02 00                | type 0: (func (param s32))
+0x000049 | 02 09                   | func subsection, 9 bytes
+0x00004b | 01                      | count 1
+0x00004c | 07                      | func 0: body size 7
+0x00004d | 00                      | type 0
+0x00004e | 00                      | arg.get
+0x00004f | 00                      |   index 0
+0x000050 | 20                      | s32-to-i32
+0x000051 | 01                      | call-core
+0x000052 | 00                      |   core func 0
+0x000053 | 02                      | end
+0x000054 | 03 04                   | export subsection, 4 bytes
+0x000056 | 01                      | count 1
+0x000057 | 00 01 66                | export "f" (func 0)
+"#
+    );
+
+    // Malformed sections are dumped up to the error.
+    let wasm = wit_text::parse_str(
+        r#"(module (@custom "wasm-interface-types" "\050.1.0\00\04\01\01\ff\00"))"#,
+    )?;
+    assert_eq!(
+        wit_printer::dump_bytes(&wasm)?,
+        r#"0x00001f | 05 30 2e 31 2e 30       | version "0.1.0"
+0x000025 | 00 04                   | type subsection, 4 bytes
+0x000027 | 01                      | count 1
+error: failed to parse at byte 42: invalid value type: 255
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn adapters_only() -> Result<()> {
+    let core = wit_text::parse_str(
+        r#"
+            (module
+                (func $log (param i32))
+            )
+        "#,
+    )?;
+    let wasm = wit_text::parse_str(
+        r#"
+            (module
+                (func $log (param i32))
+                (@interface func (export "log") (param $x s32)
+                    arg.get $x
+                    s32-to-i32
+                    call-core $log)
+            )
+        "#,
+    )?;
+    let text = wit_printer::Config::new()
+        .adapters_only(true)
+        .print_bytes(&wasm)?;
+    assert_eq!(
+        text,
+        r#"(@interface type (;0;) (func (param s32)))
+(@interface func (;0;) (type 0) (param $x s32)
+  arg.get $x
+  s32-to-i32
+  call-core $log)
+(@interface export "log" (func 0))
+"#
+    );
+    assert_eq!(wit_text::parse_str_with_core(&text, &core)?, wasm);
+
+    // A bare section has no core module to name functions with.
+    let section = wasmparser::Parser::new(0)
+        .parse_all(&wasm)
+        .find_map(|payload| match payload {
+            Ok(wasmparser::Payload::CustomSection {
+                name: "wasm-interface-types",
+                data,
+                ..
+            }) => Some(data),
+            _ => None,
+        })
+        .unwrap();
+    let text = wit_printer::print_bytes(section)?;
+    assert_eq!(
+        text,
+        r#"(@interface type (;0;) (func (param s32)))
+(@interface func (;0;) (type 0) (param $x s32)
+  arg.get $x
+  s32-to-i32
+  call-core 0)
+(@interface export "log" (func 0))
+"#
+    );
+    assert_eq!(wit_text::parse_str_with_core(&text, &core)?, wasm);
+    Ok(())
+}
+
+#[test]
+fn core_names_and_type_comments() -> Result<()> {
+    let wasm = wit_text::parse_str(
+        r#"
+            (module
+                (memory 1)
+                (func $malloc (param i32) (result i32)
+                    i32.const 0)
+                (func $log (param i32 i32))
+                (@interface func (export "log") (param string)
+                    arg.get 0
+                    string-to-memory $malloc
+                    call-core $log)
+            )
+        "#,
+    )?;
+    let text = wit_printer::Config::new()
+        .adapters_only(true)
+        .type_comments(true)
+        .print_bytes(&wasm)?;
+    assert_eq!(
+        text,
+        r#"(@interface type (;0;) (func (param string)))
+(@interface func (;0;) (type 0) (; func(string) ;)
+  arg.get 0
+  string-to-memory $malloc
+  call-core $log)
+(@interface export "log" (func 0))
+"#
+    );
+
+    let text = wit_printer::Config::new()
+        .adapters_only(true)
+        .core_indices(true)
+        .print_bytes(&wasm)?;
+    assert_eq!(
+        text,
+        r#"(@interface type (;0;) (func (param string)))
+(@interface func (;0;) (type 0)
+  arg.get 0
+  string-to-memory 0
+  call-core 1)
+(@interface export "log" (func 0))
+"#
+    );
+
+    // Comments and either form of reference parse back to the same module.
+    for indices in [false, true].iter() {
+        let text = wit_printer::Config::new()
+            .core_indices(*indices)
+            .type_comments(true)
+            .print_bytes(&wasm)?;
+        assert_eq!(wit_text::parse_str(&text)?, wasm);
+    }
+    Ok(())
+}
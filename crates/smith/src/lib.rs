@@ -0,0 +1,557 @@
+//! Generation of random, valid wasm modules with an interface types section.
+//!
+//! A [`Module`] implements [`Arbitrary`], so it can be created from the raw
+//! bytes handed out by a fuzzer. Every generated module passes
+//! `wit-validator`: adapters only ever use values of the right type, calls
+//! only refer to adapters which have already been defined, and so on. This is
+//! intended for differential testing of the tools in this repository, for
+//! example checking that a module survives being printed and parsed again.
+//!
+//! The core module has a few imported and local functions, whose bodies are
+//! just `unreachable`, along with a single memory. The interface types section
+//! uses every instruction except for extension instructions, which can't be
+//! validated.
+
+#![deny(missing_docs)]
+
+use arbitrary::{Arbitrary, Result, Unstructured};
+use std::collections::HashSet;
+use std::fmt::Write;
+use wit_ir::{Instruction, StringToMemory, ValType};
+
+/// A randomly generated wasm module with an interface types section.
+#[derive(Debug)]
+pub struct Module {
+    core_imports: Vec<Signature>,
+    core_funcs: Vec<Signature>,
+    import_memory: bool,
+    wit: wit_ir::Module,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Signature {
+    params: Vec<ValType>,
+    results: Vec<ValType>,
+}
+
+/// Value types which can be used in core wasm signatures.
+const CORE_TYPES: &[ValType] = &[
+    ValType::I32,
+    ValType::I64,
+    ValType::F32,
+    ValType::F64,
+    ValType::Externref,
+];
+
+const ADAPTER_TYPES: &[ValType] = &[
+    ValType::S8,
+    ValType::S16,
+    ValType::S32,
+    ValType::S64,
+    ValType::U8,
+    ValType::U16,
+    ValType::U32,
+    ValType::U64,
+    ValType::F32,
+    ValType::F64,
+    ValType::String,
+    ValType::Externref,
+    ValType::I32,
+    ValType::I64,
+];
+
+/// Every conversion instruction, along with the type it consumes and the type
+/// it produces.
+const CONVERSIONS: &[(Instruction, ValType, ValType)] = &[
+    (Instruction::I32ToS8, ValType::I32, ValType::S8),
+    (Instruction::I32ToS8X, ValType::I32, ValType::S8),
+    (Instruction::I32ToU8, ValType::I32, ValType::U8),
+    (Instruction::I32ToS16, ValType::I32, ValType::S16),
+    (Instruction::I32ToS16X, ValType::I32, ValType::S16),
+    (Instruction::I32ToU16, ValType::I32, ValType::U16),
+    (Instruction::I32ToS32, ValType::I32, ValType::S32),
+    (Instruction::I32ToU32, ValType::I32, ValType::U32),
+    (Instruction::I32ToS64, ValType::I32, ValType::S64),
+    (Instruction::I32ToU64, ValType::I32, ValType::U64),
+    (Instruction::I64ToS8, ValType::I64, ValType::S8),
+    (Instruction::I64ToS8X, ValType::I64, ValType::S8),
+    (Instruction::I64ToU8, ValType::I64, ValType::U8),
+    (Instruction::I64ToS16, ValType::I64, ValType::S16),
+    (Instruction::I64ToS16X, ValType::I64, ValType::S16),
+    (Instruction::I64ToU16, ValType::I64, ValType::U16),
+    (Instruction::I64ToS32, ValType::I64, ValType::S32),
+    (Instruction::I64ToS32X, ValType::I64, ValType::S32),
+    (Instruction::I64ToU32, ValType::I64, ValType::U32),
+    (Instruction::I64ToS64, ValType::I64, ValType::S64),
+    (Instruction::I64ToU64, ValType::I64, ValType::U64),
+    (Instruction::S8ToI32, ValType::S8, ValType::I32),
+    (Instruction::U8ToI32, ValType::U8, ValType::I32),
+    (Instruction::S16ToI32, ValType::S16, ValType::I32),
+    (Instruction::U16ToI32, ValType::U16, ValType::I32),
+    (Instruction::S32ToI32, ValType::S32, ValType::I32),
+    (Instruction::U32ToI32, ValType::U32, ValType::I32),
+    (Instruction::S64ToI32, ValType::S64, ValType::I32),
+    (Instruction::S64ToI32X, ValType::S64, ValType::I32),
+    (Instruction::U64ToI32, ValType::U64, ValType::I32),
+    (Instruction::U64ToI32X, ValType::U64, ValType::I32),
+    (Instruction::S8ToI64, ValType::S8, ValType::I64),
+    (Instruction::U8ToI64, ValType::U8, ValType::I64),
+    (Instruction::S16ToI64, ValType::S16, ValType::I64),
+    (Instruction::U16ToI64, ValType::U16, ValType::I64),
+    (Instruction::S32ToI64, ValType::S32, ValType::I64),
+    (Instruction::U32ToI64, ValType::U32, ValType::I64),
+    (Instruction::S64ToI64, ValType::S64, ValType::I64),
+    (Instruction::U64ToI64, ValType::U64, ValType::I64),
+];
+
+/// How deeply nested the expressions of an adapter function may be.
+const MAX_DEPTH: u32 = 4;
+
+impl<'a> Arbitrary<'a> for Module {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Module> {
+        let mut module = Module {
+            core_imports: Vec::new(),
+            core_funcs: Vec::new(),
+            import_memory: u.arbitrary()?,
+            wit: wit_ir::Module::default(),
+        };
+        module.core(u)?;
+        module.adapters(u)?;
+        Ok(module)
+    }
+}
+
+impl Module {
+    /// Returns the interface types section of this module.
+    pub fn wit(&self) -> &wit_ir::Module {
+        &self.wit
+    }
+
+    /// Encodes this module as a wasm binary, with the interface types section
+    /// at the end.
+    ///
+    /// The interface types section is left out if it's empty.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut wat = String::from("(module\n");
+        for (i, sig) in self.core_imports.iter().enumerate() {
+            write!(wat, "  (import \"core\" \"f{}\" (func", i).unwrap();
+            write_signature(&mut wat, sig);
+            wat.push_str("))\n");
+        }
+        if self.import_memory {
+            wat.push_str("  (import \"core\" \"memory\" (memory 1))\n");
+        }
+        for sig in self.core_funcs.iter() {
+            wat.push_str("  (func");
+            write_signature(&mut wat, sig);
+            wat.push_str(" unreachable)\n");
+        }
+        if !self.import_memory {
+            wat.push_str("  (memory 1)\n");
+        }
+        wat.push(')');
+
+        let mut wasm = wat::parse_str(&wat).expect("generated core module is valid");
+        if self.wit != wit_ir::Module::default() {
+            wasm.extend_from_slice(&self.wit.to_custom_section());
+        }
+        wasm
+    }
+
+    fn core(&mut self, u: &mut Unstructured<'_>) -> Result<()> {
+        for _ in 0..u.int_in_range(0..=4)? {
+            let sig = signature(u, CORE_TYPES)?;
+            self.core_imports.push(sig);
+        }
+
+        // Make sure that there's always a way to create a value of any core
+        // type, a `malloc` for `string-to-memory`, and a function with no
+        // results for `defer-call-core`.
+        for ty in CORE_TYPES {
+            self.core_funcs.push(Signature {
+                params: Vec::new(),
+                results: vec![*ty],
+            });
+        }
+        self.core_funcs.push(Signature {
+            params: vec![ValType::I32],
+            results: vec![ValType::I32],
+        });
+        self.core_funcs.push(Signature {
+            params: vec![ValType::I32],
+            results: Vec::new(),
+        });
+
+        for _ in 0..u.int_in_range(0..=4)? {
+            let sig = signature(u, CORE_TYPES)?;
+            self.core_funcs.push(sig);
+        }
+        Ok(())
+    }
+
+    fn adapters(&mut self, u: &mut Unstructured<'_>) -> Result<()> {
+        for _ in 0..u.int_in_range(0..=6)? {
+            let sig = signature(u, ADAPTER_TYPES)?;
+            self.wit.types.push(wit_ir::Type {
+                params: sig.params,
+                results: sig.results,
+            });
+        }
+
+        // Core imports which will be implemented each get an adapter type with
+        // the same signature.
+        let mut implements = Vec::new();
+        for (i, sig) in self.core_imports.iter().enumerate() {
+            if u.arbitrary()? {
+                implements.push((i as u32, self.wit.types.len() as u32));
+                self.wit.types.push(wit_ir::Type {
+                    params: sig.params.clone(),
+                    results: sig.results.clone(),
+                });
+            }
+        }
+        if self.wit.types.is_empty() {
+            return Ok(());
+        }
+
+        let ntypes = self.wit.types.len() as u32;
+        for _ in 0..u.int_in_range(0..=3)? {
+            self.wit.imports.push(wit_ir::Import {
+                module: u.arbitrary()?,
+                name: u.arbitrary()?,
+                ty: u.int_in_range(0..=ntypes - 1)?,
+            });
+        }
+        let mut tys = Vec::new();
+        for _ in 0..u.int_in_range(0..=5)? {
+            tys.push(u.int_in_range(0..=ntypes - 1)?);
+        }
+        for (core_func, ty) in implements {
+            self.wit.implements.push(wit_ir::Implement {
+                core_func,
+                adapter_func: (self.wit.imports.len() + tys.len()) as u32,
+            });
+            tys.push(ty);
+        }
+        for ty in tys {
+            let func = self.wit.imports.len() + self.wit.funcs.len();
+            self.wit.funcs.push(wit_ir::Func {
+                ty,
+                instrs: Vec::new(),
+            });
+            let ty = &self.wit.types[ty as usize];
+            let mut body = Body {
+                module: self,
+                params: &ty.params,
+                callable: func + 1,
+                instrs: Vec::new(),
+            };
+            body.produce_all(u, &ty.results, MAX_DEPTH)?;
+            let instrs = body.instrs;
+            self.wit.funcs.last_mut().unwrap().instrs = instrs;
+        }
+
+        let nfuncs = (self.wit.imports.len() + self.wit.funcs.len()) as u32;
+        if nfuncs > 0 {
+            let mut names = HashSet::new();
+            for _ in 0..u.int_in_range(0..=3)? {
+                let name: String = u.arbitrary()?;
+                if names.insert(name.clone()) {
+                    self.wit.exports.push(wit_ir::Export {
+                        name,
+                        func: u.int_in_range(0..=nfuncs - 1)?,
+                    });
+                }
+            }
+        }
+
+        for func in 0..nfuncs {
+            let params = self.adapter_type(func).params.len() as u32;
+            let mut names = HashSet::new();
+            for param in 0..params {
+                if !u.arbitrary()? {
+                    continue;
+                }
+                let name = identifier(u)?;
+                if names.insert(name.clone()) {
+                    self.wit
+                        .param_names
+                        .push(wit_ir::ParamName { func, param, name });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn adapter_type(&self, func: u32) -> &wit_ir::Type {
+        let func = func as usize;
+        let ty = match self.wit.imports.get(func) {
+            Some(import) => import.ty,
+            None => self.wit.funcs[func - self.wit.imports.len()].ty,
+        };
+        &self.wit.types[ty as usize]
+    }
+
+    fn core_func_count(&self) -> u32 {
+        (self.core_imports.len() + self.core_funcs.len()) as u32
+    }
+
+    fn core_func(&self, func: u32) -> &Signature {
+        let func = func as usize;
+        match self.core_imports.get(func) {
+            Some(sig) => sig,
+            None => &self.core_funcs[func - self.core_imports.len()],
+        }
+    }
+
+    /// Returns the local core function with no parameters which returns `ty`.
+    fn core_constant(&self, ty: ValType) -> u32 {
+        let i = CORE_TYPES.iter().position(|t| *t == ty).unwrap();
+        (self.core_imports.len() + i) as u32
+    }
+
+    fn malloc(&self) -> u32 {
+        (self.core_imports.len() + CORE_TYPES.len()) as u32
+    }
+}
+
+fn signature(u: &mut Unstructured<'_>, tys: &[ValType]) -> Result<Signature> {
+    let mut params = Vec::new();
+    for _ in 0..u.int_in_range(0..=4)? {
+        params.push(*u.choose(tys)?);
+    }
+    let mut results = Vec::new();
+    for _ in 0..u.int_in_range(0..=2)? {
+        results.push(*u.choose(tys)?);
+    }
+    Ok(Signature { params, results })
+}
+
+fn write_signature(wat: &mut String, sig: &Signature) {
+    for param in sig.params.iter() {
+        write!(wat, " (param {})", core_type(*param)).unwrap();
+    }
+    for result in sig.results.iter() {
+        write!(wat, " (result {})", core_type(*result)).unwrap();
+    }
+}
+
+fn core_type(ty: ValType) -> &'static str {
+    match ty {
+        ValType::I32 => "i32",
+        ValType::I64 => "i64",
+        ValType::F32 => "f32",
+        ValType::F64 => "f64",
+        ValType::Externref => "externref",
+        _ => unreachable!(),
+    }
+}
+
+/// Generates a name which is valid as a `$`-prefixed identifier in the text
+/// format.
+fn identifier(u: &mut Unstructured<'_>) -> Result<String> {
+    const CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789_";
+    let mut name = String::new();
+    name.push(*u.choose(&CHARS[..26])? as char);
+    for _ in 0..u.int_in_range(0..=8)? {
+        name.push(*u.choose(CHARS)? as char);
+    }
+    Ok(name)
+}
+
+/// Generator of the body of one adapter function.
+///
+/// Bodies are built by producing each of the function's results in turn,
+/// each of which is produced by an instruction whose operands are recursively
+/// produced first.
+struct Body<'a> {
+    module: &'a Module,
+    params: &'a [ValType],
+    /// Adapter functions with an index below this one have been defined and
+    /// can be called.
+    callable: usize,
+    instrs: Vec<Instruction>,
+}
+
+enum Choice {
+    ArgGet(u32),
+    CallCore(u32),
+    CallAdapter(u32),
+    Convert(usize),
+    MemoryToString,
+    StringToMemory,
+}
+
+impl Body<'_> {
+    /// Pushes values of all of `tys` onto the stack, in order.
+    fn produce_all(&mut self, u: &mut Unstructured<'_>, tys: &[ValType], depth: u32) -> Result<()> {
+        let mut i = 0;
+        while i < tys.len() {
+            if depth > 0 && u.ratio(1, 8)? {
+                self.call_without_results(u, depth - 1)?;
+            }
+            i += self.produce(u, &tys[i..], depth)?;
+        }
+
+        // `defer-call-core` leaves its arguments on the stack, so its
+        // arguments can be the tail of what was just produced.
+        if u.ratio(1, 4)? {
+            let candidates = (0..self.module.core_func_count())
+                .filter(|f| {
+                    let sig = self.module.core_func(*f);
+                    sig.results.is_empty() && !sig.params.is_empty() && tys.ends_with(&sig.params)
+                })
+                .collect::<Vec<_>>();
+            if !candidates.is_empty() {
+                let func = *u.choose(&candidates)?;
+                self.instrs.push(Instruction::DeferCallCore(func));
+            }
+        }
+        Ok(())
+    }
+
+    /// Pushes a value of `tys[0]`, or possibly several values which are a
+    /// prefix of `tys`, onto the stack. Returns how many values were pushed.
+    fn produce(&mut self, u: &mut Unstructured<'_>, tys: &[ValType], depth: u32) -> Result<usize> {
+        let ty = tys[0];
+        if depth == 0 {
+            self.terminal(u, ty)?;
+            return Ok(1);
+        }
+
+        let mut choices = Vec::new();
+        for (i, param) in self.params.iter().enumerate() {
+            if *param == ty {
+                choices.push(Choice::ArgGet(i as u32));
+            }
+        }
+        for f in 0..self.module.core_func_count() {
+            let results = &self.module.core_func(f).results;
+            if !results.is_empty() && tys.starts_with(results) {
+                choices.push(Choice::CallCore(f));
+            }
+        }
+        for f in 0..self.callable as u32 {
+            let results = &self.module.adapter_type(f).results;
+            if !results.is_empty() && tys.starts_with(results) {
+                choices.push(Choice::CallAdapter(f));
+            }
+        }
+        for (i, (_, _, output)) in CONVERSIONS.iter().enumerate() {
+            if *output == ty {
+                choices.push(Choice::Convert(i));
+            }
+        }
+        if ty == ValType::String {
+            choices.push(Choice::MemoryToString);
+        }
+        if tys.starts_with(&[ValType::I32, ValType::I32]) {
+            choices.push(Choice::StringToMemory);
+        }
+        if choices.is_empty() {
+            self.terminal(u, ty)?;
+            return Ok(1);
+        }
+
+        Ok(match u.choose(&choices)? {
+            Choice::ArgGet(i) => {
+                self.instrs.push(Instruction::ArgGet(*i));
+                1
+            }
+            Choice::CallCore(f) => {
+                let sig = self.module.core_func(*f);
+                self.produce_all(u, &sig.params, depth - 1)?;
+                self.instrs.push(Instruction::CallCore(*f));
+                sig.results.len()
+            }
+            Choice::CallAdapter(f) => {
+                let ty = self.module.adapter_type(*f);
+                self.produce_all(u, &ty.params, depth - 1)?;
+                self.instrs.push(Instruction::CallAdapter(*f));
+                ty.results.len()
+            }
+            Choice::Convert(i) => {
+                let (instr, input, _) = &CONVERSIONS[*i];
+                self.produce_all(u, &[*input], depth - 1)?;
+                self.instrs.push(instr.clone());
+                1
+            }
+            Choice::MemoryToString => {
+                self.produce_all(u, &[ValType::I32, ValType::I32], depth - 1)?;
+                self.instrs.push(Instruction::MemoryToString(0));
+                1
+            }
+            Choice::StringToMemory => {
+                self.produce_all(u, &[ValType::String], depth - 1)?;
+                self.instrs
+                    .push(Instruction::StringToMemory(StringToMemory {
+                        malloc: self.module.malloc(),
+                        mem: 0,
+                    }));
+                2
+            }
+        })
+    }
+
+    /// Pushes a value of `ty` without any further nesting of calls.
+    fn terminal(&mut self, u: &mut Unstructured<'_>, ty: ValType) -> Result<()> {
+        let args = (0..self.params.len() as u32)
+            .filter(|i| self.params[*i as usize] == ty)
+            .collect::<Vec<_>>();
+        if !args.is_empty() && u.arbitrary()? {
+            let arg = *u.choose(&args)?;
+            self.instrs.push(Instruction::ArgGet(arg));
+            return Ok(());
+        }
+        match ty {
+            ValType::I32 | ValType::I64 | ValType::F32 | ValType::F64 | ValType::Externref => {
+                let f = self.module.core_constant(ty);
+                self.instrs.push(Instruction::CallCore(f));
+            }
+            ValType::String => {
+                self.terminal(u, ValType::I32)?;
+                self.terminal(u, ValType::I32)?;
+                self.instrs.push(Instruction::MemoryToString(0));
+            }
+            _ => {
+                let (instr, input, _) = CONVERSIONS
+                    .iter()
+                    .find(|(_, input, output)| *input == ValType::I32 && *output == ty)
+                    .unwrap();
+                self.terminal(u, *input)?;
+                self.instrs.push(instr.clone());
+            }
+        }
+        Ok(())
+    }
+
+    /// Calls a core or adapter function which doesn't return anything, which
+    /// leaves the stack as it was.
+    fn call_without_results(&mut self, u: &mut Unstructured<'_>, depth: u32) -> Result<()> {
+        let mut choices = Vec::new();
+        for f in 0..self.module.core_func_count() {
+            if self.module.core_func(f).results.is_empty() {
+                choices.push(Choice::CallCore(f));
+            }
+        }
+        for f in 0..self.callable as u32 {
+            if self.module.adapter_type(f).results.is_empty() {
+                choices.push(Choice::CallAdapter(f));
+            }
+        }
+        match u.choose(&choices)? {
+            Choice::CallCore(f) => {
+                let sig = self.module.core_func(*f);
+                self.produce_all(u, &sig.params, depth)?;
+                self.instrs.push(Instruction::CallCore(*f));
+            }
+            Choice::CallAdapter(f) => {
+                let ty = self.module.adapter_type(*f);
+                self.produce_all(u, &ty.params, depth)?;
+                self.instrs.push(Instruction::CallAdapter(*f));
+            }
+            _ => unreachable!(),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,70 @@
+//! Differential tests over randomly generated modules.
+//!
+//! Each module is checked to be valid, to be unchanged by printing it and
+//! parsing the text back, and to have the same interface types section after
+//! being loaded into and emitted from `walrus`.
+
+use arbitrary::{Arbitrary, Unstructured};
+use std::collections::HashSet;
+use wasmparser::Payload;
+
+#[test]
+fn round_trip() {
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    let mut data = Vec::new();
+    let mut opcodes = HashSet::new();
+    for seed in 0..500 {
+        data.clear();
+        for _ in 0..(seed % 64) * 64 {
+            // xorshift64
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            data.push(state as u8);
+        }
+        let module = wit_smith::Module::arbitrary(&mut Unstructured::new(&data)).unwrap();
+        check(&module.to_bytes()).unwrap_or_else(|e| panic!("seed {}: {:?}", seed, e));
+        for func in module.wit().funcs.iter() {
+            opcodes.extend(func.instrs.iter().map(|i| i.opcode()));
+        }
+    }
+
+    // Every instruction other than `end` and extension instructions should
+    // have been generated at least once.
+    assert_eq!(opcodes.len(), 45, "{:?}", opcodes);
+}
+
+fn check(wasm: &[u8]) -> anyhow::Result<()> {
+    let text = wit_printer::print_bytes(wasm)?;
+    if let Err(e) = wit_validator::validate(wasm) {
+        anyhow::bail!("{:?}\n{}", e, text);
+    }
+
+    let wasm2 = wit_text::parse_str(&text)?;
+    if wasm != &wasm2[..] {
+        anyhow::bail!("printing and parsing changed the module:\n{}", text);
+    }
+
+    let mut module = walrus::ModuleConfig::new()
+        .on_parse(wit_walrus::on_parse)
+        .parse(wasm)?;
+    let emitted = module.emit_wasm();
+    if section(wasm) != section(&emitted) {
+        anyhow::bail!("walrus changed the section of:\n{}", text);
+    }
+    Ok(())
+}
+
+fn section(wasm: &[u8]) -> Option<&[u8]> {
+    for payload in wasmparser::Parser::new(0).parse_all(wasm) {
+        if let Payload::CustomSection {
+            name: wit_schema_version::SECTION_NAME,
+            data,
+            ..
+        } = payload.unwrap()
+        {
+            return Some(data);
+        }
+    }
+    None
+}
@@ -5,6 +5,9 @@
 //! expected output of the test. That can be automatically updated with
 //! `BLESS=1` in the environment. Otherwise the test are checked against the
 //! listed expectation.
+//!
+//! `*.wast` scripts are also run, but they contain their own assertions so
+//! they have no `CHECK-ALL` annotation and the test's output is ignored.
 
 use anyhow::{bail, Context, Result};
 use rayon::prelude::*;
@@ -52,6 +55,10 @@ pub fn run(dir: &Path, run: fn(&Path) -> Result<String>) {
 
 fn run_test(test: &Path, bless: bool, run: fn(&Path) -> anyhow::Result<String>) -> Result<()> {
     (|| -> Result<_> {
+        if test.extension().and_then(|s| s.to_str()) == Some("wast") {
+            run(test)?;
+            return Ok(());
+        }
         let expected = FileCheck::from_file(test)?;
         let actual = run(test)?;
         expected.check(&actual, bless)?;
@@ -71,6 +78,7 @@ fn find_tests(path: &Path, tests: &mut Vec<PathBuf>) {
         match f.path().extension().and_then(|s| s.to_str()) {
             Some("wit") => {}
             Some("wat") => {}
+            Some("wast") => {}
             _ => continue,
         }
         tests.push(f.path());
@@ -89,12 +97,12 @@ impl FileCheck {
         while let Some(line) = iter.next() {
             if line.starts_with("(; CHECK-ALL:") {
                 let mut pattern = String::new();
-                while let Some(line) = iter.next() {
+                for line in iter.by_ref() {
                     if line == ";)" {
                         break;
                     }
                     pattern.push_str(line);
-                    pattern.push_str("\n");
+                    pattern.push('\n');
                 }
                 while pattern.ends_with("\n") {
                     pattern.pop();
@@ -142,7 +150,7 @@ fn update_output(path: &Path, output: &str) -> Result<()> {
     let mut new_output = String::new();
     for line in output.lines() {
         new_output.push_str(line);
-        new_output.push_str("\n");
+        new_output.push('\n');
     }
     let new = format!(
         "{}\n\n(; CHECK-ALL:\n{}\n;)\n",
@@ -162,10 +170,10 @@ fn normalize(output: &str) -> String {
             continue;
         }
         ret.push_str(line);
-        ret.push_str("\n");
+        ret.push('\n');
     }
     while ret.ends_with("\n") {
         ret.pop();
     }
-    return ret;
+    ret
 }
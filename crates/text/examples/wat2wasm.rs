@@ -7,6 +7,12 @@ fn main() -> anyhow::Result<()> {
     opts.optopt("o", "", "set output file name", "NAME");
     opts.optflag("h", "help", "print this help menu");
     opts.optflag("", "no-validate", "don't validate the module");
+    opts.optopt(
+        "",
+        "core",
+        "treat FILE as adapters only, combined with this core module",
+        "FILE",
+    );
     let matches = opts.parse(env::args_os().skip(1))?;
     if matches.opt_present("h") {
         print_usage(opts);
@@ -19,10 +25,20 @@ fn main() -> anyhow::Result<()> {
         std::process::exit(1);
     };
 
-    let wasm = wit_text::parse_file(&input)?;
-    if !matches.opt_present("no-validate") {
-        wit_validator::validate(&wasm)?;
-    }
+    let validate = !matches.opt_present("no-validate");
+    let wasm = match matches.opt_str("core") {
+        Some(core) => {
+            let wasm = wit_text::parse_file_with_core(&input, &core)?;
+            if validate {
+                wit_validator::validate(&wasm)?;
+            }
+            wasm
+        }
+        // Validating the text directly reports errors at their source
+        // location rather than at an offset in the binary.
+        None if validate => wit_text::validate_file(&input)?,
+        None => wit_text::parse_file(&input)?,
+    };
     if let Some(output) = matches.opt_str("o") {
         std::fs::write(&output, wasm).context(format!("failed to write `{}`", output))?;
     }
@@ -66,17 +66,46 @@ impl<'a> Parse<'a> for Func<'a> {
 }
 
 /// A list of instructions, possibly in s-expression form
-#[allow(missing_docs)]
 pub struct Instructions<'a> {
+    /// The instructions, in the order that they're executed.
     pub instrs: Vec<ast::Instruction<'a>>,
+    /// Where each instruction in `instrs` was defined.
+    ///
+    /// This is only used to report errors and may be empty, for example if
+    /// the instructions weren't parsed from text.
+    pub spans: Vec<wast::Span>,
 }
 
 impl<'a> Parse<'a> for Instructions<'a> {
     fn parse(parser: Parser<'a>) -> Result<Instructions<'a>> {
-        let mut instrs = Vec::new();
+        let mut ret = Instructions {
+            instrs: Vec::new(),
+            spans: Vec::new(),
+        };
         while !parser.is_empty() {
-            instrs.push(parser.parse()?);
+            if parser.peek::<wast::LParen>() {
+                parse_folded(parser, &mut ret)?;
+            } else {
+                ret.spans.push(parser.cur_span());
+                ret.instrs.push(parser.parse()?);
+            }
         }
-        Ok(Instructions { instrs })
+        Ok(ret)
     }
 }
+
+/// Parses a folded instruction like `(call-core $f (arg.get 0))`, where all
+/// operands are themselves folded instructions which are evaluated before the
+/// instruction itself.
+fn parse_folded<'a>(parser: Parser<'a>, instrs: &mut Instructions<'a>) -> Result<()> {
+    parser.parens(|parser| {
+        let span = parser.cur_span();
+        let instr = parser.parse()?;
+        while !parser.is_empty() {
+            parse_folded(parser, instrs)?;
+        }
+        instrs.spans.push(span);
+        instrs.instrs.push(instr);
+        Ok(())
+    })
+}
@@ -95,6 +95,8 @@ instructions! {
         U32ToI64 : "u32-to-i64",
         S64ToI64 : "s64-to-i64",
         U64ToI64 : "u64-to-i64",
+
+        Extension(Extension<'a>) : "extension",
     }
 }
 
@@ -133,3 +135,21 @@ impl<'a> Parse<'a> for StringToMemory<'a> {
         })
     }
 }
+
+/// Payload of an `extension` instruction, an instruction that isn't otherwise
+/// known which is encoded with its opcode and raw immediates.
+pub struct Extension<'a> {
+    /// The opcode of the extension instruction.
+    pub opcode: u32,
+    /// The encoded immediates of the instruction.
+    pub immediates: &'a [u8],
+}
+
+impl<'a> Parse<'a> for Extension<'a> {
+    fn parse(parser: Parser<'a>) -> Result<Self> {
+        Ok(Extension {
+            opcode: parser.parse()?,
+            immediates: parser.parse()?,
+        })
+    }
+}
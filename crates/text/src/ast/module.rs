@@ -21,6 +21,11 @@ impl<'a> Parse<'a> for Wat<'a> {
 }
 
 /// A WebAssembly interface types-enhanced module.
+///
+/// This is typically parsed from text, but can also be built in code. Inline
+/// exports, imports and type uses are expanded by [`Module::encode`] just as
+/// they are for parsed modules, and references to names that don't exist are
+/// reported as errors.
 pub struct Module<'a> {
     /// The core WebAssembly module which doesn't use interface types.
     pub core: wast::Module<'a>,
@@ -32,14 +37,61 @@ pub struct Module<'a> {
 impl Module<'_> {
     /// Encodes this `Module` into its binary form.
     pub fn encode(&mut self) -> std::result::Result<Vec<u8>, wast::Error> {
+        self.encode_module(None, false)
+    }
+
+    /// Encodes this `Module` into its binary form and validates the result.
+    ///
+    /// This runs `wit_validator::validate` over the encoded module, and any
+    /// error is reported at the adapter or instruction that failed to
+    /// validate. Errors which can't be traced back to an adapter, such as
+    /// those in the core module, are reported at the start of the module.
+    pub fn encode_checked(&mut self) -> std::result::Result<Vec<u8>, wast::Error> {
+        self.encode_module(None, true)
+    }
+
+    /// Encodes this `Module` into its binary form, also returning a map from
+    /// offsets of adapters in the binary back to where they were defined.
+    ///
+    /// This is useful for reporting errors found in the binary, such as by
+    /// `wit-validator`, against the original text.
+    pub fn encode_with_spans(
+        &mut self,
+    ) -> std::result::Result<(Vec<u8>, crate::SpanMap), wast::Error> {
+        let mut spans = crate::SpanMap::default();
+        let wasm = self.encode_module(Some(&mut spans), false)?;
+        Ok((wasm, spans))
+    }
+
+    fn encode_module(
+        &mut self,
+        spans: Option<&mut crate::SpanMap>,
+        check: bool,
+    ) -> std::result::Result<Vec<u8>, wast::Error> {
         let names = self.core.resolve()?;
-        let core = match &self.core.kind {
+        let fields = match &self.core.kind {
             wast::ModuleKind::Text(list) => &list[..],
             wast::ModuleKind::Binary(_) => &[],
         };
-        crate::resolve::resolve(core, &mut self.adapters, &names)?;
+        let core = crate::resolve::TextCore {
+            fields,
+            names: &names,
+        };
+        crate::resolve::resolve(&core, &mut self.adapters)?;
         let mut core = self.core.encode()?;
-        crate::binary::append(&self.adapters, &mut core);
+        let mut map = crate::SpanMap::default();
+        let map_spans = spans.is_some() || check;
+        crate::binary::append(
+            &self.adapters,
+            &mut core,
+            if map_spans { Some(&mut map) } else { None },
+        );
+        if check {
+            crate::typecheck::typecheck(&core, &map, self.core.span)?;
+        }
+        if let Some(spans) = spans {
+            *spans = map;
+        }
         Ok(core)
     }
 }
@@ -94,6 +146,77 @@ fn parse_text_module<'a>(
     })
 }
 
+/// A standalone `*.wit` file containing only `@interface` adapters.
+///
+/// Unlike [`Module`] there's no core module here; instead adapters refer to
+/// core functions by their `name` section names and are combined with a
+/// separately compiled core module in [`Wit::encode`].
+pub struct Wit<'a> {
+    /// The `@interface` adapter directives listed in this file.
+    pub adapters: Vec<Adapter<'a>>,
+}
+
+impl Wit<'_> {
+    /// Encodes these adapters alongside the binary `core` module, returning
+    /// the full binary of the combined module.
+    ///
+    /// The returned module is `core` with a wasm interface types custom
+    /// section appended to it.
+    pub fn encode(&mut self, core: &[u8]) -> std::result::Result<Vec<u8>, wast::Error> {
+        self.encode_wit(core, false)
+    }
+
+    /// Encodes these adapters alongside `core`, like [`Wit::encode`], and
+    /// validates the combined module.
+    ///
+    /// This runs `wit_validator::validate` over the encoded module, and any
+    /// error is reported at the adapter or instruction that failed to
+    /// validate. Errors in the core module are reported at the start of the
+    /// file.
+    pub fn encode_checked(&mut self, core: &[u8]) -> std::result::Result<Vec<u8>, wast::Error> {
+        self.encode_wit(core, true)
+    }
+
+    fn encode_wit(
+        &mut self,
+        core: &[u8],
+        check: bool,
+    ) -> std::result::Result<Vec<u8>, wast::Error> {
+        if !core.starts_with(b"\0asm") {
+            return Err(wast::Error::new(
+                wast::Span::from_offset(0),
+                "core module isn't a binary wasm module".to_string(),
+            ));
+        }
+        let resolve_core = crate::resolve::BinaryCore::new(core)?;
+        crate::resolve::resolve(&resolve_core, &mut self.adapters)?;
+        let mut ret = core.to_vec();
+        let mut map = crate::SpanMap::default();
+        crate::binary::append(
+            &self.adapters,
+            &mut ret,
+            if check { Some(&mut map) } else { None },
+        );
+        if check {
+            // Errors in the core module have nowhere better to point to.
+            crate::typecheck::typecheck(&ret, &map, wast::Span::from_offset(0))?;
+        }
+        Ok(ret)
+    }
+}
+
+impl<'a> Parse<'a> for Wit<'a> {
+    fn parse(parser: Parser<'a>) -> Result<Wit<'a>> {
+        let _r = parser.register_annotation("interface");
+
+        let mut adapters = Vec::new();
+        while !parser.is_empty() {
+            adapters.push(parser.parens(|parser| parser.parse())?);
+        }
+        Ok(Wit { adapters })
+    }
+}
+
 /// List of possible `@interface` adapters that can be listed in a module.
 pub enum Adapter<'a> {
     /// An interface type definition (function signature).
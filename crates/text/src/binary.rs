@@ -1,9 +1,33 @@
 use crate::ast::*;
+use wast::Span;
 
-pub fn append(adapters: &[Adapter<'_>], wasm: &mut Vec<u8>) {
-    if adapters.len() == 0 {
+/// A map from byte offsets in an encoded module back to where the adapters at
+/// those offsets were defined in the text format.
+///
+/// This is produced by [`Module::encode_with_spans`] and is used to translate
+/// the offsets reported by `wit-validator` into source locations.
+#[derive(Debug, Default, Clone)]
+pub struct SpanMap {
+    offsets: Vec<(usize, Span)>,
+}
+
+impl SpanMap {
+    /// Returns the span of the adapter item or instruction which was encoded
+    /// starting at `offset`, if any.
+    pub fn lookup(&self, offset: usize) -> Option<Span> {
+        let i = self
+            .offsets
+            .binary_search_by_key(&offset, |(offset, _)| *offset)
+            .ok()?;
+        Some(self.offsets[i].1)
+    }
+}
+
+pub fn append(adapters: &[Adapter<'_>], wasm: &mut Vec<u8>, spans: Option<&mut SpanMap>) {
+    if adapters.is_empty() {
         return;
     }
+    let start = wasm.len();
     let mut types = Vec::new();
     let mut imports = Vec::new();
     let mut funcs = Vec::new();
@@ -19,6 +43,35 @@ pub fn append(adapters: &[Adapter<'_>], wasm: &mut Vec<u8>) {
         }
     }
 
+    // Imports come first in the function index space, so parameter names are
+    // collected from imports and then local functions.
+    let param_names = imports
+        .iter()
+        .map(|i| &i.ty)
+        .chain(funcs.iter().map(|f| &f.ty))
+        .enumerate()
+        .flat_map(|(func, ty)| {
+            ty.ty
+                .params
+                .iter()
+                .enumerate()
+                .filter_map(move |(param, (name, _))| Some((func, param, (*name)?)))
+        })
+        .collect::<Vec<_>>();
+
+    let item_spans = spans.as_ref().map(|_| ItemSpans {
+        imports: imports.iter().map(|i| i.span).collect(),
+        funcs: funcs
+            .iter()
+            .map(|f| match &f.kind {
+                FuncKind::Inline { instrs } => (f.span, instrs.spans.clone()),
+                FuncKind::Import { .. } => (f.span, Vec::new()),
+            })
+            .collect(),
+        exports: exports.iter().map(|e| e.func.span()).collect(),
+        implements: implements.iter().map(|i| i.span).collect(),
+    });
+
     let mut writer = wit_writer::Writer::new();
 
     // First up is the type section ...
@@ -57,7 +110,7 @@ pub fn append(adapters: &[Adapter<'_>], wasm: &mut Vec<u8>) {
     let mut w = writer.funcs(funcs.len() as u32);
     for func in funcs {
         let mut w = w.add(get_num(
-            &func.ty.index.as_ref().expect("unresolved type use"),
+            func.ty.index.as_ref().expect("unresolved type use"),
         ));
         assert!(func.export.is_none());
         let instrs = match &func.kind {
@@ -65,59 +118,68 @@ pub fn append(adapters: &[Adapter<'_>], wasm: &mut Vec<u8>) {
             FuncKind::Import { .. } => panic!("imports should be de-inlined"),
         };
         for instr in instrs.instrs.iter() {
+            use wit_parser::Instruction as R; // "raw"
             use Instruction::*;
 
-            match instr {
-                ArgGet(a) => w.arg_get(get_num(a)),
-                CallCore(a) => w.call_core(get_num(a)),
-                DeferCallCore(a) => w.defer_call_core(get_num(a)),
-                CallAdapter(a) => w.call_adapter(get_num(a)),
-                MemoryToString(a) => w.memory_to_string(get_num(&a.mem)),
-                StringToMemory(a) => w.string_to_memory(get_num(&a.malloc), get_num(&a.mem)),
-
-                I32ToS8 => w.i32_to_s8(),
-                I32ToS8X => w.i32_to_s8x(),
-                I32ToU8 => w.i32_to_u8(),
-                I32ToS16 => w.i32_to_s16(),
-                I32ToS16X => w.i32_to_s16x(),
-                I32ToU16 => w.i32_to_u16(),
-                I32ToS32 => w.i32_to_s32(),
-                I32ToU32 => w.i32_to_u32(),
-                I32ToS64 => w.i32_to_s64(),
-                I32ToU64 => w.i32_to_u64(),
-
-                I64ToS8 => w.i64_to_s8(),
-                I64ToS8X => w.i64_to_s8x(),
-                I64ToU8 => w.i64_to_u8(),
-                I64ToS16 => w.i64_to_s16(),
-                I64ToS16X => w.i64_to_s16x(),
-                I64ToU16 => w.i64_to_u16(),
-                I64ToS32 => w.i64_to_s32(),
-                I64ToS32X => w.i64_to_s32x(),
-                I64ToU32 => w.i64_to_u32(),
-                I64ToS64 => w.i64_to_s64(),
-                I64ToU64 => w.i64_to_u64(),
-
-                S8ToI32 => w.s8_to_i32(),
-                U8ToI32 => w.u8_to_i32(),
-                S16ToI32 => w.s16_to_i32(),
-                U16ToI32 => w.u16_to_i32(),
-                S32ToI32 => w.s32_to_i32(),
-                U32ToI32 => w.u32_to_i32(),
-                S64ToI32 => w.s64_to_i32(),
-                S64ToI32X => w.s64_to_i32x(),
-                U64ToI32 => w.u64_to_i32(),
-                U64ToI32X => w.u64_to_i32x(),
-
-                S8ToI64 => w.s8_to_i64(),
-                U8ToI64 => w.u8_to_i64(),
-                S16ToI64 => w.s16_to_i64(),
-                U16ToI64 => w.u16_to_i64(),
-                S32ToI64 => w.s32_to_i64(),
-                U32ToI64 => w.u32_to_i64(),
-                S64ToI64 => w.s64_to_i64(),
-                U64ToI64 => w.u64_to_i64(),
-            }
+            w.instr(&match instr {
+                ArgGet(a) => R::ArgGet(get_num(a)),
+                CallCore(a) => R::CallCore(get_num(a)),
+                DeferCallCore(a) => R::DeferCallCore(get_num(a)),
+                CallAdapter(a) => R::CallAdapter(get_num(a)),
+                MemoryToString(a) => R::MemoryToString(get_num(&a.mem)),
+                StringToMemory(a) => R::StringToMemory(wit_parser::StringToMemory {
+                    malloc: get_num(&a.malloc),
+                    mem: get_num(&a.mem),
+                }),
+
+                I32ToS8 => R::I32ToS8,
+                I32ToS8X => R::I32ToS8X,
+                I32ToU8 => R::I32ToU8,
+                I32ToS16 => R::I32ToS16,
+                I32ToS16X => R::I32ToS16X,
+                I32ToU16 => R::I32ToU16,
+                I32ToS32 => R::I32ToS32,
+                I32ToU32 => R::I32ToU32,
+                I32ToS64 => R::I32ToS64,
+                I32ToU64 => R::I32ToU64,
+
+                I64ToS8 => R::I64ToS8,
+                I64ToS8X => R::I64ToS8X,
+                I64ToU8 => R::I64ToU8,
+                I64ToS16 => R::I64ToS16,
+                I64ToS16X => R::I64ToS16X,
+                I64ToU16 => R::I64ToU16,
+                I64ToS32 => R::I64ToS32,
+                I64ToS32X => R::I64ToS32X,
+                I64ToU32 => R::I64ToU32,
+                I64ToS64 => R::I64ToS64,
+                I64ToU64 => R::I64ToU64,
+
+                S8ToI32 => R::S8ToI32,
+                U8ToI32 => R::U8ToI32,
+                S16ToI32 => R::S16ToI32,
+                U16ToI32 => R::U16ToI32,
+                S32ToI32 => R::S32ToI32,
+                U32ToI32 => R::U32ToI32,
+                S64ToI32 => R::S64ToI32,
+                S64ToI32X => R::S64ToI32X,
+                U64ToI32 => R::U64ToI32,
+                U64ToI32X => R::U64ToI32X,
+
+                S8ToI64 => R::S8ToI64,
+                U8ToI64 => R::U8ToI64,
+                S16ToI64 => R::S16ToI64,
+                U16ToI64 => R::U16ToI64,
+                S32ToI64 => R::S32ToI64,
+                U32ToI64 => R::U32ToI64,
+                S64ToI64 => R::S64ToI64,
+                U64ToI64 => R::U64ToI64,
+
+                Extension(e) => R::Extension(wit_parser::Extension {
+                    opcode: e.opcode,
+                    immediates: e.immediates.to_vec(),
+                }),
+            });
         }
     }
     drop(w);
@@ -129,7 +191,7 @@ pub fn append(adapters: &[Adapter<'_>], wasm: &mut Vec<u8>) {
     }
     drop(w);
 
-    // ... and finally the implements section
+    // ... then the implements section ...
     let mut w = writer.implements(implements.len() as u32);
     for implement in implements {
         let implemented = match &implement.implemented {
@@ -144,7 +206,96 @@ pub fn append(adapters: &[Adapter<'_>], wasm: &mut Vec<u8>) {
     }
     drop(w);
 
+    // ... and finally the names of parameters
+    let mut w = writer.param_names(param_names.len() as u32);
+    for (func, param, name) in param_names {
+        w.add(func as u32, param as u32, name.name());
+    }
+    drop(w);
+
     wasm.extend_from_slice(&writer.into_custom_section());
+
+    if let (Some(map), Some(items)) = (spans, item_spans) {
+        record_spans(map, &items, wasm, start);
+    }
+}
+
+/// Spans of each adapter item, in the order they're encoded in.
+struct ItemSpans {
+    imports: Vec<Span>,
+    funcs: Vec<(Span, Vec<Span>)>,
+    exports: Vec<Span>,
+    implements: Vec<Span>,
+}
+
+/// Re-parses the custom section encoded at `start` in `wasm`, recording the
+/// offset of each item alongside the span it came from.
+fn record_spans(map: &mut SpanMap, items: &ItemSpans, wasm: &[u8], start: usize) {
+    const BUG: &str = "failed to re-parse encoded adapters";
+    let mut reader = wasmparser::BinaryReader::new_with_offset(&wasm[start..], start);
+    reader.read_u8().expect(BUG);
+    reader.read_var_u32().expect(BUG);
+    reader.read_string().expect(BUG);
+    let data_offset = reader.original_position();
+    let mut parser = wit_parser::Parser::new(data_offset, &wasm[data_offset..]).expect(BUG);
+
+    // Parameter names are attributed to the function they name, and imports
+    // come first in the function index space.
+    let func_spans = items
+        .imports
+        .iter()
+        .copied()
+        .chain(items.funcs.iter().map(|(span, _)| *span))
+        .collect::<Vec<_>>();
+
+    let offsets = &mut map.offsets;
+    while !parser.is_empty() {
+        match parser.section().expect(BUG) {
+            // Types don't have a span of their own to point at.
+            wit_parser::Section::Type(_) => {}
+            wit_parser::Section::Import(mut s) => {
+                for span in items.imports.iter() {
+                    offsets.push((s.original_position(), *span));
+                    s.next();
+                }
+            }
+            wit_parser::Section::Func(mut s) => {
+                for (span, instr_spans) in items.funcs.iter() {
+                    offsets.push((s.original_position(), *span));
+                    let func = s.next().expect(BUG).expect(BUG);
+                    let mut instrs = func.instrs();
+                    for instr_span in instr_spans {
+                        offsets.push((instrs.original_position(), *instr_span));
+                        instrs.next();
+                    }
+                }
+            }
+            wit_parser::Section::Export(mut s) => {
+                for span in items.exports.iter() {
+                    offsets.push((s.original_position(), *span));
+                    s.next();
+                }
+            }
+            wit_parser::Section::Implement(mut s) => {
+                for span in items.implements.iter() {
+                    offsets.push((s.original_position(), *span));
+                    s.next();
+                }
+            }
+            wit_parser::Section::Unknown { .. } => {}
+            wit_parser::Section::ParamNames(mut s) => loop {
+                let pos = s.original_position();
+                let name = match s.next() {
+                    Some(name) => name.expect(BUG),
+                    None => break,
+                };
+                if let Some(span) = func_spans.get(name.func as usize) {
+                    offsets.push((pos, *span));
+                }
+            },
+        }
+    }
+    offsets.sort_by_key(|(offset, _)| *offset);
 }
 
 fn get_num(idx: &wast::Index<'_>) -> u32 {
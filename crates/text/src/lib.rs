@@ -16,7 +16,9 @@ use wast::parser::ParseBuffer;
 mod ast;
 mod binary;
 mod resolve;
+mod typecheck;
 pub use ast::*;
+pub use binary::SpanMap;
 
 /// Parses a `file` on the filesystem as a textual representation of WebAssembly
 /// Interface Types, returning the binary representation of the module.
@@ -102,7 +104,109 @@ fn _parse_str(wat: &str) -> Result<Vec<u8>, wast::Error> {
         err.set_text(wat);
         err
     };
-    let buf = ParseBuffer::new(&wat).map_err(adjust)?;
+    let buf = ParseBuffer::new(wat).map_err(adjust)?;
     let mut ast = wast::parser::parse::<Wat>(&buf).map_err(adjust)?;
     ast.module.encode().map_err(adjust)
 }
+
+/// Parses a `file` on the filesystem as the text format and validates the
+/// resulting binary with `wit-validator`, returning the binary representation
+/// of the module.
+///
+/// # Errors
+///
+/// For information about errors, see the [`validate_text`] documentation.
+pub fn validate_file(file: impl AsRef<Path>) -> anyhow::Result<Vec<u8>> {
+    _validate_file(file.as_ref())
+}
+
+fn _validate_file(file: &Path) -> anyhow::Result<Vec<u8>> {
+    let contents =
+        std::fs::read_to_string(file).context(format!("failed to read `{}`", file.display()))?;
+    validate_text(&contents).map_err(|mut e| {
+        e.set_path(file);
+        e.into()
+    })
+}
+
+/// Parses an in-memory string as the text format, like [`parse_str`], and
+/// then validates the resulting binary with `wit-validator`.
+///
+/// Validation errors in the adapters are reported at the location in `wat`
+/// of the adapter, or instruction, which failed to validate, rather than at
+/// an offset in the binary.
+///
+/// # Errors
+///
+/// In addition to the errors that [`parse_str`] can return, this function
+/// will fail if the module doesn't validate. Errors which can't be traced
+/// back to an adapter, such as those in the core module, are reported at the
+/// start of the module.
+pub fn validate_text(wat: impl AsRef<str>) -> Result<Vec<u8>, wast::Error> {
+    _validate_text(wat.as_ref())
+}
+
+fn _validate_text(wat: &str) -> Result<Vec<u8>, wast::Error> {
+    let adjust = |mut err: wast::Error| {
+        err.set_text(wat);
+        err
+    };
+    let buf = ParseBuffer::new(wat).map_err(adjust)?;
+    let mut ast = wast::parser::parse::<Wat>(&buf).map_err(adjust)?;
+    ast.module.encode_checked().map_err(adjust)
+}
+
+/// Parses a standalone `*.wit` file of adapters and combines it with the core
+/// module in `core`, returning the binary representation of the combined
+/// module.
+///
+/// The `core` file can either be a `*.wat` or `*.wasm` file, and core functions
+/// are referenced from `wit` by the names in the core module's `name` section.
+///
+/// # Errors
+///
+/// For information about errors, see the [`parse_str_with_core`]
+/// documentation.
+pub fn parse_file_with_core(
+    wit: impl AsRef<Path>,
+    core: impl AsRef<Path>,
+) -> anyhow::Result<Vec<u8>> {
+    _parse_file_with_core(wit.as_ref(), core.as_ref())
+}
+
+fn _parse_file_with_core(wit: &Path, core: &Path) -> anyhow::Result<Vec<u8>> {
+    let core = parse_file(core)?;
+    let contents =
+        std::fs::read_to_string(wit).context(format!("failed to read `{}`", wit.display()))?;
+    parse_str_with_core(&contents, &core).map_err(|mut e| {
+        e.set_path(wit);
+        e.into()
+    })
+}
+
+/// Parses an in-memory string as a standalone `*.wit` file of adapters,
+/// combining it with the binary `core` module.
+///
+/// The `wit` input may only contain `@interface` declarations. Any references
+/// they make to core functions by name are resolved through the `name` section
+/// of `core`, and the returned module is `core` with the encoded adapters
+/// appended as a custom section.
+///
+/// # Errors
+///
+/// In addition to the errors that [`parse_str`] can return, this function
+/// will fail if `core` isn't a valid binary module, already has an interface
+/// types section, or doesn't define the names referenced by `wit`.
+pub fn parse_str_with_core(wit: impl AsRef<str>, core: &[u8]) -> Result<Vec<u8>, wast::Error> {
+    _parse_str_with_core(wit.as_ref(), core)
+}
+
+fn _parse_str_with_core(wit: &str, core: &[u8]) -> Result<Vec<u8>, wast::Error> {
+    let adjust = |mut err: wast::Error| {
+        err.set_text(wit);
+        err
+    };
+    let buf = ParseBuffer::new(wit).map_err(adjust)?;
+    let mut ast = wast::parser::parse::<Wit>(&buf).map_err(adjust)?;
+    ast.encode(core).map_err(adjust)
+}
@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+use wasmparser::{ImportSectionEntryType, Name, NameSectionReader, Payload};
+use wast::{Error, Index, Span};
+
+/// The core wasm module that adapters refer to.
+///
+/// Adapters can either be defined inline in a text module, in which case core
+/// items are resolved through the text module's names, or in a standalone
+/// `*.wit` file, in which case they're resolved against a binary module.
+pub trait Core<'a> {
+    /// Resolves `idx` within the core function namespace.
+    fn resolve_func(&self, idx: &mut Index<'a>) -> Result<(), Error>;
+
+    /// Resolves `idx` within the core memory namespace.
+    fn resolve_memory(&self, idx: &mut Index<'a>) -> Result<(), Error>;
+
+    /// Returns the module/field pair of each imported core function, in
+    /// order.
+    fn func_imports(&self) -> Vec<(&str, Option<&str>)>;
+}
+
+/// A core module defined in the text format alongside its adapters.
+pub struct TextCore<'a, 'b> {
+    pub fields: &'b [wast::ModuleField<'a>],
+    pub names: &'b wast::Names<'a>,
+}
+
+impl<'a> Core<'a> for TextCore<'a, '_> {
+    fn resolve_func(&self, idx: &mut Index<'a>) -> Result<(), Error> {
+        self.names.resolve_func(idx)
+    }
+
+    fn resolve_memory(&self, idx: &mut Index<'a>) -> Result<(), Error> {
+        self.names.resolve_memory(idx)
+    }
+
+    fn func_imports(&self) -> Vec<(&str, Option<&str>)> {
+        self.fields
+            .iter()
+            .filter_map(|field| match field {
+                wast::ModuleField::Import(i) => match i.item.kind {
+                    wast::ItemKind::Func(_) => Some((i.module, i.field)),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// A core module in its binary form, whose functions are named by its `name`
+/// section.
+pub struct BinaryCore<'b> {
+    func_names: HashMap<&'b str, u32>,
+    func_imports: Vec<(&'b str, Option<&'b str>)>,
+}
+
+impl<'b> BinaryCore<'b> {
+    /// Parses the parts of `wasm` needed to resolve adapters against it.
+    ///
+    /// Errors are reported at the start of the adapter file since they don't
+    /// correspond to any location within it.
+    pub fn new(wasm: &'b [u8]) -> Result<BinaryCore<'b>, Error> {
+        let err = |msg: String| Error::new(Span::from_offset(0), msg);
+        let mut ret = BinaryCore {
+            func_names: HashMap::new(),
+            func_imports: Vec::new(),
+        };
+        for payload in wasmparser::Parser::new(0).parse_all(wasm) {
+            let payload = payload.map_err(|e| err(format!("invalid core module: {}", e)))?;
+            match payload {
+                Payload::ImportSection(s) => {
+                    for import in s {
+                        let import =
+                            import.map_err(|e| err(format!("invalid core module: {}", e)))?;
+                        if let ImportSectionEntryType::Function(_) = import.ty {
+                            ret.func_imports.push((import.module, import.field));
+                        }
+                    }
+                }
+                Payload::CustomSection {
+                    name: wit_schema_version::SECTION_NAME,
+                    ..
+                } => {
+                    return Err(err(
+                        "core module already has a wasm interface types section".to_string(),
+                    ));
+                }
+                Payload::CustomSection {
+                    name: "name",
+                    data,
+                    data_offset,
+                } => {
+                    // Names are only a convenience for resolution, so a
+                    // malformed name section is ignored like engines do.
+                    let _ = ret.parse_names(data, data_offset);
+                }
+                _ => {}
+            }
+        }
+        Ok(ret)
+    }
+
+    fn parse_names(&mut self, data: &'b [u8], offset: usize) -> wasmparser::Result<()> {
+        let mut reader = NameSectionReader::new(data, offset)?;
+        while !reader.eof() {
+            if let Name::Function(names) = reader.read()? {
+                let mut map = names.get_map()?;
+                for _ in 0..map.get_count() {
+                    let naming = map.read()?;
+                    self.func_names.entry(naming.name).or_insert(naming.index);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Core<'a> for BinaryCore<'_> {
+    fn resolve_func(&self, idx: &mut Index<'a>) -> Result<(), Error> {
+        let id = match idx {
+            Index::Num(..) => return Ok(()),
+            Index::Id(id) => *id,
+        };
+        match self.func_names.get(id.name()) {
+            Some(n) => {
+                *idx = Index::Num(*n, id.span());
+                Ok(())
+            }
+            None => Err(Error::new(
+                id.span(),
+                format!("failed to find func named `${}`", id.name()),
+            )),
+        }
+    }
+
+    fn resolve_memory(&self, idx: &mut Index<'a>) -> Result<(), Error> {
+        match idx {
+            Index::Num(..) => Ok(()),
+            Index::Id(id) => Err(Error::new(
+                id.span(),
+                format!(
+                    "failed to find memory named `${}`, memories of a binary \
+                     core module can only be referenced by index",
+                    id.name()
+                ),
+            )),
+        }
+    }
+
+    fn func_imports(&self) -> Vec<(&str, Option<&str>)> {
+        self.func_imports.clone()
+    }
+}
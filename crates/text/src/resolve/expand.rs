@@ -1,4 +1,5 @@
 use crate::ast::*;
+use crate::resolve::Core;
 use std::mem;
 use wast::{Error, Index};
 
@@ -59,7 +60,7 @@ impl<'a> Expander<'a> {
     pub fn deinline_non_import(
         &mut self,
         item: &mut Adapter<'a>,
-        core: &[wast::ModuleField<'a>],
+        core: &dyn Core<'a>,
     ) -> Result<(), Error> {
         match item {
             Adapter::Func(f) => {
@@ -111,23 +112,15 @@ impl<'a> Expander<'a> {
     fn find_func_index(
         &self,
         span: wast::Span,
-        core: &[wast::ModuleField<'_>],
+        core: &dyn Core<'_>,
         module: &str,
         field: &str,
     ) -> Result<u32, Error> {
         let mut idx = 0;
         let mut ret = None;
-        for entry in core {
-            let i = match entry {
-                wast::ModuleField::Import(i) => i,
-                _ => continue,
-            };
-            match i.item.kind {
-                wast::ItemKind::Func(_) => {}
-                _ => continue,
-            }
+        for (m, f) in core.func_imports() {
             idx += 1;
-            if i.module != module || i.field != Some(field) {
+            if m != module || f != Some(field) {
                 continue;
             }
             if ret.is_some() {
@@ -1,15 +1,13 @@
 use crate::ast::*;
 use wast::Error;
 
+mod core;
 mod expand;
 mod names;
 mod tyexpand;
+pub use self::core::{BinaryCore, Core, TextCore};
 
-pub fn resolve<'a>(
-    core: &[wast::ModuleField<'a>],
-    adapters: &mut Vec<Adapter<'a>>,
-    names: &wast::Names<'a>,
-) -> Result<(), Error> {
+pub fn resolve<'a>(core: &dyn Core<'a>, adapters: &mut Vec<Adapter<'a>>) -> Result<(), Error> {
     // Expanding inline annotations
     let mut expander = expand::Expander::default();
     expander.process(adapters, expand::Expander::deinline_import)?;
@@ -30,7 +28,7 @@ pub fn resolve<'a>(
 
     // Name resolution of adapters
     move_imports_first(adapters);
-    let mut resolver = names::Resolver::new(names);
+    let mut resolver = names::Resolver::new(core);
     for adapter in adapters.iter_mut() {
         resolver.register(adapter);
     }
@@ -41,15 +39,9 @@ pub fn resolve<'a>(
 }
 
 fn move_imports_first(adapters: &mut [Adapter<'_>]) {
-    adapters.sort_by_key(|f| match f {
-        Adapter::Import(_) => false,
-        _ => true,
-    });
+    adapters.sort_by_key(|f| !matches!(f, Adapter::Import(_)));
 }
 
 fn move_types_first(adapters: &mut [Adapter<'_>]) {
-    adapters.sort_by_key(|f| match f {
-        Adapter::Type(_) => false,
-        _ => true,
-    });
+    adapters.sort_by_key(|f| !matches!(f, Adapter::Type(_)));
 }
@@ -1,5 +1,6 @@
 use crate::ast::*;
-use std::collections::HashMap;
+use crate::resolve::Core;
+use std::collections::{HashMap, HashSet};
 use wast::{Error, Id, Index, Span};
 
 #[derive(Copy, Clone)]
@@ -20,7 +21,7 @@ impl Ns {
 pub struct Resolver<'a, 'b> {
     ns: [Namespace<'a>; 2],
     tys: Vec<Type<'a>>,
-    names: &'b wast::Names<'a>,
+    core: &'b dyn Core<'a>,
 }
 
 struct Type<'a> {
@@ -35,11 +36,11 @@ struct Namespace<'a> {
 }
 
 impl<'a, 'b> Resolver<'a, 'b> {
-    pub fn new(names: &'b wast::Names<'a>) -> Resolver<'a, 'b> {
+    pub fn new(core: &'b dyn Core<'a>) -> Resolver<'a, 'b> {
         Resolver {
             ns: Default::default(),
             tys: Default::default(),
-            names,
+            core,
         }
     }
 
@@ -72,10 +73,14 @@ impl<'a, 'b> Resolver<'a, 'b> {
 
     pub fn resolve(&self, field: &mut Adapter<'a>) -> Result<(), Error> {
         match field {
-            Adapter::Import(i) => self.resolve_type_use(i.span, &mut i.ty),
+            Adapter::Import(i) => {
+                self.resolve_type_use(i.span, &mut i.ty)?;
+                check_param_names(&i.ty.ty.params)
+            }
 
             Adapter::Func(f) => {
                 self.resolve_type_use(f.span, &mut f.ty)?;
+                check_param_names(&f.ty.ty.params)?;
                 if let FuncKind::Inline { instrs } = &mut f.kind {
                     let mut resolver = ExprResolver::new(self);
 
@@ -96,7 +101,7 @@ impl<'a, 'b> Resolver<'a, 'b> {
 
             Adapter::Implement(i) => {
                 if let Implemented::ByIndex(i) = &mut i.implemented {
-                    self.names.resolve_func(i)?;
+                    self.core.resolve_func(i)?;
                 }
                 if let Implementation::ByIndex(i) = &mut i.implementation {
                     self.resolve_idx(i, Ns::Func)?;
@@ -104,7 +109,7 @@ impl<'a, 'b> Resolver<'a, 'b> {
                 Ok(())
             }
 
-            Adapter::Type(_) => Ok(()),
+            Adapter::Type(t) => check_param_names(&t.params),
         }
     }
 
@@ -121,7 +126,7 @@ impl<'a, 'b> Resolver<'a, 'b> {
             Some(ty) => ty,
             None => return Ok(()),
         };
-        if ty.ty.params.len() > 0 || ty.ty.results.len() > 0 {
+        if !ty.ty.params.is_empty() || !ty.ty.results.is_empty() {
             let params_not_equal = expected.params.iter().map(|t| &t.1).ne(ty
                 .ty
                 .params
@@ -131,7 +136,7 @@ impl<'a, 'b> Resolver<'a, 'b> {
                 let span = ty.index_span.unwrap_or(span);
                 return Err(Error::new(
                     span,
-                    format!("inline function type type doesn't match type reference"),
+                    "inline function type type doesn't match type reference".to_string(),
                 ));
             }
         } else {
@@ -157,6 +162,21 @@ impl<'a, 'b> Resolver<'a, 'b> {
     }
 }
 
+fn check_param_names(params: &[(Option<Id<'_>>, ValType)]) -> Result<(), Error> {
+    let mut names = HashSet::new();
+    for (name, _) in params {
+        if let Some(name) = name {
+            if !names.insert(name) {
+                return Err(Error::new(
+                    name.span(),
+                    format!("duplicate parameter named `${}`", name.name()),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
 impl<'a> Namespace<'a> {
     fn register(&mut self, name: Option<Id<'a>>) {
         if let Some(name) = name {
@@ -200,14 +220,14 @@ impl<'a, 'b, 'c> ExprResolver<'a, 'b, 'c> {
                 .resolve(i)
                 .map(|_| ())
                 .map_err(|id| self.resolver.resolve_error(id, "local")),
-            CallCore(i) => self.resolver.names.resolve_func(i),
-            MemoryToString(m) => self.resolver.names.resolve_memory(&mut m.mem),
+            CallCore(i) => self.resolver.core.resolve_func(i),
+            MemoryToString(m) => self.resolver.core.resolve_memory(&mut m.mem),
             StringToMemory(m) => {
-                self.resolver.names.resolve_func(&mut m.malloc)?;
-                self.resolver.names.resolve_memory(&mut m.mem)
+                self.resolver.core.resolve_func(&mut m.malloc)?;
+                self.resolver.core.resolve_memory(&mut m.mem)
             }
             CallAdapter(f) => self.resolver.resolve_idx(f, Ns::Func),
-            DeferCallCore(f) => self.resolver.names.resolve_func(f),
+            DeferCallCore(f) => self.resolver.core.resolve_func(f),
             _ => Ok(()),
         }
     }
@@ -70,6 +70,6 @@ impl<'a> Expander<'a> {
         }));
         self.types.insert(key, self.ntypes);
         self.ntypes += 1;
-        return self.ntypes - 1;
+        self.ntypes - 1
     }
 }
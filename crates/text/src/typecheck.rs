@@ -0,0 +1,26 @@
+//! Validation of encoded modules, reported against the text they came from.
+//!
+//! There's no separate typechecker here: this re-runs the full
+//! `wit_validator::validate` over the freshly encoded module and uses a
+//! [`SpanMap`] to translate the offset of any error back into a
+//! [`wast::Span`].
+
+use crate::SpanMap;
+use wast::Span;
+
+/// Validates the encoded module `wasm`, returning an error located with
+/// `spans` if it's ill-typed.
+///
+/// Errors which can't be located, such as those in the core module, are
+/// reported at `fallback`.
+pub fn typecheck(wasm: &[u8], spans: &SpanMap, fallback: Span) -> Result<(), wast::Error> {
+    let err = match wit_validator::validate(wasm) {
+        Ok(()) => return Ok(()),
+        Err(e) => e,
+    };
+    let span = err
+        .downcast_ref::<wit_validator::ErrorLocation>()
+        .and_then(|loc| spans.lookup(loc.offset()))
+        .unwrap_or(fallback);
+    Err(wast::Error::new(span, format!("{:#}", err)))
+}
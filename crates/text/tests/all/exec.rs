@@ -0,0 +1,448 @@
+//! A small executor for adapter functions, used to run the `invoke`s of
+//! `*.wast` scripts.
+//!
+//! Core wasm is run with `wasmi` and adapters are interpreted directly from
+//! their `wit-ir` representation, with the same semantics as the JS generated
+//! by `wit-bindgen-js`. Imported adapters, and core imports which aren't
+//! implemented by an adapter, trap when called.
+
+use anyhow::{anyhow, bail, Context, Result};
+use std::convert::TryFrom;
+use std::sync::Arc;
+use wasmi::{AsContext, AsContextMut, ExternRef, ExternType, Val};
+use wasmparser::Payload;
+use wit_ir::{Instruction, ValType};
+
+/// A value passed to or returned from an adapter function.
+///
+/// Floats are stored as their bits so comparisons of NaN values are exact.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    S8(i8),
+    S16(i16),
+    S32(i32),
+    S64(i64),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    F32(u32),
+    F64(u64),
+    String(String),
+    Externref(Option<u32>),
+    I32(i32),
+    I64(i64),
+}
+
+impl Value {
+    fn ty(&self) -> ValType {
+        match self {
+            Value::S8(_) => ValType::S8,
+            Value::S16(_) => ValType::S16,
+            Value::S32(_) => ValType::S32,
+            Value::S64(_) => ValType::S64,
+            Value::U8(_) => ValType::U8,
+            Value::U16(_) => ValType::U16,
+            Value::U32(_) => ValType::U32,
+            Value::U64(_) => ValType::U64,
+            Value::F32(_) => ValType::F32,
+            Value::F64(_) => ValType::F64,
+            Value::String(_) => ValType::String,
+            Value::Externref(_) => ValType::Externref,
+            Value::I32(_) => ValType::I32,
+            Value::I64(_) => ValType::I64,
+        }
+    }
+}
+
+/// An instantiated module whose adapter exports can be invoked.
+pub struct Instance {
+    store: wasmi::Store<State>,
+}
+
+struct State {
+    wit: Arc<wit_ir::Module>,
+    /// Every core function, indexed by its index in the core module.
+    funcs: Vec<wasmi::Func>,
+    /// Every core memory, indexed by its index in the core module.
+    memories: Vec<wasmi::Memory>,
+}
+
+impl Instance {
+    /// Instantiates the binary module `wasm`, which should already be valid.
+    pub fn new(wasm: &[u8]) -> Result<Instance> {
+        let wit = Arc::new(match section(wasm)? {
+            Some(data) => wit_ir::Module::from_bytes(data)?,
+            None => wit_ir::Module::default(),
+        });
+
+        // Adapters can call core functions and use memories which aren't
+        // exported, so export everything under names of our own.
+        let mut core = walrus::ModuleConfig::new()
+            .on_parse(|module, indices| {
+                let funcs = module.funcs.iter().count() as u32;
+                for i in 0..funcs {
+                    let id = indices.get_func(i)?;
+                    module.exports.add(&format!("exec:func{}", i), id);
+                }
+                let memories = module.memories.iter().count() as u32;
+                for i in 0..memories {
+                    let id = indices.get_memory(i)?;
+                    module.exports.add(&format!("exec:memory{}", i), id);
+                }
+                Ok(())
+            })
+            .parse(wasm)
+            .context("failed to parse core module")?;
+        let core = core.emit_wasm();
+
+        let engine = wasmi::Engine::default();
+        let module = wasmi::Module::new(&engine, &core[..]).map_err(|e| anyhow!("{}", e))?;
+        let mut store = wasmi::Store::new(
+            &engine,
+            State {
+                wit: wit.clone(),
+                funcs: Vec::new(),
+                memories: Vec::new(),
+            },
+        );
+        let mut linker = wasmi::Linker::new(&engine);
+        let mut core_func = 0;
+        for import in module.imports() {
+            let (module, name) = (import.module(), import.name());
+            match import.ty() {
+                ExternType::Func(ty) => {
+                    let implement = wit.implements.iter().find(|i| i.core_func == core_func);
+                    let desc = format!("unimplemented import `{}::{}`", module, name);
+                    match implement.map(|i| i.adapter_func) {
+                        Some(adapter) => linker.func_new(module, name, ty.clone(), {
+                            move |mut caller, params, results| {
+                                let args = params
+                                    .iter()
+                                    .map(|p| from_val(&caller, p))
+                                    .collect::<Result<_>>();
+                                let ret = args.and_then(|a| call_adapter(&mut caller, adapter, a));
+                                let ret = ret.map_err(|e| wasmi::Error::new(format!("{:#}", e)))?;
+                                for (slot, val) in results.iter_mut().zip(ret) {
+                                    *slot = to_val(&mut caller, val);
+                                }
+                                Ok(())
+                            }
+                        }),
+                        None => linker.func_new(module, name, ty.clone(), move |_, _, _| {
+                            Err(wasmi::Error::new(desc.clone()))
+                        }),
+                    }
+                    .map_err(|e| anyhow!("{}", e))?;
+                    core_func += 1;
+                }
+                ExternType::Memory(ty) => {
+                    let memory =
+                        wasmi::Memory::new(&mut store, *ty).map_err(|e| anyhow!("{}", e))?;
+                    linker
+                        .define(module, name, memory)
+                        .map_err(|e| anyhow!("{}", e))?;
+                }
+                _ => bail!("unsupported import `{}::{}`", module, name),
+            }
+        }
+        let instance = linker
+            .instantiate(&mut store, &module)
+            .and_then(|pre| pre.start(&mut store))
+            .map_err(|e| anyhow!("failed to instantiate: {}", e))?;
+
+        let mut funcs = Vec::new();
+        while let Some(f) = instance.get_func(&store, &format!("exec:func{}", funcs.len())) {
+            funcs.push(f);
+        }
+        let mut memories = Vec::new();
+        while let Some(m) = instance.get_memory(&store, &format!("exec:memory{}", memories.len())) {
+            memories.push(m);
+        }
+        let state = store.data_mut();
+        state.funcs = funcs;
+        state.memories = memories;
+        Ok(Instance { store })
+    }
+
+    /// Calls the adapter exported as `name` with `args`, returning its
+    /// results.
+    ///
+    /// Traps, and calls with arguments of the wrong type, are returned as
+    /// errors.
+    pub fn invoke(&mut self, name: &str, args: Vec<Value>) -> Result<Vec<Value>> {
+        let wit = self.store.data().wit.clone();
+        let export = wit
+            .exports
+            .iter()
+            .find(|e| e.name == name)
+            .ok_or_else(|| anyhow!("no adapter exported as `{}`", name))?;
+        let params = &adapter_type(&wit, export.func)?.params;
+        let tys = args.iter().map(|a| a.ty()).collect::<Vec<_>>();
+        if *params != tys {
+            bail!("expected arguments of type {:?}, found {:?}", params, tys);
+        }
+        call_adapter(&mut self.store, export.func, args)
+    }
+}
+
+fn call_adapter(
+    cx: &mut impl AsContextMut<Data = State>,
+    idx: u32,
+    args: Vec<Value>,
+) -> Result<Vec<Value>> {
+    let wit = cx.as_context().data().wit.clone();
+    let func = match idx.checked_sub(wit.imports.len() as u32) {
+        Some(i) => wit
+            .funcs
+            .get(i as usize)
+            .ok_or_else(|| anyhow!("adapter function index out of bounds: {}", idx))?,
+        None => {
+            let import = &wit.imports[idx as usize];
+            bail!("unimplemented import `{}::{}`", import.module, import.name)
+        }
+    };
+
+    let mut stack = Vec::new();
+    let mut deferred = Vec::new();
+    let ret = run(cx, &wit, &func.instrs, &args, &mut stack, &mut deferred);
+    // Like the `finally` block in the JS from `wit-bindgen-js`, deferred calls
+    // run even if the adapter traps, and a trap in one of them wins.
+    for (f, args) in deferred {
+        call_core(cx, f, args)?;
+    }
+    ret.map(|()| stack)
+}
+
+/// Runs the instructions of an adapter, leaving its results in `stack` and the
+/// core calls it defers in `deferred`.
+fn run(
+    cx: &mut impl AsContextMut<Data = State>,
+    wit: &wit_ir::Module,
+    instrs: &[Instruction],
+    args: &[Value],
+    stack: &mut Vec<Value>,
+    deferred: &mut Vec<(u32, Vec<Value>)>,
+) -> Result<()> {
+    for instr in instrs.iter() {
+        match instr {
+            Instruction::ArgGet(i) => stack.push(
+                args.get(*i as usize)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("argument index out of bounds: {}", i))?,
+            ),
+            Instruction::CallCore(f) => {
+                let n = core_func(cx, *f)?.ty(&*cx).params().len();
+                let args = split_off(stack, n)?;
+                stack.extend(call_core(cx, *f, args)?);
+            }
+            Instruction::CallAdapter(f) => {
+                let n = adapter_type(wit, *f)?.params.len();
+                let args = split_off(stack, n)?;
+                stack.extend(call_adapter(cx, *f, args)?);
+            }
+            Instruction::DeferCallCore(f) => {
+                // The arguments to a deferred call stay on the stack.
+                let n = core_func(cx, *f)?.ty(&*cx).params().len();
+                let args = split_off(stack, n)?;
+                stack.extend(args.iter().cloned());
+                deferred.push((*f, args));
+            }
+            Instruction::MemoryToString(mem) => {
+                let len = pop_i32(stack)? as u32 as usize;
+                let ptr = pop_i32(stack)? as u32 as usize;
+                let data = memory(cx, *mem)?.data(&*cx);
+                let bytes = ptr
+                    .checked_add(len)
+                    .and_then(|end| data.get(ptr..end))
+                    .ok_or_else(|| anyhow!("out of bounds memory access"))?;
+                let s = std::str::from_utf8(bytes).map_err(|_| anyhow!("invalid utf-8"))?;
+                stack.push(Value::String(s.to_string()));
+            }
+            Instruction::StringToMemory(args) => {
+                let s = match stack.pop() {
+                    Some(Value::String(s)) => s,
+                    other => bail!("expected a string on the stack, found {:?}", other),
+                };
+                let len = i32::try_from(s.len()).map_err(|_| anyhow!("string too long"))?;
+                let mut ret = call_core(cx, args.malloc, vec![Value::I32(len)])?;
+                let ptr = pop_i32(&mut ret)?;
+                let data = memory(cx, args.mem)?.data_mut(&mut *cx);
+                (ptr as u32 as usize)
+                    .checked_add(s.len())
+                    .and_then(|end| data.get_mut(ptr as u32 as usize..end))
+                    .ok_or_else(|| anyhow!("out of bounds memory access"))?
+                    .copy_from_slice(s.as_bytes());
+                stack.push(Value::I32(ptr));
+                stack.push(Value::I32(len));
+            }
+            Instruction::End => bail!("extra `end` instruction found"),
+            Instruction::Extension(ext) => {
+                bail!("unsupported extension instruction: {}", ext.opcode)
+            }
+            other => {
+                let val = stack
+                    .pop()
+                    .ok_or_else(|| anyhow!("value stack underflow"))?;
+                stack.push(convert(other, val)?);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn convert(instr: &Instruction, val: Value) -> Result<Value> {
+    use Instruction::*;
+
+    let overflow = || anyhow!("integer overflow");
+    Ok(match (instr, val) {
+        (I32ToS8, Value::I32(x)) => Value::S8(x as i8),
+        (I32ToS8X, Value::I32(x)) => Value::S8(i8::try_from(x).map_err(|_| overflow())?),
+        (I32ToU8, Value::I32(x)) => Value::U8(x as u8),
+        (I32ToS16, Value::I32(x)) => Value::S16(x as i16),
+        (I32ToS16X, Value::I32(x)) => Value::S16(i16::try_from(x).map_err(|_| overflow())?),
+        (I32ToU16, Value::I32(x)) => Value::U16(x as u16),
+        (I32ToS32, Value::I32(x)) => Value::S32(x),
+        (I32ToU32, Value::I32(x)) => Value::U32(x as u32),
+        (I32ToS64, Value::I32(x)) => Value::S64(x.into()),
+        (I32ToU64, Value::I32(x)) => Value::U64((x as u32).into()),
+
+        (I64ToS8, Value::I64(x)) => Value::S8(x as i8),
+        (I64ToS8X, Value::I64(x)) => Value::S8(i8::try_from(x).map_err(|_| overflow())?),
+        (I64ToU8, Value::I64(x)) => Value::U8(x as u8),
+        (I64ToS16, Value::I64(x)) => Value::S16(x as i16),
+        (I64ToS16X, Value::I64(x)) => Value::S16(i16::try_from(x).map_err(|_| overflow())?),
+        (I64ToU16, Value::I64(x)) => Value::U16(x as u16),
+        (I64ToS32, Value::I64(x)) => Value::S32(x as i32),
+        (I64ToS32X, Value::I64(x)) => Value::S32(i32::try_from(x).map_err(|_| overflow())?),
+        (I64ToU32, Value::I64(x)) => Value::U32(x as u32),
+        (I64ToS64, Value::I64(x)) => Value::S64(x),
+        (I64ToU64, Value::I64(x)) => Value::U64(x as u64),
+
+        (S8ToI32, Value::S8(x)) => Value::I32(x.into()),
+        (U8ToI32, Value::U8(x)) => Value::I32(x.into()),
+        (S16ToI32, Value::S16(x)) => Value::I32(x.into()),
+        (U16ToI32, Value::U16(x)) => Value::I32(x.into()),
+        (S32ToI32, Value::S32(x)) => Value::I32(x),
+        (U32ToI32, Value::U32(x)) => Value::I32(x as i32),
+        (S64ToI32, Value::S64(x)) => Value::I32(x as i32),
+        (S64ToI32X, Value::S64(x)) => Value::I32(i32::try_from(x).map_err(|_| overflow())?),
+        (U64ToI32, Value::U64(x)) => Value::I32(x as i32),
+        (U64ToI32X, Value::U64(x)) => Value::I32(u32::try_from(x).map_err(|_| overflow())? as i32),
+
+        (S8ToI64, Value::S8(x)) => Value::I64(x.into()),
+        (U8ToI64, Value::U8(x)) => Value::I64(x.into()),
+        (S16ToI64, Value::S16(x)) => Value::I64(x.into()),
+        (U16ToI64, Value::U16(x)) => Value::I64(x.into()),
+        (S32ToI64, Value::S32(x)) => Value::I64(x.into()),
+        (U32ToI64, Value::U32(x)) => Value::I64(x.into()),
+        (S64ToI64, Value::S64(x)) => Value::I64(x),
+        (U64ToI64, Value::U64(x)) => Value::I64(x as i64),
+
+        (instr, val) => bail!("cannot apply `{:?}` to {:?}", instr, val),
+    })
+}
+
+fn call_core(
+    cx: &mut impl AsContextMut<Data = State>,
+    idx: u32,
+    args: Vec<Value>,
+) -> Result<Vec<Value>> {
+    let func = core_func(cx, idx)?;
+    let params = args
+        .into_iter()
+        .map(|a| to_val(&mut *cx, a))
+        .collect::<Vec<_>>();
+    let mut results = func
+        .ty(&*cx)
+        .results()
+        .iter()
+        .map(|ty| Val::default(*ty))
+        .collect::<Vec<_>>();
+    func.call(&mut *cx, &params, &mut results)
+        .map_err(|e| anyhow!("{}", e))?;
+    results.iter().map(|r| from_val(&*cx, r)).collect()
+}
+
+fn core_func(cx: &impl AsContext<Data = State>, idx: u32) -> Result<wasmi::Func> {
+    cx.as_context()
+        .data()
+        .funcs
+        .get(idx as usize)
+        .copied()
+        .ok_or_else(|| anyhow!("core function index out of bounds: {}", idx))
+}
+
+fn memory(cx: &impl AsContext<Data = State>, idx: u32) -> Result<wasmi::Memory> {
+    cx.as_context()
+        .data()
+        .memories
+        .get(idx as usize)
+        .copied()
+        .ok_or_else(|| anyhow!("memory index out of bounds: {}", idx))
+}
+
+fn adapter_type(wit: &wit_ir::Module, idx: u32) -> Result<&wit_ir::Type> {
+    let ty = match idx.checked_sub(wit.imports.len() as u32) {
+        Some(i) => wit.funcs.get(i as usize).map(|f| f.ty),
+        None => Some(wit.imports[idx as usize].ty),
+    };
+    ty.and_then(|ty| wit.types.get(ty as usize))
+        .ok_or_else(|| anyhow!("adapter function index out of bounds: {}", idx))
+}
+
+fn to_val(cx: impl AsContextMut, val: Value) -> Val {
+    match val {
+        Value::I32(x) => Val::I32(x),
+        Value::I64(x) => Val::I64(x),
+        Value::F32(x) => Val::F32(wasmi::core::F32::from_bits(x)),
+        Value::F64(x) => Val::F64(wasmi::core::F64::from_bits(x)),
+        Value::Externref(x) => Val::ExternRef(ExternRef::new::<u32>(cx, x)),
+        // Validation guarantees that only core values are passed to core
+        // functions.
+        other => panic!("{:?} isn't a core value", other),
+    }
+}
+
+fn from_val(cx: impl AsContext, val: &Val) -> Result<Value> {
+    Ok(match val {
+        Val::I32(x) => Value::I32(*x),
+        Val::I64(x) => Value::I64(*x),
+        Val::F32(x) => Value::F32(x.to_bits()),
+        Val::F64(x) => Value::F64(x.to_bits()),
+        Val::ExternRef(r) => Value::Externref(
+            r.data(cx.as_context())
+                .map(|data| *data.downcast_ref::<u32>().unwrap()),
+        ),
+        Val::FuncRef(_) => bail!("funcref values aren't supported"),
+    })
+}
+
+fn split_off(stack: &mut Vec<Value>, n: usize) -> Result<Vec<Value>> {
+    if n > stack.len() {
+        bail!("value stack underflow");
+    }
+    Ok(stack.split_off(stack.len() - n))
+}
+
+fn pop_i32(stack: &mut Vec<Value>) -> Result<i32> {
+    match stack.pop() {
+        Some(Value::I32(x)) => Ok(x),
+        other => bail!("expected an i32 on the stack, found {:?}", other),
+    }
+}
+
+fn section(wasm: &[u8]) -> Result<Option<&[u8]>> {
+    for payload in wasmparser::Parser::new(0).parse_all(wasm) {
+        if let Payload::CustomSection {
+            name: wit_schema_version::SECTION_NAME,
+            data,
+            ..
+        } = payload?
+        {
+            return Ok(Some(data));
+        }
+    }
+    Ok(None)
+}
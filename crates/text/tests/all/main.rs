@@ -1,17 +1,26 @@
 //! Test suite which executes everything inside of the top-level `tests`
 //! directory. Each file is its own test and has an assertion of the expected
-//! output at the end of the test.
+//! output at the end of the test, except for `*.wast` scripts which are made
+//! up of their own assertions.
 
 use anyhow::{bail, Context, Result};
 use std::fs;
 use std::path::Path;
 
+mod exec;
+mod script;
+
 fn main() {
     std::env::set_current_dir("../..").unwrap();
     test_helpers::run("tests".as_ref(), run);
 }
 
 fn run(path: &Path) -> Result<String> {
+    if path.extension().and_then(|s| s.to_str()) == Some("wast") {
+        script::run(path)?;
+        return Ok(String::new());
+    }
+
     let test = Test::read_from(path)?;
 
     let binary = wit_text::parse_file(path);
@@ -78,6 +87,18 @@ fn run(path: &Path) -> Result<String> {
         );
     }
 
+    // Folded printing must also parse back to exactly the same binary.
+    let folded = wit_printer::Config::new()
+        .fold_instructions(true)
+        .print_bytes(&binary)?;
+    if wit_text::parse_str(&folded)? != binary {
+        bail!(
+            "round-trip serialization of the folded text failed:\n\n\
+             tried to serialize:\n    {}",
+            folded.replace("\n", "\n    ")
+        );
+    }
+
     // And that all passed! Consider our success as the fully-pretty-printed
     // version of the module.
     Ok(wit)
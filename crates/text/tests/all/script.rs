@@ -0,0 +1,346 @@
+//! Support for `*.wast` scripts, which are lists of modules and assertions
+//! about them in the style of the core wasm spec tests:
+//!
+//! * `(module ...)` - defines a module which must be valid, and which later
+//!   `invoke`s run against.
+//! * `(assert_malformed (module quote "...") "message")` - asserts that the
+//!   module fails to parse with an error containing `message`.
+//! * `(assert_invalid (module ...) "message")` - asserts that the module
+//!   parses but fails validation with an error containing `message`.
+//! * `(assert_return (invoke "name" values...) values...)` - invokes the
+//!   adapter exported as `name` and asserts it returns the listed values.
+//! * `(assert_trap (invoke "name" values...) "message")` - asserts that
+//!   invoking the adapter traps with an error containing `message`.
+//! * `(invoke "name" values...)` - invokes an adapter ignoring its results.
+//!
+//! Values are written as `(s8.const 1)` through `(u64.const 1)`,
+//! `(i32.const 1)`, `(i64.const 1)`, `(f32.const 1.0)`, `(f64.const 1.0)`,
+//! `(string.const "...")`, `(ref.extern 1)` and `(ref.null extern)`.
+
+use crate::exec::{Instance, Value};
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+use wast::parser::{self, Parse, ParseBuffer, Parser};
+use wast::Span;
+
+mod kw {
+    pub use wast::kw::*;
+
+    wast::custom_keyword!(f32_const = "f32.const");
+    wast::custom_keyword!(f64_const = "f64.const");
+    wast::custom_keyword!(i32_const = "i32.const");
+    wast::custom_keyword!(i64_const = "i64.const");
+    wast::custom_keyword!(ref_extern = "ref.extern");
+    wast::custom_keyword!(s16_const = "s16.const");
+    wast::custom_keyword!(s32_const = "s32.const");
+    wast::custom_keyword!(s64_const = "s64.const");
+    wast::custom_keyword!(s8_const = "s8.const");
+    wast::custom_keyword!(string_const = "string.const");
+    wast::custom_keyword!(u16_const = "u16.const");
+    wast::custom_keyword!(u32_const = "u32.const");
+    wast::custom_keyword!(u64_const = "u64.const");
+    wast::custom_keyword!(u8_const = "u8.const");
+}
+
+/// Runs every directive in the script at `path`, failing on the first
+/// assertion which doesn't hold.
+pub fn run(path: &Path) -> Result<()> {
+    let contents =
+        fs::read_to_string(path).context(format!("failed to read `{}`", path.display()))?;
+    run_str(&contents).map_err(|mut e| {
+        e.set_path(path);
+        e.set_text(&contents);
+        e.into()
+    })
+}
+
+fn run_str(contents: &str) -> Result<(), wast::Error> {
+    let buf = ParseBuffer::new(contents)?;
+    let script = parser::parse::<Script>(&buf)?;
+    let mut instance = None;
+    for directive in script.directives {
+        match directive {
+            Directive::Module(mut module) => {
+                let span = module.span();
+                let wasm = module.encode()?;
+                wit_validator::validate(&wasm).map_err(|e| error(span, e))?;
+                instance = Some(Instance::new(&wasm).map_err(|e| error(span, e))?);
+            }
+            Directive::AssertMalformed {
+                span,
+                mut module,
+                message,
+            } => match module.encode() {
+                Ok(_) => return Err(error(span, "expected a malformed module")),
+                Err(e) => check_message(span, &e.to_string(), message)?,
+            },
+            Directive::AssertInvalid {
+                span,
+                mut module,
+                message,
+            } => {
+                let wasm = module.encode()?;
+                match wit_validator::validate(&wasm) {
+                    Ok(()) => return Err(error(span, "expected an invalid module")),
+                    Err(e) => check_message(span, &format!("{:?}", e), message)?,
+                }
+            }
+            Directive::AssertReturn {
+                span,
+                invoke,
+                results,
+            } => {
+                let actual = invoke.run(instance.as_mut())?;
+                if actual != results {
+                    let msg = format!("expected {:?}, found {:?}", results, actual);
+                    return Err(error(span, msg));
+                }
+            }
+            Directive::AssertTrap {
+                span,
+                invoke,
+                message,
+            } => match invoke.run(instance.as_mut()) {
+                Ok(results) => {
+                    let msg = format!("expected a trap, found {:?}", results);
+                    return Err(error(span, msg));
+                }
+                Err(e) => check_message(span, &e.to_string(), message)?,
+            },
+            Directive::Invoke(invoke) => {
+                invoke.run(instance.as_mut())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn check_message(span: Span, actual: &str, expected: &str) -> Result<(), wast::Error> {
+    if actual.contains(expected) {
+        return Ok(());
+    }
+    let msg = format!(
+        "expected an error containing {:?}, found: {}",
+        expected, actual
+    );
+    Err(error(span, msg))
+}
+
+fn error(span: Span, msg: impl std::fmt::Display) -> wast::Error {
+    wast::Error::new(span, format!("{:#}", msg))
+}
+
+struct Script<'a> {
+    directives: Vec<Directive<'a>>,
+}
+
+impl<'a> Parse<'a> for Script<'a> {
+    fn parse(parser: Parser<'a>) -> parser::Result<Self> {
+        let mut directives = Vec::new();
+        while !parser.is_empty() {
+            directives.push(parser.parens(|p| p.parse())?);
+        }
+        Ok(Script { directives })
+    }
+}
+
+enum Directive<'a> {
+    Module(ScriptModule<'a>),
+    AssertMalformed {
+        span: Span,
+        module: ScriptModule<'a>,
+        message: &'a str,
+    },
+    AssertInvalid {
+        span: Span,
+        module: ScriptModule<'a>,
+        message: &'a str,
+    },
+    AssertReturn {
+        span: Span,
+        invoke: Invoke<'a>,
+        results: Vec<Value>,
+    },
+    AssertTrap {
+        span: Span,
+        invoke: Invoke<'a>,
+        message: &'a str,
+    },
+    Invoke(Invoke<'a>),
+}
+
+impl<'a> Parse<'a> for Directive<'a> {
+    fn parse(parser: Parser<'a>) -> parser::Result<Self> {
+        let mut l = parser.lookahead1();
+        if l.peek::<kw::module>() {
+            return Ok(Directive::Module(parser.parse()?));
+        }
+        if l.peek::<kw::assert_malformed>() {
+            let span = parser.parse::<kw::assert_malformed>()?.0;
+            return Ok(Directive::AssertMalformed {
+                span,
+                module: parser.parens(|p| p.parse())?,
+                message: parser.parse()?,
+            });
+        }
+        if l.peek::<kw::assert_invalid>() {
+            let span = parser.parse::<kw::assert_invalid>()?.0;
+            return Ok(Directive::AssertInvalid {
+                span,
+                module: parser.parens(|p| p.parse())?,
+                message: parser.parse()?,
+            });
+        }
+        if l.peek::<kw::assert_return>() {
+            let span = parser.parse::<kw::assert_return>()?.0;
+            let invoke = parser.parens(|p| p.parse())?;
+            let mut results = Vec::new();
+            while !parser.is_empty() {
+                results.push(parser.parens(|p| p.parse())?);
+            }
+            return Ok(Directive::AssertReturn {
+                span,
+                invoke,
+                results,
+            });
+        }
+        if l.peek::<kw::assert_trap>() {
+            let span = parser.parse::<kw::assert_trap>()?.0;
+            return Ok(Directive::AssertTrap {
+                span,
+                invoke: parser.parens(|p| p.parse())?,
+                message: parser.parse()?,
+            });
+        }
+        if l.peek::<kw::invoke>() {
+            return Ok(Directive::Invoke(parser.parse()?));
+        }
+        Err(l.error())
+    }
+}
+
+/// A module in a script, either written inline or as strings to be parsed
+/// with `(module quote ...)`.
+enum ScriptModule<'a> {
+    Text(wit_text::Module<'a>),
+    Quote(Span, Vec<&'a [u8]>),
+}
+
+impl ScriptModule<'_> {
+    fn span(&self) -> Span {
+        match self {
+            ScriptModule::Text(m) => m.core.span,
+            ScriptModule::Quote(span, _) => *span,
+        }
+    }
+
+    fn encode(&mut self) -> Result<Vec<u8>, wast::Error> {
+        match self {
+            ScriptModule::Text(m) => m.encode(),
+            ScriptModule::Quote(span, strings) => {
+                let mut text = String::new();
+                for s in strings.iter() {
+                    let s = std::str::from_utf8(s)
+                        .map_err(|_| error(*span, "malformed UTF-8 encoding"))?;
+                    text.push_str(s);
+                    text.push(' ');
+                }
+                // Errors here already point into the quoted text rather than
+                // the script.
+                wit_text::parse_str(&text)
+            }
+        }
+    }
+}
+
+impl<'a> Parse<'a> for ScriptModule<'a> {
+    fn parse(parser: Parser<'a>) -> parser::Result<Self> {
+        if parser.peek2::<kw::quote>() {
+            let span = parser.parse::<kw::module>()?.0;
+            parser.parse::<kw::quote>()?;
+            let mut strings = Vec::new();
+            while !parser.is_empty() {
+                strings.push(parser.parse()?);
+            }
+            return Ok(ScriptModule::Quote(span, strings));
+        }
+        Ok(ScriptModule::Text(parser.parse()?))
+    }
+}
+
+struct Invoke<'a> {
+    span: Span,
+    name: &'a str,
+    args: Vec<Value>,
+}
+
+impl Invoke<'_> {
+    fn run(self, instance: Option<&mut Instance>) -> Result<Vec<Value>, wast::Error> {
+        let span = self.span;
+        let instance = instance.ok_or_else(|| error(span, "no module to invoke"))?;
+        instance
+            .invoke(self.name, self.args)
+            .map_err(|e| error(span, e))
+    }
+}
+
+impl<'a> Parse<'a> for Invoke<'a> {
+    fn parse(parser: Parser<'a>) -> parser::Result<Self> {
+        let span = parser.parse::<kw::invoke>()?.0;
+        let name = parser.parse()?;
+        let mut args = Vec::new();
+        while !parser.is_empty() {
+            args.push(parser.parens(|p| p.parse())?);
+        }
+        Ok(Invoke { span, name, args })
+    }
+}
+
+impl<'a> Parse<'a> for Value {
+    fn parse(parser: Parser<'a>) -> parser::Result<Self> {
+        let mut l = parser.lookahead1();
+        macro_rules! value {
+            ($($kw:ident => $variant:ident,)*) => ($(
+                if l.peek::<kw::$kw>() {
+                    parser.parse::<kw::$kw>()?;
+                    return Ok(Value::$variant(parser.parse()?));
+                }
+            )*)
+        }
+        value! {
+            s8_const => S8,
+            s16_const => S16,
+            s32_const => S32,
+            s64_const => S64,
+            u8_const => U8,
+            u16_const => U16,
+            u32_const => U32,
+            u64_const => U64,
+            i32_const => I32,
+            i64_const => I64,
+        }
+        if l.peek::<kw::f32_const>() {
+            parser.parse::<kw::f32_const>()?;
+            return Ok(Value::F32(parser.parse::<wast::Float32>()?.bits));
+        }
+        if l.peek::<kw::f64_const>() {
+            parser.parse::<kw::f64_const>()?;
+            return Ok(Value::F64(parser.parse::<wast::Float64>()?.bits));
+        }
+        if l.peek::<kw::string_const>() {
+            parser.parse::<kw::string_const>()?;
+            return Ok(Value::String(parser.parse::<&str>()?.to_string()));
+        }
+        if l.peek::<kw::ref_extern>() {
+            parser.parse::<kw::ref_extern>()?;
+            return Ok(Value::Externref(Some(parser.parse()?)));
+        }
+        if l.peek::<kw::ref_null>() {
+            parser.parse::<kw::ref_null>()?;
+            parser.parse::<kw::r#extern>()?;
+            return Ok(Value::Externref(None));
+        }
+        Err(l.error())
+    }
+}
@@ -0,0 +1,122 @@
+//! Tests for building `wit_text` ASTs in code rather than parsing them.
+
+use wast::{Index, Span};
+use wit_text::*;
+
+fn module(adapters: Vec<Adapter<'static>>) -> Module<'static> {
+    Module {
+        core: wast::Module {
+            span: Span::from_offset(0),
+            id: None,
+            name: None,
+            kind: wast::ModuleKind::Text(Vec::new()),
+        },
+        adapters,
+    }
+}
+
+fn identity(export: Option<&'static str>) -> Adapter<'static> {
+    let span = Span::from_offset(0);
+    Adapter::Func(Func {
+        span,
+        name: None,
+        export,
+        ty: TypeUse {
+            index_span: None,
+            index: None,
+            ty: Type {
+                name: None,
+                params: vec![(None, ValType::S32)],
+                results: vec![ValType::S32],
+            },
+        },
+        kind: FuncKind::Inline {
+            instrs: Instructions {
+                instrs: vec![Instruction::ArgGet(Index::Num(0, span))],
+                spans: Vec::new(),
+            },
+        },
+    })
+}
+
+#[test]
+fn encode_built_module() {
+    let wasm = module(vec![identity(Some("f"))]).encode().unwrap();
+    let text = wit_printer::print_bytes(&wasm).unwrap();
+    assert!(
+        text.contains("(@interface export \"f\" (func 0))"),
+        "{}",
+        text
+    );
+    assert!(text.contains("arg.get 0"), "{}", text);
+}
+
+#[test]
+fn encode_built_module_errors() {
+    let span = Span::from_offset(0);
+    let export = Adapter::Export(Export {
+        func: Index::Num(1, span),
+        name: "f",
+    });
+    let err = module(vec![identity(None), export])
+        .encode_checked()
+        .unwrap_err();
+    assert!(err.to_string().contains("adapter export 0"), "{}", err);
+
+    let implement = Adapter::Implement(Implement {
+        span,
+        implemented: Implemented::ByName {
+            module: "a",
+            name: "b",
+        },
+        implementation: Implementation::ByIndex(Index::Num(0, span)),
+    });
+    let err = module(vec![identity(None), implement])
+        .encode()
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("not found in core module"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn encode_unexpanded_module() {
+    let span = Span::from_offset(0);
+    let import = Adapter::Func(Func {
+        span,
+        name: None,
+        export: Some("g"),
+        ty: TypeUse {
+            index_span: None,
+            index: None,
+            ty: Type {
+                name: None,
+                params: vec![(None, ValType::String)],
+                results: Vec::new(),
+            },
+        },
+        kind: FuncKind::Import {
+            module: "a",
+            name: "b",
+        },
+    });
+    let wasm = module(vec![identity(Some("f")), import]).encode().unwrap();
+    let text = wit_printer::print_bytes(&wasm).unwrap();
+    assert!(
+        text.contains("(@interface import \"a\" \"b\" (func (;0;) (type 1)))"),
+        "{}",
+        text
+    );
+    assert!(
+        text.contains("(@interface export \"g\" (func 0))"),
+        "{}",
+        text
+    );
+    assert!(
+        text.contains("(@interface export \"f\" (func 1))"),
+        "{}",
+        text
+    );
+}
@@ -0,0 +1,54 @@
+#[test]
+fn instruction_error_points_at_instruction() {
+    let wat = r#"(module
+  (func $foo (param i32))
+  (@interface func (param s32)
+    arg.get 0
+    call-core $foo)
+)"#;
+    let err = wit_text::validate_text(wat).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "failed to validate interface types section: failed to validate adapter func 0: \
+         expected I32 on type stack, found S32\n     \
+         --> <anon>:5:5\n      |\n    5 |     call-core $foo)\n      |     ^"
+    );
+}
+
+#[test]
+fn adapter_error_points_at_adapter() {
+    let wat = "(module\n  (@interface func (result s32)))";
+    let err = wit_text::validate_text(wat).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "failed to validate interface types section: failed to validate adapter func 0: \
+         expected S32 on type stack, found nothing\n     \
+         --> <anon>:2:15\n      |\n    2 |   (@interface func (result s32)))\n      |               ^"
+    );
+}
+
+#[test]
+fn valid_module() {
+    let wat = r#"(module
+  (func $foo (param i32))
+  (@interface func (param s32)
+    arg.get 0
+    s32-to-i32
+    call-core $foo)
+)"#;
+    let wasm = wit_text::validate_text(wat).unwrap();
+    wit_validator::validate(&wasm).unwrap();
+}
+
+#[test]
+fn file_errors_include_path() {
+    let path = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("validate-file.wat");
+    std::fs::write(&path, "(module\n  (@interface func (result s32)))").unwrap();
+    let err = wit_text::validate_file(&path).unwrap_err();
+    let msg = format!("{:?}", err);
+    assert!(
+        msg.contains(&format!("--> {}:2:15", path.display())),
+        "{}",
+        msg
+    );
+}
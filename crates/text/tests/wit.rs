@@ -0,0 +1,92 @@
+const CORE: &str = r#"
+    (module
+        (import "host" "log" (func $log (param i32 i32)))
+        (memory (export "memory") 1)
+        (func $malloc (param i32) (result i32)
+            i32.const 0)
+        (func $greet (param i32 i32)
+            local.get 0
+            local.get 1
+            call $log)
+        (export "malloc" (func $malloc))
+    )
+"#;
+
+const WIT: &str = r#"
+    (@interface func (export "greet") (param $msg string)
+        arg.get $msg
+        string-to-memory $malloc
+        call-core $greet)
+
+    (@interface func $log (import "host" "log") (param string))
+
+    (@interface implement (import "host" "log") (param $ptr i32) (param $len i32)
+        arg.get $ptr
+        arg.get $len
+        memory-to-string
+        call-adapter $log)
+"#;
+
+#[test]
+fn adapters_with_binary_core() {
+    let core = wat::parse_str(CORE).unwrap();
+    let wasm = wit_text::parse_str_with_core(WIT, &core).unwrap();
+    assert!(wasm.starts_with(&core));
+    wit_validator::validate(&wasm).unwrap();
+
+    let text = wit_printer::print_bytes(&wasm).unwrap();
+    assert!(text.contains("call-core $greet"), "{}", text);
+    assert!(text.contains("string-to-memory $malloc"), "{}", text);
+}
+
+#[test]
+fn unknown_core_name() {
+    let core = wat::parse_str(CORE).unwrap();
+    let wit = "(@interface func (export \"f\") call-core $missing)";
+    let err = wit_text::parse_str_with_core(wit, &core).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("failed to find func named `$missing`"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn core_must_be_binary() {
+    let err = wit_text::parse_str_with_core("", b"(module)").unwrap_err();
+    assert!(
+        err.to_string().contains("isn't a binary wasm module"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn typecheck_on_encode() {
+    let text = "(module\n  (@interface func (result s32)\n    arg.get 0))";
+    let buf = wast::parser::ParseBuffer::new(text).unwrap();
+    let mut wat = wast::parser::parse::<wit_text::Wat>(&buf).unwrap();
+    let mut err = wat.module.encode_checked().unwrap_err();
+    err.set_text(text);
+    let msg = err.to_string();
+    assert!(msg.contains("parameter index out of bounds: 0"), "{}", msg);
+    assert!(msg.contains("--> <anon>:3:5"), "{}", msg);
+}
+
+#[test]
+fn typecheck_wit_on_encode() {
+    let core = wat::parse_str(CORE).unwrap();
+    let wit = "(@interface func (export \"f\") (param s32)\n  arg.get 0\n  call-core $greet)";
+    let buf = wast::parser::ParseBuffer::new(wit).unwrap();
+    let mut ast = wast::parser::parse::<wit_text::Wit>(&buf).unwrap();
+    assert!(ast.encode(&core).is_ok());
+
+    let buf = wast::parser::ParseBuffer::new(wit).unwrap();
+    let mut ast = wast::parser::parse::<wit_text::Wit>(&buf).unwrap();
+    let mut err = ast.encode_checked(&core).unwrap_err();
+    err.set_text(wit);
+    let msg = err.to_string();
+    assert!(msg.contains("expected I32 on type stack"), "{}", msg);
+    assert!(msg.contains("--> <anon>:3:3"), "{}", msg);
+}
@@ -8,7 +8,7 @@
 
 use anyhow::{anyhow, bail, Context, Result};
 use std::collections::HashSet;
-use std::mem;
+use std::fmt;
 use wasmparser::{FuncType, ImportSectionEntryType, Payload, TypeDef};
 use wit_parser::*;
 
@@ -20,72 +20,161 @@ use wit_parser::*;
 /// the module as needed. For example core wasm functions aren't typechecked
 /// here.
 pub fn validate(bytes: &[u8]) -> Result<()> {
-    let mut validator = Validator::default();
-    for payload in wasmparser::Parser::new(0).parse_all(bytes) {
-        match payload? {
-            Payload::TypeSection(s) => {
-                validator.validate_section(1, "type", s, |v, ty| {
-                    if let TypeDef::Func(ty) = ty {
-                        v.core_types.push(ty);
-                    }
-                    Ok(())
-                })?;
-            }
-            Payload::ImportSection(s) => {
-                validator.validate_section(2, "import", s, |v, ty| {
-                    match ty.ty {
-                        ImportSectionEntryType::Function(ty) => {
-                            v.validate_core_type_idx(ty)?;
-                            v.core_funcs.push((ty, CoreFunc::Import));
+    Config::new().validate(bytes)
+}
+
+/// Configuration for how a module is validated.
+#[derive(Clone, Debug)]
+pub struct Config {
+    strict: bool,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config { strict: true }
+    }
+}
+
+impl Config {
+    /// Creates a new configuration with default settings.
+    pub fn new() -> Config {
+        Config::default()
+    }
+
+    /// Whether subsections and extension instructions which aren't known to
+    /// this validator are rejected.
+    ///
+    /// When `false` unknown subsections are skipped entirely, and adapter
+    /// functions containing extension instructions are only checked up to the
+    /// first such instruction, since its effect on the stack isn't known.
+    /// Defaults to `true`.
+    pub fn strict(&mut self, strict: bool) -> &mut Config {
+        self.strict = strict;
+        self
+    }
+
+    /// Validates an entire WebAssembly module listed by `bytes` with this
+    /// configuration.
+    ///
+    /// See [`validate`] for more information.
+    pub fn validate(&self, bytes: &[u8]) -> Result<()> {
+        let mut validator = Validator {
+            strict: self.strict,
+            ..Validator::default()
+        };
+        for payload in wasmparser::Parser::new(0).parse_all(bytes) {
+            match payload? {
+                Payload::TypeSection(s) => {
+                    validator.validate_section(1, "type", s, |v, ty| {
+                        if let TypeDef::Func(ty) = ty {
+                            v.core_types.push(ty);
                         }
-                        ImportSectionEntryType::Memory(_) => {
-                            v.memories += 1;
+                        Ok(())
+                    })?;
+                }
+                Payload::ImportSection(s) => {
+                    validator.validate_section(2, "import", s, |v, ty| {
+                        match ty.ty {
+                            ImportSectionEntryType::Function(ty) => {
+                                v.validate_core_type_idx(ty)?;
+                                v.core_funcs.push((ty, CoreFunc::Import));
+                            }
+                            ImportSectionEntryType::Memory(_) => {
+                                v.memories += 1;
+                            }
+                            _ => {}
                         }
-                        _ => {}
-                    }
-                    Ok(())
-                })?;
-            }
-            Payload::FunctionSection(s) => {
-                validator.validate_section(3, "function", s, |v, ty| {
-                    v.validate_core_type_idx(ty)?;
-                    v.core_funcs.push((ty, CoreFunc::Local));
-                    Ok(())
-                })?;
-            }
-            Payload::MemorySection(s) => {
-                validator.validate_section(4, "memory", s, |v, _| {
-                    v.memories += 1;
-                    Ok(())
-                })?;
-            }
-            Payload::CustomSection {
-                name: wit_schema_version::SECTION_NAME,
-                data,
-                data_offset,
-            } => {
-                validator
-                    .validate_wit_custom_section(data_offset, data)
-                    .context("failed to validate interface types section")?;
-            }
-            _ => {}
+                        Ok(())
+                    })?;
+                }
+                Payload::FunctionSection(s) => {
+                    validator.validate_section(3, "function", s, |v, ty| {
+                        v.validate_core_type_idx(ty)?;
+                        v.core_funcs.push((ty, CoreFunc::Local));
+                        Ok(())
+                    })?;
+                }
+                Payload::MemorySection(s) => {
+                    validator.validate_section(4, "memory", s, |v, _| {
+                        v.memories += 1;
+                        Ok(())
+                    })?;
+                }
+                Payload::CustomSection {
+                    name: wit_schema_version::SECTION_NAME,
+                    data,
+                    data_offset,
+                } => {
+                    validator
+                        .validate_wit_custom_section(data_offset, data)
+                        .context("failed to validate interface types section")?;
+                }
+                _ => {}
+            }
         }
+        Ok(())
+    }
+}
+
+/// Context attached to errors returned from [`validate`] which describes the
+/// item of the wasm interface types section that failed to validate and where
+/// it's located.
+///
+/// This can be retrieved from an error with `anyhow::Error::downcast_ref`, and
+/// displays as a plain description of the item.
+#[derive(Debug, Clone)]
+pub struct ErrorLocation {
+    message: String,
+    offset: usize,
+}
+
+impl ErrorLocation {
+    /// Returns the byte offset in the original wasm file of the item, or the
+    /// instruction within a function, which failed to validate.
+    pub fn offset(&self) -> usize {
+        self.offset
     }
-    Ok(())
 }
 
+impl fmt::Display for ErrorLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Iterators over the items of a wasm interface types subsection which know
+/// where each item is located.
+trait WitItems: Iterator {
+    fn position(&self) -> usize;
+}
+
+macro_rules! wit_items {
+    ($($name:ident)*) => ($(
+        impl WitItems for $name<'_> {
+            fn position(&self) -> usize {
+                self.original_position()
+            }
+        }
+    )*)
+}
+
+wit_items!(Types Imports Funcs Exports Implements ParamNames);
+
 /// A validator for the wasm interface types section.
 ///
 /// This structure is used to visit *just* the wasm interface types subsection,
 /// if it's already been parsed out.
 #[derive(Default)]
 struct Validator<'a> {
+    strict: bool,
     visited: bool,
     last_order: u8,
     memories: u32,
     types: Vec<Type>,
     func: Vec<u32>,
     exports: HashSet<&'a str>,
+    offset: Option<usize>,
+    param_names: HashSet<(u32, u32)>,
     core_types: Vec<FuncType>,
     core_funcs: Vec<(u32, CoreFunc)>,
     type_stack: Vec<ValType>,
@@ -114,19 +203,33 @@ impl<'a> Validator<'a> {
         while !parser.is_empty() {
             match parser.section().context("failed to read section header")? {
                 Section::Type(s) => {
-                    self.validate_section(100, "adapter type", s, Self::validate_type)?
+                    self.validate_wit_section(100, "adapter type", s, Self::validate_type)?
                 }
                 Section::Import(s) => {
-                    self.validate_section(101, "adapter import", s, Self::validate_import)?
+                    self.validate_wit_section(101, "adapter import", s, Self::validate_import)?
                 }
                 Section::Func(s) => {
-                    self.validate_section(102, "adapter func", s, Self::validate_func)?
+                    self.validate_wit_section(102, "adapter func", s, Self::validate_func)?
                 }
                 Section::Export(s) => {
-                    self.validate_section(103, "adapter export", s, Self::validate_export)?
+                    self.validate_wit_section(103, "adapter export", s, Self::validate_export)?
                 }
-                Section::Implement(s) => {
-                    self.validate_section(104, "adapter implement", s, Self::validate_implement)?
+                Section::Implement(s) => self.validate_wit_section(
+                    104,
+                    "adapter implement",
+                    s,
+                    Self::validate_implement,
+                )?,
+                Section::ParamNames(s) => self.validate_wit_section(
+                    105,
+                    "adapter param name",
+                    s,
+                    Self::validate_param_name,
+                )?,
+                Section::Unknown { id, .. } => {
+                    if self.strict {
+                        bail!("unknown subsection id: {}", id);
+                    }
                 }
             }
         }
@@ -138,25 +241,68 @@ impl<'a> Validator<'a> {
         id: u8,
         name: &str,
         iter: S,
-        mut validate: impl FnMut(&mut Self, T) -> Result<()>,
+        validate: impl FnMut(&mut Self, T) -> Result<()>,
     ) -> Result<()>
     where
         S: IntoIterator<Item = Result<T, E>>,
         E: Into<anyhow::Error>,
+    {
+        self.validate_items(id, name, iter.into_iter(), |_| None, validate)
+    }
+
+    fn validate_wit_section<S, T>(
+        &mut self,
+        id: u8,
+        name: &str,
+        iter: S,
+        validate: impl FnMut(&mut Self, T) -> Result<()>,
+    ) -> Result<()>
+    where
+        S: WitItems<Item = Result<T, wit_parser::Error>>,
+    {
+        self.validate_items(id, name, iter, |i| Some(i.position()), validate)
+    }
+
+    fn validate_items<S, T, E>(
+        &mut self,
+        id: u8,
+        name: &str,
+        mut iter: S,
+        position: impl Fn(&S) -> Option<usize>,
+        mut validate: impl FnMut(&mut Self, T) -> Result<()>,
+    ) -> Result<()>
+    where
+        S: Iterator<Item = Result<T, E>>,
+        E: Into<anyhow::Error>,
     {
         if id <= self.last_order {
             bail!("found `{}` section but was out of order", name);
         }
         self.last_order = id;
-        for (i, item) in iter.into_iter().enumerate() {
+        for i in 0.. {
+            self.offset = position(&iter);
+            let item = match iter.next() {
+                Some(item) => item,
+                None => break,
+            };
             let item = item
                 .map_err(|e| e.into())
-                .with_context(|| format!("failed to parse {} {}", name, i))?;
-            validate(self, item).with_context(|| format!("failed to validate {} {}", name, i))?;
+                .map_err(|e| self.locate(e, format!("failed to parse {} {}", name, i)))?;
+            validate(self, item)
+                .map_err(|e| self.locate(e, format!("failed to validate {} {}", name, i)))?;
         }
         Ok(())
     }
 
+    /// Adds `message` as context to `err`, along with the location of the
+    /// item currently being validated if it's known.
+    fn locate(&self, err: anyhow::Error, message: String) -> anyhow::Error {
+        match self.offset {
+            Some(offset) => err.context(ErrorLocation { message, offset }),
+            None => err.context(message),
+        }
+    }
+
     fn validate_type(&mut self, ty: Type) -> Result<()> {
         self.types.push(ty);
         Ok(())
@@ -169,21 +315,42 @@ impl<'a> Validator<'a> {
     }
 
     fn validate_func(&mut self, func: Func<'a>) -> Result<()> {
-        let mut type_stack = mem::replace(&mut self.type_stack, Vec::new());
+        let mut type_stack = std::mem::take(&mut self.type_stack);
         self.func.push(func.ty);
+        let start = self.offset;
         let ty = self.validate_adapter_type_idx(func.ty)?;
+        let (params, results) = (ty.params.clone(), ty.results.clone());
 
-        for instr in func.instrs() {
-            self.validate_instr(instr?, &ty.params, &mut type_stack)?;
+        let mut instrs = func.instrs();
+        loop {
+            self.offset = Some(instrs.original_position());
+            let instr = match instrs.next() {
+                Some(instr) => instr?,
+                None => break,
+            };
+            if let Instruction::Extension(ext) = &instr {
+                if self.strict {
+                    bail!("unknown extension instruction: {}", ext.opcode);
+                }
+                // The effect of this instruction on the stack isn't known, so
+                // the rest of the function can't be typechecked.
+                type_stack.truncate(0);
+                self.type_stack = type_stack;
+                return Ok(());
+            }
+            self.validate_instr(instr, &params, &mut type_stack)?;
         }
-        for result in ty.results.iter() {
+        // Errors about the function's results are attributed to the function
+        // itself rather than its last instruction.
+        self.offset = start;
+        for result in results.iter().rev() {
             self.expect_interface(*result, &mut type_stack)?;
         }
         if !type_stack.is_empty() {
             bail!("value stack isn't empty on function exit");
         }
         self.type_stack = type_stack;
-        return Ok(());
+        Ok(())
     }
 
     fn validate_instr(
@@ -222,8 +389,7 @@ impl<'a> Validator<'a> {
                     bail!("memory index out of bounds: {}", args.mem);
                 }
                 let ty = self.validate_core_func_idx(args.malloc)?.0;
-                if &*ty.params != [wasmparser::Type::I32] || &*ty.returns != [wasmparser::Type::I32]
-                {
+                if *ty.params != [wasmparser::Type::I32] || *ty.returns != [wasmparser::Type::I32] {
                     bail!(
                         "malloc function {} does not have correct signature",
                         args.malloc
@@ -244,19 +410,20 @@ impl<'a> Validator<'a> {
             }
             DeferCallCore(idx) => {
                 let ty = self.validate_core_func_idx(idx)?.0;
-                if ty.returns.len() > 0 {
+                if !ty.returns.is_empty() {
                     bail!("cannot have returned values in deferred calls");
                 }
                 // Make sure everything on the stack is right...
-                for param in ty.params.iter() {
+                for param in ty.params.iter().rev() {
                     self.expect_wasm(*param, stack)?;
                 }
                 // ... but don't actually consume it.
-                for param in ty.params.iter().rev() {
+                for param in ty.params.iter() {
                     stack.push(wasm2adapter(*param)?);
                 }
             }
             End => bail!("extra `end` instruction found"),
+            Extension(_) => unreachable!("handled by `validate_func`"),
 
             I32ToS8 => {
                 self.expect_wasm(wasmparser::Type::I32, stack)?;
@@ -493,6 +660,21 @@ impl<'a> Validator<'a> {
             .ok_or_else(|| anyhow!("type index too large: {}", ty))
     }
 
+    fn validate_param_name(&mut self, name: ParamName<'a>) -> Result<()> {
+        let ty = self.validate_adapter_func_idx(name.func)?;
+        if name.param as usize >= ty.params.len() {
+            bail!("parameter index out of bounds: {}", name.param);
+        }
+        if !self.param_names.insert((name.func, name.param)) {
+            bail!(
+                "found duplicate name for parameter {} of adapter func {}",
+                name.param,
+                name.func
+            );
+        }
+        Ok(())
+    }
+
     fn validate_adapter_type_idx(&self, ty: u32) -> Result<&Type> {
         self.types
             .get(ty as usize)
@@ -517,14 +699,14 @@ impl<'a> Validator<'a> {
 }
 
 fn tys_match(a: ValType, b: wasmparser::Type) -> bool {
-    match (a, b) {
+    matches!(
+        (a, b),
         (ValType::I32, wasmparser::Type::I32)
-        | (ValType::I64, wasmparser::Type::I64)
-        | (ValType::F32, wasmparser::Type::F32)
-        | (ValType::F64, wasmparser::Type::F64)
-        | (ValType::Externref, wasmparser::Type::ExternRef) => true,
-        _ => false,
-    }
+            | (ValType::I64, wasmparser::Type::I64)
+            | (ValType::F32, wasmparser::Type::F32)
+            | (ValType::F64, wasmparser::Type::F64)
+            | (ValType::Externref, wasmparser::Type::ExternRef)
+    )
 }
 
 fn wasm2adapter(a: wasmparser::Type) -> Result<ValType> {
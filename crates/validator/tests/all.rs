@@ -0,0 +1,30 @@
+use wit_parser::{Extension, Instruction};
+
+#[test]
+fn non_strict_validation() {
+    let mut writer = wit_writer::Writer::new();
+    writer.types(1).add(1, |w| w.s32(), 1, |w| w.s32());
+    let mut funcs = writer.funcs(1);
+    let mut w = funcs.add(0);
+    w.arg_get(0);
+    w.instr(&Instruction::Extension(Extension {
+        opcode: 7,
+        immediates: vec![1, 2],
+    }));
+    drop(w);
+    drop(funcs);
+
+    let mut wasm = b"\0asm\x01\0\0\0".to_vec();
+    wasm.extend_from_slice(&writer.into_custom_section());
+
+    let err = wit_validator::validate(&wasm).unwrap_err();
+    assert!(
+        format!("{:#}", err).contains("unknown extension instruction: 7"),
+        "{:#}",
+        err
+    );
+    wit_validator::Config::new()
+        .strict(false)
+        .validate(&wasm)
+        .unwrap();
+}
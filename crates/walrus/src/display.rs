@@ -0,0 +1,254 @@
+use crate::{CoreIdsToIndices, FuncKind, Instruction, ValType, WasmInterfaceTypes};
+use crate::{FuncId, TypeId};
+use std::collections::HashMap;
+use std::fmt;
+use walrus::{FunctionId, MemoryId, Module};
+
+/// Helper returned by [`WasmInterfaceTypes::display`] to print out a section in
+/// its textual form.
+///
+/// The output is the same `(@interface ...)` syntax that the `wit-printer`
+/// crate produces for a binary module, with one adapter item per line.
+pub struct Display<'a> {
+    wit: &'a WasmInterfaceTypes,
+    module: &'a Module,
+}
+
+impl WasmInterfaceTypes {
+    /// Returns an object which implements `std::fmt::Display` to print out
+    /// this section in the wasm interface types text format.
+    ///
+    /// References to core functions and memories are resolved through
+    /// `module`, the module that this section is attached to. Core functions
+    /// are printed with their `$name` if it's a valid identifier that no other
+    /// function shares, and otherwise core items are printed with the index
+    /// they'll have when `module` is emitted.
+    pub fn display<'a>(&'a self, module: &'a Module) -> Display<'a> {
+        Display { wit: self, module }
+    }
+}
+
+impl fmt::Display for Display<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let core = CoreIdsToIndices::new(self.module);
+        let mut uses = HashMap::new();
+        for func in self.module.funcs.iter() {
+            if let Some(name) = &func.name {
+                *uses.entry(name.as_str()).or_insert(0) += 1;
+            }
+        }
+        let core_names = self
+            .module
+            .funcs
+            .iter()
+            .filter_map(|func| Some((func.id(), func.name.as_deref()?)))
+            .filter(|(_, name)| is_id(name) && uses[name] == 1)
+            .collect();
+        let mut printer = Printer {
+            f,
+            core: &core,
+            core_names,
+            types: HashMap::new(),
+            funcs: HashMap::new(),
+            first: true,
+        };
+        printer.print(self.wit)
+    }
+}
+
+struct Printer<'a, 'b> {
+    f: &'a mut fmt::Formatter<'b>,
+    core: &'a CoreIdsToIndices,
+    /// Names of core functions which can be printed as `$name`.
+    core_names: HashMap<FunctionId, &'a str>,
+    types: HashMap<TypeId, u32>,
+    funcs: HashMap<FuncId, u32>,
+    first: bool,
+}
+
+impl Printer<'_, '_> {
+    fn print(&mut self, wit: &WasmInterfaceTypes) -> fmt::Result {
+        // Assign indices in the same order as they're encoded in the binary
+        // format, imports first and then local functions.
+        for ty in wit.types.iter() {
+            self.types.insert(ty.id(), self.types.len() as u32);
+        }
+        for import in wit.imports.iter() {
+            self.funcs.insert(import.func, self.funcs.len() as u32);
+        }
+        for func in wit.funcs.iter() {
+            if let FuncKind::Local(_) = func.kind {
+                self.funcs.insert(func.id(), self.funcs.len() as u32);
+            }
+        }
+
+        for ty in wit.types.iter() {
+            self.start()?;
+            write!(
+                self.f,
+                "(@interface type (;{};) (func",
+                self.types[&ty.id()]
+            )?;
+            for param in ty.params() {
+                write!(self.f, " (param {})", param)?;
+            }
+            for result in ty.results() {
+                write!(self.f, " (result {})", result)?;
+            }
+            self.f.write_str("))")?;
+        }
+        for import in wit.imports.iter() {
+            self.start()?;
+            self.f.write_str("(@interface import ")?;
+            self.string(&import.module)?;
+            self.f.write_str(" ")?;
+            self.string(&import.name)?;
+            write!(
+                self.f,
+                " (func (;{};) (type {})))",
+                self.funcs[&import.func],
+                self.types[&wit.funcs.get(import.func).ty],
+            )?;
+        }
+        for func in wit.funcs.iter() {
+            let instrs = match &func.kind {
+                FuncKind::Local(instrs) => instrs,
+                FuncKind::Import(_) => continue,
+            };
+            self.start()?;
+            write!(
+                self.f,
+                "(@interface func (;{};) (type {})",
+                self.funcs[&func.id()],
+                self.types[&func.ty],
+            )?;
+            for instr in instrs {
+                self.f.write_str("\n  ")?;
+                self.instr(instr)?;
+            }
+            self.f.write_str(")")?;
+        }
+        for export in wit.exports.iter() {
+            self.start()?;
+            self.f.write_str("(@interface export ")?;
+            self.string(&export.name)?;
+            write!(self.f, " (func {}))", self.funcs[&export.func])?;
+        }
+        for implement in wit.implements.iter() {
+            self.start()?;
+            self.f.write_str("(@interface implement (func ")?;
+            self.core_func(implement.core_func)?;
+            write!(self.f, ") (func {}))", self.funcs[&implement.adapter_func])?;
+        }
+        Ok(())
+    }
+
+    fn start(&mut self) -> fmt::Result {
+        if !self.first {
+            self.f.write_str("\n")?;
+        }
+        self.first = false;
+        Ok(())
+    }
+
+    fn instr(&mut self, instr: &Instruction) -> fmt::Result {
+        match instr {
+            Instruction::ArgGet(i) => write!(self.f, "arg.get {}", i),
+            Instruction::CallCore(f) => {
+                self.f.write_str("call-core ")?;
+                self.core_func(*f)
+            }
+            Instruction::DeferCallCore(f) => {
+                self.f.write_str("defer-call-core ")?;
+                self.core_func(*f)
+            }
+            Instruction::CallAdapter(f) => write!(self.f, "call-adapter {}", self.funcs[f]),
+            Instruction::MemoryToString(mem) => {
+                self.f.write_str("memory-to-string")?;
+                self.core_memory(*mem)
+            }
+            Instruction::StringToMemory { mem, malloc } => {
+                self.f.write_str("string-to-memory ")?;
+                self.core_func(*malloc)?;
+                self.core_memory(*mem)
+            }
+            Instruction::IntToWasm {
+                input,
+                output,
+                trap,
+            } => write!(
+                self.f,
+                "{}-to-{}{}",
+                input,
+                output,
+                if *trap { "x" } else { "" }
+            ),
+            Instruction::WasmToInt {
+                input,
+                output,
+                trap,
+            } => write!(
+                self.f,
+                "{}-to-{}{}",
+                input,
+                output,
+                if *trap { "x" } else { "" }
+            ),
+            Instruction::Extension(ext) => {
+                write!(self.f, "extension {} \"", ext.opcode)?;
+                for byte in ext.immediates.iter() {
+                    write!(self.f, "\\{:02x}", byte)?;
+                }
+                self.f.write_str("\"")
+            }
+        }
+    }
+
+    fn core_func(&mut self, id: FunctionId) -> fmt::Result {
+        let idx = match self.core.func(id) {
+            Some(i) => i,
+            None => return write!(self.f, "(;{:?};)", id),
+        };
+        match self.core_names.get(&id) {
+            Some(name) => write!(self.f, "${}", name),
+            None => write!(self.f, "{}", idx),
+        }
+    }
+
+    /// Prints `s` as a string literal, escaped the same way as `wit-printer`.
+    fn string(&mut self, s: &str) -> fmt::Result {
+        self.f.write_str("\"")?;
+        for byte in s.bytes() {
+            match byte {
+                b'"' | b'\\' => write!(self.f, "\\{}", byte as char)?,
+                b' '..=b'~' => write!(self.f, "{}", byte as char)?,
+                _ => write!(self.f, "\\{:02x}", byte)?,
+            }
+        }
+        self.f.write_str("\"")
+    }
+
+    /// Prints a memory operand, omitting it entirely if it's the default
+    /// memory.
+    fn core_memory(&mut self, id: MemoryId) -> fmt::Result {
+        match self.core.memory(id) {
+            Some(0) => Ok(()),
+            Some(i) => write!(self.f, " {}", i),
+            None => write!(self.f, " (;{:?};)", id),
+        }
+    }
+}
+
+/// Whether `name` can be printed as a `$name` identifier in the text format.
+fn is_id(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-./:<=>?@\\^_`|~".contains(c))
+}
+
+impl fmt::Display for ValType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        crate::types::walrus2parse(*self).fmt(f)
+    }
+}
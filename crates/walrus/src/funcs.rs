@@ -1,7 +1,8 @@
-use crate::WitIdsToIndices;
+use crate::{CoreIndices, WitIdsToIndices};
 use crate::{ImportId, TypeId, ValType, WasmInterfaceTypes, WitIndicesToIds};
-use anyhow::Result;
+use anyhow::{bail, Result};
 use id_arena::{Arena, Id};
+use std::collections::BTreeMap;
 use walrus::IndicesToIds;
 
 #[derive(Debug, Default)]
@@ -14,6 +15,8 @@ pub struct Func {
     id: FuncId,
     pub ty: TypeId,
     pub kind: FuncKind,
+    /// Names of this function's parameters, keyed by parameter index.
+    pub param_names: BTreeMap<u32, String>,
 }
 
 #[derive(Debug)]
@@ -43,6 +46,9 @@ pub enum Instruction {
         output: ValType,
         trap: bool,
     },
+    /// An extension instruction which isn't otherwise understood, preserved
+    /// verbatim. Any indices in its immediates aren't remapped.
+    Extension(wit_parser::Extension),
 }
 
 pub type FuncId = Id<Func>;
@@ -135,6 +141,7 @@ impl WasmInterfaceTypes {
                     R::S64ToI64 => i2w(VT::S64, RVT::I64, false),
                     R::U64ToI64 => i2w(VT::U64, RVT::I64, false),
 
+                    R::Extension(e) => W::Extension(e),
                     R::End => continue,
                 });
             }
@@ -148,11 +155,53 @@ impl WasmInterfaceTypes {
         Ok(())
     }
 
+    pub(crate) fn parse_param_names(
+        &mut self,
+        names: wit_parser::ParamNames,
+        wids: &mut WitIndicesToIds,
+    ) -> Result<()> {
+        for name in names {
+            let name = name?;
+            let func = self.funcs.get_mut(wids.func(name.func)?);
+            if func.param_names.contains_key(&name.param) {
+                bail!(
+                    "found duplicate name for parameter {} of adapter func {}",
+                    name.param,
+                    name.func
+                );
+            }
+            func.param_names.insert(name.param, name.name.to_string());
+        }
+        Ok(())
+    }
+
+    pub(crate) fn encode_param_names(
+        &self,
+        writer: &mut wit_writer::Writer,
+        wids: &WitIdsToIndices,
+    ) {
+        let mut names = self
+            .funcs
+            .iter()
+            .flat_map(|f| {
+                let func = wids.func(f.id());
+                f.param_names
+                    .iter()
+                    .map(move |(param, name)| (func, *param, name))
+            })
+            .collect::<Vec<_>>();
+        names.sort();
+        let mut w = writer.param_names(names.len() as u32);
+        for (func, param, name) in names {
+            w.add(func, param, name);
+        }
+    }
+
     pub(crate) fn encode_funcs(
         &self,
         writer: &mut wit_writer::Writer,
         wids: &mut WitIdsToIndices,
-        ids: &walrus::IdsToIndices,
+        ids: &impl CoreIndices,
     ) {
         // Filter out imported functions since those went in the import section
         let funcs = self
@@ -188,75 +237,70 @@ impl WasmInterfaceTypes {
                         input,
                         output,
                         trap,
-                    } => i2w(&mut w, input, output, trap),
+                    } => w.instr(&i2w(input, output, trap)),
                     WasmToInt {
                         input,
                         output,
                         trap,
-                    } => w2i(&mut w, input, output, trap),
+                    } => w.instr(&w2i(input, output, trap)),
+                    Extension(ref e) => w.instr(&wit_parser::Instruction::Extension(e.clone())),
                 }
             }
         }
-        fn w2i(
-            w: &mut wit_writer::Instructions<'_, '_>,
-            input: walrus::ValType,
-            output: ValType,
-            trap: bool,
-        ) {
+        fn w2i(input: walrus::ValType, output: ValType, trap: bool) -> wit_parser::Instruction {
+            use wit_parser::Instruction as R; // "raw"
+
             match (input, output, trap) {
-                (walrus::ValType::I32, ValType::S8, false) => w.i32_to_s8(),
-                (walrus::ValType::I32, ValType::S8, true) => w.i32_to_s8x(),
-                (walrus::ValType::I32, ValType::U8, _) => w.i32_to_u8(),
-                (walrus::ValType::I32, ValType::S16, false) => w.i32_to_s16(),
-                (walrus::ValType::I32, ValType::S16, true) => w.i32_to_s16x(),
-                (walrus::ValType::I32, ValType::U16, _) => w.i32_to_u16(),
-                (walrus::ValType::I32, ValType::S32, _) => w.i32_to_s32(),
-                (walrus::ValType::I32, ValType::U32, _) => w.i32_to_u32(),
-                (walrus::ValType::I32, ValType::S64, _) => w.i32_to_s64(),
-                (walrus::ValType::I32, ValType::U64, _) => w.i32_to_u64(),
-
-                (walrus::ValType::I64, ValType::S8, false) => w.i64_to_s8(),
-                (walrus::ValType::I64, ValType::S8, true) => w.i64_to_s8x(),
-                (walrus::ValType::I64, ValType::U8, _) => w.i64_to_u8(),
-                (walrus::ValType::I64, ValType::S16, false) => w.i64_to_s16(),
-                (walrus::ValType::I64, ValType::S16, true) => w.i64_to_s16x(),
-                (walrus::ValType::I64, ValType::U16, _) => w.i64_to_u16(),
-                (walrus::ValType::I64, ValType::S32, false) => w.i64_to_s32(),
-                (walrus::ValType::I64, ValType::S32, true) => w.i64_to_s32x(),
-                (walrus::ValType::I64, ValType::U32, _) => w.i64_to_u32(),
-                (walrus::ValType::I64, ValType::S64, _) => w.i64_to_s64(),
-                (walrus::ValType::I64, ValType::U64, _) => w.i64_to_u64(),
+                (walrus::ValType::I32, ValType::S8, false) => R::I32ToS8,
+                (walrus::ValType::I32, ValType::S8, true) => R::I32ToS8X,
+                (walrus::ValType::I32, ValType::U8, _) => R::I32ToU8,
+                (walrus::ValType::I32, ValType::S16, false) => R::I32ToS16,
+                (walrus::ValType::I32, ValType::S16, true) => R::I32ToS16X,
+                (walrus::ValType::I32, ValType::U16, _) => R::I32ToU16,
+                (walrus::ValType::I32, ValType::S32, _) => R::I32ToS32,
+                (walrus::ValType::I32, ValType::U32, _) => R::I32ToU32,
+                (walrus::ValType::I32, ValType::S64, _) => R::I32ToS64,
+                (walrus::ValType::I32, ValType::U64, _) => R::I32ToU64,
+
+                (walrus::ValType::I64, ValType::S8, false) => R::I64ToS8,
+                (walrus::ValType::I64, ValType::S8, true) => R::I64ToS8X,
+                (walrus::ValType::I64, ValType::U8, _) => R::I64ToU8,
+                (walrus::ValType::I64, ValType::S16, false) => R::I64ToS16,
+                (walrus::ValType::I64, ValType::S16, true) => R::I64ToS16X,
+                (walrus::ValType::I64, ValType::U16, _) => R::I64ToU16,
+                (walrus::ValType::I64, ValType::S32, false) => R::I64ToS32,
+                (walrus::ValType::I64, ValType::S32, true) => R::I64ToS32X,
+                (walrus::ValType::I64, ValType::U32, _) => R::I64ToU32,
+                (walrus::ValType::I64, ValType::S64, _) => R::I64ToS64,
+                (walrus::ValType::I64, ValType::U64, _) => R::I64ToU64,
 
                 _ => unreachable!(),
             }
         }
 
-        fn i2w(
-            w: &mut wit_writer::Instructions<'_, '_>,
-            input: ValType,
-            output: walrus::ValType,
-            trap: bool,
-        ) {
+        fn i2w(input: ValType, output: walrus::ValType, trap: bool) -> wit_parser::Instruction {
+            use wit_parser::Instruction as R; // "raw"
+
             match (input, output, trap) {
-                (ValType::S8, walrus::ValType::I32, _) => w.s8_to_i32(),
-                (ValType::U8, walrus::ValType::I32, _) => w.u8_to_i32(),
-                (ValType::S16, walrus::ValType::I32, _) => w.s16_to_i32(),
-                (ValType::U16, walrus::ValType::I32, _) => w.u16_to_i32(),
-                (ValType::S32, walrus::ValType::I32, _) => w.s32_to_i32(),
-                (ValType::U32, walrus::ValType::I32, _) => w.u32_to_i32(),
-                (ValType::S64, walrus::ValType::I32, false) => w.s64_to_i32(),
-                (ValType::S64, walrus::ValType::I32, true) => w.s64_to_i32x(),
-                (ValType::U64, walrus::ValType::I32, false) => w.u64_to_i32(),
-                (ValType::U64, walrus::ValType::I32, true) => w.u64_to_i32x(),
-
-                (ValType::S8, walrus::ValType::I64, _) => w.s8_to_i64(),
-                (ValType::U8, walrus::ValType::I64, _) => w.u8_to_i64(),
-                (ValType::S16, walrus::ValType::I64, _) => w.s16_to_i64(),
-                (ValType::U16, walrus::ValType::I64, _) => w.u16_to_i64(),
-                (ValType::S32, walrus::ValType::I64, _) => w.s32_to_i64(),
-                (ValType::U32, walrus::ValType::I64, _) => w.u32_to_i64(),
-                (ValType::S64, walrus::ValType::I64, _) => w.s64_to_i64(),
-                (ValType::U64, walrus::ValType::I64, _) => w.u64_to_i64(),
+                (ValType::S8, walrus::ValType::I32, _) => R::S8ToI32,
+                (ValType::U8, walrus::ValType::I32, _) => R::U8ToI32,
+                (ValType::S16, walrus::ValType::I32, _) => R::S16ToI32,
+                (ValType::U16, walrus::ValType::I32, _) => R::U16ToI32,
+                (ValType::S32, walrus::ValType::I32, _) => R::S32ToI32,
+                (ValType::U32, walrus::ValType::I32, _) => R::U32ToI32,
+                (ValType::S64, walrus::ValType::I32, false) => R::S64ToI32,
+                (ValType::S64, walrus::ValType::I32, true) => R::S64ToI32X,
+                (ValType::U64, walrus::ValType::I32, false) => R::U64ToI32,
+                (ValType::U64, walrus::ValType::I32, true) => R::U64ToI32X,
+
+                (ValType::S8, walrus::ValType::I64, _) => R::S8ToI64,
+                (ValType::U8, walrus::ValType::I64, _) => R::U8ToI64,
+                (ValType::S16, walrus::ValType::I64, _) => R::S16ToI64,
+                (ValType::U16, walrus::ValType::I64, _) => R::U16ToI64,
+                (ValType::S32, walrus::ValType::I64, _) => R::S32ToI64,
+                (ValType::U32, walrus::ValType::I64, _) => R::U32ToI64,
+                (ValType::S64, walrus::ValType::I64, _) => R::S64ToI64,
+                (ValType::U64, walrus::ValType::I64, _) => R::U64ToI64,
 
                 _ => unreachable!(),
             }
@@ -299,6 +343,7 @@ impl Funcs {
             id,
             ty,
             kind: FuncKind::Import(import),
+            param_names: BTreeMap::new(),
         })
     }
 
@@ -308,6 +353,7 @@ impl Funcs {
             id,
             ty,
             kind: FuncKind::Local(instrs),
+            param_names: BTreeMap::new(),
         })
     }
 }
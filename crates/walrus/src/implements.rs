@@ -1,4 +1,4 @@
-use crate::{FuncId, WasmInterfaceTypes, WitIdsToIndices, WitIndicesToIds};
+use crate::{CoreIndices, FuncId, WasmInterfaceTypes, WitIdsToIndices, WitIndicesToIds};
 use anyhow::Result;
 use id_arena::{Arena, Id};
 use walrus::IndicesToIds;
@@ -39,7 +39,7 @@ impl WasmInterfaceTypes {
         &self,
         writer: &mut wit_writer::Writer,
         wids: &WitIdsToIndices,
-        ids: &walrus::IdsToIndices,
+        ids: &impl CoreIndices,
     ) {
         let mut w = writer.implements(self.implements.arena.len() as u32);
         for implement in self.implements.iter() {
@@ -2,6 +2,7 @@ use anyhow::{anyhow, Context, Result};
 use std::borrow::Cow;
 use std::collections::HashMap;
 use walrus::{passes::Roots, CustomSection, IdsToIndices, IndicesToIds, Module};
+use walrus::{FunctionId, FunctionKind, ImportKind, MemoryId};
 use wit_schema_version::SECTION_NAME;
 
 #[derive(Debug, Default)]
@@ -11,17 +12,28 @@ pub struct WasmInterfaceTypes {
     pub implements: Implements,
     pub exports: Exports,
     pub funcs: Funcs,
+    /// Subsections with ids that aren't understood, as `(id, contents)`,
+    /// which are re-emitted verbatim after all other subsections.
+    pub unknown: Vec<(u8, Vec<u8>)>,
 }
 
+mod display;
 mod exports;
 mod funcs;
 mod implements;
 mod imports;
+mod merge;
+mod remap;
+mod synthesize;
 mod types;
+pub use self::display::*;
 pub use self::exports::*;
 pub use self::funcs::*;
 pub use self::implements::*;
 pub use self::imports::*;
+pub use self::merge::*;
+pub use self::remap::*;
+pub use self::synthesize::*;
 pub use self::types::*;
 
 impl CustomSection for WasmInterfaceTypes {
@@ -30,14 +42,7 @@ impl CustomSection for WasmInterfaceTypes {
     }
 
     fn data(&self, indices: &IdsToIndices) -> Cow<'_, [u8]> {
-        let mut writer = wit_writer::Writer::new();
-        let mut wids = WitIdsToIndices::default();
-        self.encode_types(&mut writer, &mut wids);
-        self.encode_imports(&mut writer, &mut wids);
-        self.encode_funcs(&mut writer, &mut wids, indices);
-        self.encode_exports(&mut writer, &wids);
-        self.encode_implements(&mut writer, &wids, indices);
-        writer.into_payload().into()
+        self.encode_with(indices).into()
     }
 
     fn add_gc_roots(&self, roots: &mut Roots) {
@@ -67,6 +72,40 @@ impl CustomSection for WasmInterfaceTypes {
     }
 }
 
+impl WasmInterfaceTypes {
+    /// Encodes this section into the payload of a `wasm-interface-types`
+    /// custom section.
+    ///
+    /// This produces the same bytes that `walrus` writes for this section when
+    /// `module` is emitted, without having to emit the rest of `module`. The
+    /// `module` given must be the module that this section is attached to,
+    /// and it's used to compute the indices of core functions and memories.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this section refers to core functions or memories which aren't
+    /// present in `module`, or to adapter types or functions which have been
+    /// removed.
+    pub fn encode(&self, module: &Module) -> Vec<u8> {
+        self.encode_with(&CoreIdsToIndices::new(module))
+    }
+
+    fn encode_with(&self, indices: &impl CoreIndices) -> Vec<u8> {
+        let mut writer = wit_writer::Writer::new();
+        let mut wids = WitIdsToIndices::default();
+        self.encode_types(&mut writer, &mut wids);
+        self.encode_imports(&mut writer, &mut wids);
+        self.encode_funcs(&mut writer, &mut wids, indices);
+        self.encode_exports(&mut writer, &wids);
+        self.encode_implements(&mut writer, &wids, indices);
+        self.encode_param_names(&mut writer, &wids);
+        for (id, bytes) in self.unknown.iter() {
+            writer.raw_section(*id, bytes);
+        }
+        writer.into_payload()
+    }
+}
+
 #[derive(Default)]
 struct WitIndicesToIds {
     types: Vec<TypeId>,
@@ -119,6 +158,90 @@ impl WitIdsToIndices {
     }
 }
 
+/// Lookup of core function and memory indices used when encoding a section.
+trait CoreIndices {
+    fn get_func_index(&self, f: FunctionId) -> u32;
+    fn get_memory_index(&self, m: MemoryId) -> u32;
+}
+
+impl CoreIndices for IdsToIndices {
+    fn get_func_index(&self, f: FunctionId) -> u32 {
+        IdsToIndices::get_func_index(self, f)
+    }
+
+    fn get_memory_index(&self, m: MemoryId) -> u32 {
+        IdsToIndices::get_memory_index(self, m)
+    }
+}
+
+/// The indices that `walrus` will assign to core functions and memories when a
+/// module is emitted.
+struct CoreIdsToIndices {
+    funcs: HashMap<FunctionId, u32>,
+    memories: HashMap<MemoryId, u32>,
+}
+
+impl CoreIdsToIndices {
+    fn new(module: &Module) -> CoreIdsToIndices {
+        let mut funcs = HashMap::new();
+        let mut memories = HashMap::new();
+
+        // Imports come first in each index space, in the order they're
+        // declared ...
+        for import in module.imports.iter() {
+            match import.kind {
+                ImportKind::Function(f) => {
+                    funcs.insert(f, funcs.len() as u32);
+                }
+                ImportKind::Memory(m) => {
+                    memories.insert(m, memories.len() as u32);
+                }
+                _ => {}
+            }
+        }
+
+        // ... and then local items follow. Note that `walrus` emits local
+        // functions sorted by decreasing size, so mirror that here.
+        let mut locals = module
+            .funcs
+            .iter()
+            .filter_map(|f| match &f.kind {
+                FunctionKind::Local(l) => Some((f.id(), l.size())),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        locals.sort_by_key(|(id, size)| (std::cmp::Reverse(*size), *id));
+        for (f, _) in locals {
+            funcs.insert(f, funcs.len() as u32);
+        }
+        for m in module.memories.iter().filter(|m| m.import.is_none()) {
+            memories.insert(m.id(), memories.len() as u32);
+        }
+
+        CoreIdsToIndices { funcs, memories }
+    }
+
+    fn func(&self, f: FunctionId) -> Option<u32> {
+        self.funcs.get(&f).cloned()
+    }
+
+    fn memory(&self, m: MemoryId) -> Option<u32> {
+        self.memories.get(&m).cloned()
+    }
+}
+
+impl CoreIndices for CoreIdsToIndices {
+    fn get_func_index(&self, f: FunctionId) -> u32 {
+        self.func(f)
+            .unwrap_or_else(|| panic!("reference to dead core function found {:?}", f))
+    }
+
+    fn get_memory_index(&self, m: MemoryId) -> u32 {
+        self.memory(m)
+            .unwrap_or_else(|| panic!("reference to dead core memory found {:?}", m))
+    }
+}
+
 /// Callback for the `ModuleConfig::on_parse` function in `walrus` to act as a
 /// convenience to parse the wasm interface types custom section, if present.
 pub fn on_parse(module: &mut Module, ids: &IndicesToIds) -> Result<()> {
@@ -140,6 +263,10 @@ pub fn on_parse(module: &mut Module, ids: &IndicesToIds) -> Result<()> {
             wit_parser::Section::Func(t) => section.parse_funcs(t, ids, &mut wids)?,
             wit_parser::Section::Implement(t) => section.parse_implements(t, ids, &mut wids)?,
             wit_parser::Section::Export(t) => section.parse_exports(t, &mut wids)?,
+            wit_parser::Section::ParamNames(t) => section.parse_param_names(t, &mut wids)?,
+            wit_parser::Section::Unknown { id, bytes } => {
+                section.unknown.push((id, bytes.to_vec()))
+            }
         }
     }
 
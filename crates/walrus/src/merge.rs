@@ -0,0 +1,182 @@
+use crate::{CoreIdMap, FuncId, FuncKind, Instruction, WasmInterfaceTypes};
+use anyhow::{anyhow, Result};
+use std::collections::{HashMap, HashSet};
+use walrus::{FunctionId, MemoryId, Module};
+
+impl WasmInterfaceTypes {
+    /// Copies all adapters from `src` into this section.
+    ///
+    /// The `src` section is expected to have come from a different core wasm
+    /// module, so `map` must list, for every core function and memory that
+    /// `src` refers to, the corresponding item in the core module that this
+    /// section belongs to.
+    ///
+    /// Types which are already present in this section are reused rather than
+    /// duplicated. Exports from `src` whose name is already exported from this
+    /// section are renamed with a numeric suffix to keep export names unique.
+    /// Unknown subsections of `src` aren't copied, since any indices within
+    /// them can't be remapped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `src` refers to a core function or memory which
+    /// isn't listed in `map`. No changes are made to this section in that
+    /// case.
+    pub fn merge(&mut self, src: &WasmInterfaceTypes, map: &CoreIdMap) -> Result<()> {
+        src.check_core_ids(map)?;
+
+        // First up deduplicate types, reusing any of our own types which have
+        // the same signature.
+        let mut existing = HashMap::new();
+        for ty in self.types.iter() {
+            existing
+                .entry((ty.params().to_vec(), ty.results().to_vec()))
+                .or_insert_with(|| ty.id());
+        }
+        let mut types = HashMap::new();
+        for ty in src.types.iter() {
+            let key = (ty.params().to_vec(), ty.results().to_vec());
+            let id = match existing.get(&key) {
+                Some(id) => *id,
+                None => {
+                    let id = self.types.add(key.0.clone(), key.1.clone());
+                    existing.insert(key, id);
+                    id
+                }
+            };
+            types.insert(ty.id(), id);
+        }
+
+        // Next assign a new id to each function, and only afterwards copy over
+        // function bodies so `call-adapter` can refer to any function.
+        let mut funcs = HashMap::new();
+        for func in src.funcs.iter() {
+            let ty = types[&func.ty];
+            let id = match &func.kind {
+                FuncKind::Import(i) => {
+                    let import = src.imports.get(*i);
+                    self.add_import_func(&import.module, &import.name, ty).0
+                }
+                FuncKind::Local(_) => self.funcs.add_local(ty, Vec::new()),
+            };
+            funcs.insert(func.id(), id);
+        }
+        for func in src.funcs.iter() {
+            let dst = self.funcs.get_mut(funcs[&func.id()]);
+            dst.param_names = func.param_names.clone();
+            let instrs = match &func.kind {
+                FuncKind::Local(instrs) => instrs,
+                FuncKind::Import(_) => continue,
+            };
+            let instrs = instrs
+                .iter()
+                .map(|instr| merge_instr(instr, map, &funcs))
+                .collect();
+            self.funcs.get_mut(funcs[&func.id()]).kind = FuncKind::Local(instrs);
+        }
+
+        let mut names = self
+            .exports
+            .iter()
+            .map(|e| e.name.clone())
+            .collect::<HashSet<_>>();
+        for export in src.exports.iter() {
+            let mut name = export.name.clone();
+            let mut i = 1;
+            while names.contains(&name) {
+                name = format!("{}{}", export.name, i);
+                i += 1;
+            }
+            self.exports.add(&name, funcs[&export.func]);
+            names.insert(name);
+        }
+
+        for implement in src.implements.iter() {
+            self.implements.add(
+                funcs[&implement.adapter_func],
+                core_func(map, implement.core_func)?,
+            );
+        }
+        Ok(())
+    }
+
+    fn check_core_ids(&self, map: &CoreIdMap) -> Result<()> {
+        for func in self.funcs.iter() {
+            let instrs = match &func.kind {
+                FuncKind::Local(instrs) => instrs,
+                FuncKind::Import(_) => continue,
+            };
+            for instr in instrs {
+                match instr {
+                    Instruction::CallCore(f) | Instruction::DeferCallCore(f) => {
+                        core_func(map, *f)?;
+                    }
+                    Instruction::MemoryToString(mem) => {
+                        core_memory(map, *mem)?;
+                    }
+                    Instruction::StringToMemory { mem, malloc } => {
+                        core_memory(map, *mem)?;
+                        core_func(map, *malloc)?;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        for implement in self.implements.iter() {
+            core_func(map, implement.core_func)?;
+        }
+        Ok(())
+    }
+}
+
+fn merge_instr(
+    instr: &Instruction,
+    map: &CoreIdMap,
+    funcs: &HashMap<FuncId, FuncId>,
+) -> Instruction {
+    // Note that `check_core_ids` has already verified that all core items are
+    // present in `map`.
+    let func = |f: FunctionId| map.get_func(f).unwrap();
+    let memory = |m: MemoryId| map.get_memory(m).unwrap();
+    match *instr {
+        Instruction::CallCore(f) => Instruction::CallCore(func(f)),
+        Instruction::DeferCallCore(f) => Instruction::DeferCallCore(func(f)),
+        Instruction::CallAdapter(f) => Instruction::CallAdapter(funcs[&f]),
+        Instruction::MemoryToString(m) => Instruction::MemoryToString(memory(m)),
+        Instruction::StringToMemory { mem, malloc } => Instruction::StringToMemory {
+            mem: memory(mem),
+            malloc: func(malloc),
+        },
+        ref other => other.clone(),
+    }
+}
+
+fn core_func(map: &CoreIdMap, f: FunctionId) -> Result<FunctionId> {
+    map.get_func(f)
+        .ok_or_else(|| anyhow!("core function {:?} not found in mapping", f))
+}
+
+fn core_memory(map: &CoreIdMap, m: MemoryId) -> Result<MemoryId> {
+    map.get_memory(m)
+        .ok_or_else(|| anyhow!("core memory {:?} not found in mapping", m))
+}
+
+/// Copies the `WasmInterfaceTypes` custom section of `src`, if present, into
+/// `dst`.
+///
+/// If `dst` doesn't already have a `WasmInterfaceTypes` custom section then one
+/// is created. See [`WasmInterfaceTypes::merge`] for more information about
+/// `map` and how adapters are copied.
+pub fn merge(dst: &mut Module, src: &Module, map: &CoreIdMap) -> Result<()> {
+    let src = match src.customs.get_typed::<WasmInterfaceTypes>() {
+        Some(s) => s,
+        None => return Ok(()),
+    };
+    if let Some(section) = dst.customs.get_typed_mut::<WasmInterfaceTypes>() {
+        return section.merge(src, map);
+    }
+    let mut section = WasmInterfaceTypes::default();
+    section.merge(src, map)?;
+    dst.customs.add(section);
+    Ok(())
+}
@@ -0,0 +1,114 @@
+use crate::{FuncKind, Instruction, WasmInterfaceTypes};
+use std::collections::HashMap;
+use walrus::{FunctionId, MemoryId, Module};
+
+/// A mapping from core wasm function and memory ids to their replacements.
+///
+/// This is used to update the references that adapters make into the core
+/// module, for example when a pass replaces a core function with a wrapper and
+/// all adapters which called the original function should now call the
+/// wrapper instead.
+#[derive(Debug, Default, Clone)]
+pub struct CoreIdMap {
+    funcs: HashMap<FunctionId, FunctionId>,
+    memories: HashMap<MemoryId, MemoryId>,
+}
+
+impl CoreIdMap {
+    /// Creates a new empty mapping.
+    pub fn new() -> CoreIdMap {
+        CoreIdMap::default()
+    }
+
+    /// Registers that references to the core function `from` should be
+    /// replaced with references to `to`.
+    pub fn insert_func(&mut self, from: FunctionId, to: FunctionId) {
+        self.funcs.insert(from, to);
+    }
+
+    /// Registers that references to the core memory `from` should be replaced
+    /// with references to `to`.
+    pub fn insert_memory(&mut self, from: MemoryId, to: MemoryId) {
+        self.memories.insert(from, to);
+    }
+
+    /// Returns the replacement registered for the core function `id`, if any.
+    pub fn get_func(&self, id: FunctionId) -> Option<FunctionId> {
+        self.funcs.get(&id).cloned()
+    }
+
+    /// Returns the replacement registered for the core memory `id`, if any.
+    pub fn get_memory(&self, id: MemoryId) -> Option<MemoryId> {
+        self.memories.get(&id).cloned()
+    }
+
+    /// Returns whether no replacements have been registered.
+    pub fn is_empty(&self) -> bool {
+        self.funcs.is_empty() && self.memories.is_empty()
+    }
+
+    fn remap_func(&self, id: &mut FunctionId) {
+        if let Some(new) = self.get_func(*id) {
+            *id = new;
+        }
+    }
+
+    fn remap_memory(&self, id: &mut MemoryId) {
+        if let Some(new) = self.get_memory(*id) {
+            *id = new;
+        }
+    }
+}
+
+impl WasmInterfaceTypes {
+    /// Rewrites all references to core wasm functions and memories in this
+    /// section according to `map`.
+    ///
+    /// This updates the operands of `call-core`, `defer-call-core`,
+    /// `memory-to-string` and `string-to-memory` instructions as well as the
+    /// core function of each `implement` directive. Ids which aren't listed in
+    /// `map` are left as-is.
+    ///
+    /// Note that `implement` directives must always refer to imported core
+    /// functions, so a replacement for an implemented function should itself
+    /// be an import.
+    pub fn remap_core_ids(&mut self, map: &CoreIdMap) {
+        if map.is_empty() {
+            return;
+        }
+        for f in self.funcs.iter_mut() {
+            let instrs = match &mut f.kind {
+                FuncKind::Local(instrs) => instrs,
+                FuncKind::Import(_) => continue,
+            };
+            for instr in instrs.iter_mut() {
+                match instr {
+                    Instruction::CallCore(f) | Instruction::DeferCallCore(f) => {
+                        map.remap_func(f);
+                    }
+                    Instruction::MemoryToString(mem) => map.remap_memory(mem),
+                    Instruction::StringToMemory { mem, malloc } => {
+                        map.remap_memory(mem);
+                        map.remap_func(malloc);
+                    }
+                    _ => {}
+                }
+            }
+        }
+        for i in self.implements.iter_mut() {
+            map.remap_func(&mut i.core_func);
+        }
+    }
+}
+
+/// Rewrites the `WasmInterfaceTypes` custom section of `module`, if present,
+/// according to `map`.
+///
+/// This is intended to be called by `walrus` passes after they've replaced
+/// core functions or memories, keeping the adapters in sync with the core
+/// module. See [`WasmInterfaceTypes::remap_core_ids`] for more information.
+pub fn remap_core_ids(module: &mut Module, map: &CoreIdMap) {
+    if let Some(section) = module.customs.get_typed_mut::<WasmInterfaceTypes>() {
+        section.remap_core_ids(map);
+    }
+}
@@ -0,0 +1,186 @@
+use crate::{CoreIdsToIndices, FuncId, Instruction, ValType, WasmInterfaceTypes};
+use anyhow::{anyhow, bail, Result};
+use walrus::{ExportItem, FunctionId, MemoryId, Module};
+
+/// A signature-only description of an adapter export, used with
+/// [`WasmInterfaceTypes::synthesize_export`] to generate the adapter's body.
+#[derive(Debug, Clone)]
+pub struct AdapterSignature {
+    /// The name the adapter is exported under.
+    pub name: String,
+    /// The interface types of the adapter's parameters.
+    pub params: Vec<ValType>,
+    /// The interface types of the adapter's results.
+    pub results: Vec<ValType>,
+}
+
+impl WasmInterfaceTypes {
+    /// Adds an adapter export described by `sig` which calls the core
+    /// function `core_func`, returning the id of the new adapter function.
+    ///
+    /// The body of the adapter is synthesized following a fixed canonical ABI
+    /// for how interface values are passed to and from core wasm:
+    ///
+    /// * `s8`, `u8`, `s16`, `u16`, `s32` and `u32` are passed as an `i32`.
+    /// * `s64` and `u64` are passed as an `i64`.
+    /// * `string` parameters are copied into the module's first memory with
+    ///   `string-to-memory`, using the core function named `malloc` as the
+    ///   allocator, and are passed as an `i32` pointer and an `i32` length.
+    /// * A `string` result is returned as an `i32` pointer and an `i32` length
+    ///   into the module's first memory.
+    /// * `i32`, `i64`, `f32`, `f64` and `externref` are passed as-is.
+    ///
+    /// The `malloc` function is looked up by its name in the name section of
+    /// `module`, or otherwise by its export name.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `core_func`'s signature doesn't match the lowered
+    /// signature of `sig`, if the allocator or memory needed for strings can't
+    /// be found, if `sig` has more than one result, or if `sig.name` is
+    /// already exported. No changes are made to this section in that case.
+    pub fn synthesize_export(
+        &mut self,
+        module: &Module,
+        sig: &AdapterSignature,
+        core_func: FunctionId,
+        malloc: &str,
+    ) -> Result<FuncId> {
+        if self.exports.iter().any(|e| e.name == sig.name) {
+            bail!("adapter export `{}` is already defined", sig.name);
+        }
+        if sig.results.len() > 1 {
+            bail!("synthesized adapters can have at most one result");
+        }
+
+        let mut instrs = Vec::new();
+        let mut core_params = Vec::new();
+        for (i, param) in sig.params.iter().enumerate() {
+            instrs.push(Instruction::ArgGet(i as u32));
+            match lower(*param) {
+                Lowered::Int(output) => {
+                    instrs.push(Instruction::IntToWasm {
+                        input: *param,
+                        output,
+                        trap: false,
+                    });
+                    core_params.push(output);
+                }
+                Lowered::Same(ty) => core_params.push(ty),
+                Lowered::String => {
+                    instrs.push(Instruction::StringToMemory {
+                        mem: memory(module)?,
+                        malloc: allocator(module, malloc)?,
+                    });
+                    core_params.push(walrus::ValType::I32);
+                    core_params.push(walrus::ValType::I32);
+                }
+            }
+        }
+        instrs.push(Instruction::CallCore(core_func));
+
+        let mut core_results = Vec::new();
+        if let Some(result) = sig.results.first() {
+            match lower(*result) {
+                Lowered::Int(input) => {
+                    instrs.push(Instruction::WasmToInt {
+                        input,
+                        output: *result,
+                        trap: false,
+                    });
+                    core_results.push(input);
+                }
+                Lowered::Same(ty) => core_results.push(ty),
+                Lowered::String => {
+                    instrs.push(Instruction::MemoryToString(memory(module)?));
+                    core_results.push(walrus::ValType::I32);
+                    core_results.push(walrus::ValType::I32);
+                }
+            }
+        }
+
+        let ty = module.types.get(module.funcs.get(core_func).ty());
+        if ty.params() != &core_params[..] || ty.results() != &core_results[..] {
+            bail!(
+                "core function has signature {:?} -> {:?}, but the canonical ABI \
+                 for adapter export `{}` expects {:?} -> {:?}",
+                ty.params(),
+                ty.results(),
+                sig.name,
+                core_params,
+                core_results,
+            );
+        }
+
+        let existing = self
+            .types
+            .iter()
+            .find(|t| t.params() == &sig.params[..] && t.results() == &sig.results[..])
+            .map(|t| t.id());
+        let ty = match existing {
+            Some(id) => id,
+            None => self.types.add(sig.params.clone(), sig.results.clone()),
+        };
+        let func = self.funcs.add_local(ty, instrs);
+        self.exports.add(&sig.name, func);
+        Ok(func)
+    }
+}
+
+/// How an interface type is represented in core wasm in the canonical ABI.
+enum Lowered {
+    /// An integer converted with `*-to-i32` or `*-to-i64` style instructions.
+    Int(walrus::ValType),
+    /// A type which is the same in core wasm and doesn't need conversion.
+    Same(walrus::ValType),
+    /// A string passed as a pointer/length pair.
+    String,
+}
+
+fn lower(ty: ValType) -> Lowered {
+    match ty {
+        ValType::S8 | ValType::U8 | ValType::S16 | ValType::U16 | ValType::S32 | ValType::U32 => {
+            Lowered::Int(walrus::ValType::I32)
+        }
+        ValType::S64 | ValType::U64 => Lowered::Int(walrus::ValType::I64),
+        ValType::I32 => Lowered::Same(walrus::ValType::I32),
+        ValType::I64 => Lowered::Same(walrus::ValType::I64),
+        ValType::F32 => Lowered::Same(walrus::ValType::F32),
+        ValType::F64 => Lowered::Same(walrus::ValType::F64),
+        ValType::Externref => Lowered::Same(walrus::ValType::Externref),
+        ValType::String => Lowered::String,
+    }
+}
+
+/// Returns the memory at index 0, which is where strings live in the canonical
+/// ABI.
+fn memory(module: &Module) -> Result<MemoryId> {
+    let indices = CoreIdsToIndices::new(module);
+    module
+        .memories
+        .iter()
+        .map(|m| m.id())
+        .find(|id| indices.memory(*id) == Some(0))
+        .ok_or_else(|| anyhow!("strings require a memory but the module has none"))
+}
+
+fn allocator(module: &Module, name: &str) -> Result<FunctionId> {
+    let id = module
+        .funcs
+        .by_name(name)
+        .or_else(|| {
+            module.exports.iter().find_map(|e| match e.item {
+                ExportItem::Function(f) if e.name == name => Some(f),
+                _ => None,
+            })
+        })
+        .ok_or_else(|| anyhow!("failed to find allocator function `{}`", name))?;
+    let ty = module.types.get(module.funcs.get(id).ty());
+    if ty.params() != [walrus::ValType::I32] || ty.results() != [walrus::ValType::I32] {
+        bail!(
+            "allocator function `{}` does not have correct signature",
+            name
+        );
+    }
+    Ok(id)
+}
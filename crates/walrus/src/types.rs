@@ -16,7 +16,7 @@ pub struct Type {
 
 pub type TypeId = Id<Type>;
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum ValType {
     S8,
     S16,
@@ -58,37 +58,18 @@ impl WasmInterfaceTypes {
                 ty.params.len() as u32,
                 |w| {
                     for param in ty.params.iter() {
-                        write_ty(w, param);
+                        w.val_type(&walrus2parse(*param));
                     }
                 },
                 ty.results.len() as u32,
                 |w| {
                     for result in ty.results.iter() {
-                        write_ty(w, result);
+                        w.val_type(&walrus2parse(*result));
                     }
                 },
             );
             wids.push_ty(id);
         }
-
-        fn write_ty(w: &mut wit_writer::Type<'_>, ty: &ValType) {
-            match ty {
-                ValType::S8 => w.s8(),
-                ValType::S16 => w.s16(),
-                ValType::S32 => w.s32(),
-                ValType::S64 => w.s64(),
-                ValType::U8 => w.u8(),
-                ValType::U16 => w.u16(),
-                ValType::U32 => w.u32(),
-                ValType::U64 => w.u64(),
-                ValType::F32 => w.f32(),
-                ValType::F64 => w.f64(),
-                ValType::String => w.string(),
-                ValType::Externref => w.externref(),
-                ValType::I32 => w.i32(),
-                ValType::I64 => w.i64(),
-            }
-        }
     }
 }
 
@@ -111,6 +92,25 @@ fn parse2walrus(parse: wit_parser::ValType) -> ValType {
     }
 }
 
+pub(crate) fn walrus2parse(ty: ValType) -> wit_parser::ValType {
+    match ty {
+        ValType::S8 => wit_parser::ValType::S8,
+        ValType::S16 => wit_parser::ValType::S16,
+        ValType::S32 => wit_parser::ValType::S32,
+        ValType::S64 => wit_parser::ValType::S64,
+        ValType::U8 => wit_parser::ValType::U8,
+        ValType::U16 => wit_parser::ValType::U16,
+        ValType::U32 => wit_parser::ValType::U32,
+        ValType::U64 => wit_parser::ValType::U64,
+        ValType::F32 => wit_parser::ValType::F32,
+        ValType::F64 => wit_parser::ValType::F64,
+        ValType::String => wit_parser::ValType::String,
+        ValType::Externref => wit_parser::ValType::Externref,
+        ValType::I32 => wit_parser::ValType::I32,
+        ValType::I64 => wit_parser::ValType::I64,
+    }
+}
+
 impl Types {
     /// Gets a reference to an type given its id
     pub fn get(&self, id: TypeId) -> &Type {
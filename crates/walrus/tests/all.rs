@@ -0,0 +1,393 @@
+use anyhow::Result;
+use wit_walrus::WasmInterfaceTypes;
+
+fn parse(wat: &str) -> Result<walrus::Module> {
+    let wasm = wit_text::parse_str(wat)?;
+    walrus::ModuleConfig::new()
+        .on_parse(wit_walrus::on_parse)
+        .parse(&wasm)
+}
+
+fn print(module: &mut walrus::Module) -> Result<String> {
+    let wasm = module.emit_wasm();
+    wit_validator::validate(&wasm)?;
+    wit_printer::print_bytes(&wasm)
+}
+
+#[test]
+fn remap_core_ids() -> Result<()> {
+    let mut module = parse(
+        r#"
+            (module
+                (import "" "f" (func $f (param i32 i32)))
+                (func $g (param i32 i32))
+                (func $malloc (param i32) (result i32) i32.const 0)
+                (memory $m1 1)
+                (@interface func (export "a") (param s32)
+                    arg.get 0
+                    s32-to-i32
+                    arg.get 0
+                    s32-to-i32
+                    call-core $f)
+                (@interface func (export "b") (param string)
+                    arg.get 0
+                    string-to-memory $malloc
+                    defer-call-core $f
+                    call-core $f)
+            )
+        "#,
+    )?;
+    let f = module.funcs.by_name("f").unwrap();
+    let g = module.funcs.by_name("g").unwrap();
+
+    let mut map = wit_walrus::CoreIdMap::new();
+    map.insert_func(f, g);
+    wit_walrus::remap_core_ids(&mut module, &map);
+
+    let wit = module.customs.get_typed::<WasmInterfaceTypes>().unwrap();
+    for func in wit.funcs.iter() {
+        if let wit_walrus::FuncKind::Local(instrs) = &func.kind {
+            for instr in instrs {
+                match instr {
+                    wit_walrus::Instruction::CallCore(id)
+                    | wit_walrus::Instruction::DeferCallCore(id) => assert_eq!(*id, g),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    let text = print(&mut module)?;
+    assert!(!text.contains("call-core $f"), "{}", text);
+    assert!(text.contains("defer-call-core $g"), "{}", text);
+    Ok(())
+}
+
+#[test]
+fn merge() -> Result<()> {
+    let mut dst = parse(
+        r#"
+            (module
+                (func $f (param i32))
+                (func $g (param i32))
+                (@interface func (export "a") (param s32)
+                    arg.get 0
+                    s32-to-i32
+                    call-core $f)
+            )
+        "#,
+    )?;
+    let src = parse(
+        r#"
+            (module
+                (func $g (param i32))
+                (@interface func $adapter (export "a") (param s32)
+                    arg.get 0
+                    s32-to-i32
+                    call-core $g)
+                (@interface func (export "b") (param s32)
+                    arg.get 0
+                    call-adapter $adapter)
+            )
+        "#,
+    )?;
+
+    let mut map = wit_walrus::CoreIdMap::new();
+    map.insert_func(
+        src.funcs.by_name("g").unwrap(),
+        dst.funcs.by_name("g").unwrap(),
+    );
+    wit_walrus::merge(&mut dst, &src, &map)?;
+
+    let wit = dst.customs.get_typed::<WasmInterfaceTypes>().unwrap();
+    assert_eq!(wit.types.iter().count(), 1);
+    assert_eq!(wit.funcs.iter().count(), 3);
+    let names = wit.exports.iter().map(|e| &e.name[..]).collect::<Vec<_>>();
+    assert_eq!(names, ["a", "a1", "b"]);
+
+    let text = print(&mut dst)?;
+    assert!(text.contains("call-core $g"), "{}", text);
+    assert!(text.contains("call-adapter 1"), "{}", text);
+
+    // Core functions which aren't mapped are an error
+    let mut dst = parse("(module)")?;
+    assert!(wit_walrus::merge(&mut dst, &src, &Default::default()).is_err());
+    assert!(dst.customs.get_typed::<WasmInterfaceTypes>().is_none());
+    Ok(())
+}
+
+#[test]
+fn display() -> Result<()> {
+    let module = parse(
+        r#"
+            (module
+                (import "" "f" (func $f (param i32)))
+                (func (param i32))
+                (func $malloc (param i32) (result i32) i32.const 0)
+                (memory 1)
+                (@interface func (import "m" "n") (param string))
+                (@interface func $g (export "g") (param string)
+                    arg.get 0
+                    string-to-memory $malloc
+                    call-core 1
+                    arg.get 0
+                    call-adapter 0)
+                (@interface implement (func $f) (param s8)
+                    arg.get 0
+                    s8-to-i32
+                    i32-to-s8x
+                    s8-to-i32
+                    call-core $f)
+            )
+        "#,
+    )?;
+    let wit = module.customs.get_typed::<WasmInterfaceTypes>().unwrap();
+
+    // Note that unnamed core functions are printed with the index they'll have
+    // once emitted, and `walrus` emits larger functions first.
+    assert_eq!(
+        wit.display(&module).to_string(),
+        r#"(@interface type (;0;) (func (param string)))
+(@interface type (;1;) (func (param s8)))
+(@interface import "m" "n" (func (;0;) (type 0)))
+(@interface func (;1;) (type 0)
+  arg.get 0
+  string-to-memory $malloc
+  call-core 2
+  arg.get 0
+  call-adapter 0)
+(@interface func (;2;) (type 1)
+  arg.get 0
+  s8-to-i32
+  i32-to-s8x
+  s8-to-i32
+  call-core $f)
+(@interface export "g" (func 1))
+(@interface implement (func $f) (func 2))"#
+    );
+    Ok(())
+}
+
+#[test]
+fn display_escapes_names() -> Result<()> {
+    let mut module = parse(
+        r#"
+            (module
+                (func $a)
+                (func $b)
+                (func $c)
+                (@interface func (import "m\"" "\\n") (param string))
+                (@interface func (export "\c3\a9\0a")
+                    call-core $a
+                    call-core $b
+                    call-core $c)
+            )
+        "#,
+    )?;
+    // Names from the name section which aren't valid identifiers, or which
+    // are shared by multiple functions, are printed as indices.
+    let ids = module.funcs.iter().map(|f| f.id()).collect::<Vec<_>>();
+    module.funcs.get_mut(ids[0]).name = Some("has space".to_string());
+    module.funcs.get_mut(ids[1]).name = Some("dup".to_string());
+    module.funcs.get_mut(ids[2]).name = Some("dup".to_string());
+    let wit = module.customs.get_typed::<WasmInterfaceTypes>().unwrap();
+
+    assert_eq!(
+        wit.display(&module).to_string(),
+        r#"(@interface type (;0;) (func (param string)))
+(@interface type (;1;) (func))
+(@interface import "m\"" "\\n" (func (;0;) (type 0)))
+(@interface func (;1;) (type 1)
+  call-core 0
+  call-core 1
+  call-core 2)
+(@interface export "\c3\a9\0a" (func 1))"#
+    );
+
+    // The printer agrees on both the escaping and which names to use.
+    let display = wit.display(&module).to_string();
+    let printed = print(&mut module)?;
+    for line in display.lines() {
+        assert!(printed.contains(line), "{}\n\n{}", line, printed);
+    }
+    Ok(())
+}
+
+#[test]
+fn encode_matches_emit() -> Result<()> {
+    let mut module = parse(
+        r#"
+            (module
+                (import "" "f" (func $f (param i32)))
+                (func (param i32))
+                (func $malloc (param i32) (result i32) i32.const 0)
+                (memory 1)
+                (@interface func (export "g") (param string)
+                    arg.get 0
+                    string-to-memory $malloc
+                    call-core 1
+                    arg.get 0
+                    string-to-memory $malloc
+                    defer-call-core 2
+                    call-core 2)
+                (@interface implement (func $f) (param s8)
+                    arg.get 0
+                    s8-to-i32
+                    call-core $f)
+            )
+        "#,
+    )?;
+    let wit = module.customs.get_typed::<WasmInterfaceTypes>().unwrap();
+    let payload = wit.encode(&module);
+
+    let emitted = walrus::Module::from_buffer(&module.emit_wasm())?
+        .customs
+        .remove_raw("wasm-interface-types")
+        .unwrap();
+    assert_eq!(payload, emitted.data);
+    Ok(())
+}
+
+#[test]
+fn synthesize_export() -> Result<()> {
+    use wit_walrus::{AdapterSignature, ValType};
+
+    let mut module = parse(
+        r#"
+            (module
+                (func $greet (param i32 i32 i32) (result i32 i32)
+                    local.get 0
+                    local.get 1)
+                (func $add (param i64 i32) (result i32)
+                    local.get 1)
+                (func $malloc (param i32) (result i32) i32.const 0)
+                (memory 1)
+            )
+        "#,
+    )?;
+    let greet = module.funcs.by_name("greet").unwrap();
+    let add = module.funcs.by_name("add").unwrap();
+    let mut wit = WasmInterfaceTypes::default();
+
+    let sig = AdapterSignature {
+        name: "greet".to_string(),
+        params: vec![ValType::String, ValType::U8],
+        results: vec![ValType::String],
+    };
+    wit.synthesize_export(&module, &sig, greet, "malloc")?;
+
+    // A mismatched core signature, a duplicate name and a missing allocator
+    // are all errors.
+    assert!(wit.synthesize_export(&module, &sig, add, "malloc").is_err());
+    let sig = AdapterSignature {
+        name: "add".to_string(),
+        params: vec![ValType::String],
+        results: vec![ValType::S32],
+    };
+    assert!(wit.synthesize_export(&module, &sig, add, "nope").is_err());
+
+    let sig = AdapterSignature {
+        name: "add".to_string(),
+        params: vec![ValType::U64, ValType::S16],
+        results: vec![ValType::S32],
+    };
+    wit.synthesize_export(&module, &sig, add, "malloc")?;
+    assert_eq!(wit.funcs.iter().count(), 2);
+    assert_eq!(wit.exports.iter().count(), 2);
+
+    module.customs.add(wit);
+    let text = print(&mut module)?;
+    assert!(
+        text.contains(
+            "(@interface func (;0;) (type 0)\n    \
+             arg.get 0\n    \
+             string-to-memory $malloc\n    \
+             arg.get 1\n    \
+             u8-to-i32\n    \
+             call-core $greet\n    \
+             memory-to-string)"
+        ),
+        "{}",
+        text
+    );
+    assert!(
+        text.contains(
+            "(@interface func (;1;) (type 1)\n    \
+             arg.get 0\n    \
+             u64-to-i64\n    \
+             arg.get 1\n    \
+             s16-to-i32\n    \
+             call-core $add\n    \
+             i32-to-s32)"
+        ),
+        "{}",
+        text
+    );
+    Ok(())
+}
+
+#[test]
+fn param_names() -> Result<()> {
+    let mut module = parse(
+        r#"
+            (module
+                (@interface func (import "" "f") (param $msg string))
+                (@interface func (export "g") (param $a s32) (param s32) (param $c s32))
+            )
+        "#,
+    )?;
+    let wit = module.customs.get_typed::<WasmInterfaceTypes>().unwrap();
+    let names = wit
+        .funcs
+        .iter()
+        .map(|f| f.param_names.values().cloned().collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    assert_eq!(names, [vec!["msg"], vec!["a", "c"]]);
+
+    let text = print(&mut module)?;
+    assert!(
+        text.contains("(func (;0;) (type 0) (param $msg string))"),
+        "{}",
+        text
+    );
+    assert!(
+        text.contains("(type 1) (param $a s32) (param s32) (param $c s32))"),
+        "{}",
+        text
+    );
+    Ok(())
+}
+
+#[test]
+fn preserve_unknown() -> Result<()> {
+    let mut module = parse(
+        r#"
+            (module
+                (@interface func (export "f") (param s32) (result s32)
+                    arg.get 0
+                    extension 7 "\01\02")
+            )
+        "#,
+    )?;
+    let wit = module
+        .customs
+        .get_typed_mut::<WasmInterfaceTypes>()
+        .unwrap();
+    wit.unknown.push((0x7f, vec![1, 2, 3]));
+
+    let wasm = module.emit_wasm();
+    let text = wit_printer::print_bytes(&wasm)?;
+    assert!(text.contains("extension 7 \"\\01\\02\""), "{}", text);
+    assert!(
+        text.contains("(; unknown subsection 127: 3 bytes ;)"),
+        "{}",
+        text
+    );
+
+    let module = walrus::ModuleConfig::new()
+        .on_parse(wit_walrus::on_parse)
+        .parse(&wasm)?;
+    let wit = module.customs.get_typed::<WasmInterfaceTypes>().unwrap();
+    assert_eq!(wit.unknown, [(0x7f, vec![1, 2, 3])]);
+    Ok(())
+}
@@ -3,6 +3,7 @@
 #![deny(missing_docs)]
 
 use std::mem;
+use wit_parser::{Instruction, StringToMemory, ValType};
 
 /// A structure used to write out the raw representation of a wasm interface
 /// types subsection.
@@ -23,7 +24,7 @@ impl Writer {
             tmp: Vec::new(),
         };
         wit_schema_version::VERSION.encode(&mut w.dst);
-        return w;
+        w
     }
 
     /// Returns a section writer used to write out the type subsection of a
@@ -71,6 +72,25 @@ impl Writer {
         }
     }
 
+    /// Returns a section writer used to write out the parameter names
+    /// subsection of a wasm interface types section.
+    pub fn param_names(&mut self, cnt: u32) -> ParamNames<'_> {
+        ParamNames {
+            tmp: self.start_section(cnt),
+            dst: self,
+        }
+    }
+
+    /// Writes a subsection with the given `id` and raw contents.
+    ///
+    /// This can be used to preserve subsections that aren't otherwise known,
+    /// such as experimental extensions. Note that subsections are expected to
+    /// be written in order of their ids.
+    pub fn raw_section(&mut self, id: u8, bytes: &[u8]) {
+        self.dst.push(id);
+        bytes.encode(&mut self.dst);
+    }
+
     /// Consumes this writer, returning all bytes written so far.
     ///
     /// This will only return the payload of the wasm interface types custom
@@ -87,11 +107,11 @@ impl Writer {
         let mut tmp2 = self.pop_tmp();
         tmp2.push(0);
         tmp.encode(&mut tmp2);
-        return tmp2;
+        tmp2
     }
 
     fn pop_tmp(&mut self) -> Vec<u8> {
-        self.tmp.pop().unwrap_or(Vec::new())
+        self.tmp.pop().unwrap_or_default()
     }
 
     fn push_tmp(&mut self, mut tmp: Vec<u8>) {
@@ -108,7 +128,7 @@ impl Writer {
     }
 
     fn finish_section(&mut self, id: u8, data: Vec<u8>) {
-        if data.len() > 0 {
+        if !data.is_empty() {
             self.dst.push(id);
             data.encode(&mut self.dst);
         }
@@ -116,6 +136,12 @@ impl Writer {
     }
 }
 
+impl Default for Writer {
+    fn default() -> Writer {
+        Writer::new()
+    }
+}
+
 /// Writer for the list of types in a type subsection.
 pub struct Types<'a> {
     dst: &'a mut Writer,
@@ -156,8 +182,7 @@ impl Types<'_> {
 
 impl Drop for Types<'_> {
     fn drop(&mut self) {
-        self.dst
-            .finish_section(0x00, mem::replace(&mut self.tmp, Vec::new()));
+        self.dst.finish_section(0x00, mem::take(&mut self.tmp));
     }
 }
 
@@ -191,6 +216,28 @@ impl Type<'_> {
     pub fn i64(&mut self) { self.ty(0x0d) }
 }
 
+impl Type<'_> {
+    /// Adds the value type `ty`, as read by `wit_parser`.
+    pub fn val_type(&mut self, ty: &ValType) {
+        match ty {
+            ValType::S8 => self.s8(),
+            ValType::S16 => self.s16(),
+            ValType::S32 => self.s32(),
+            ValType::S64 => self.s64(),
+            ValType::U8 => self.u8(),
+            ValType::U16 => self.u16(),
+            ValType::U32 => self.u32(),
+            ValType::U64 => self.u64(),
+            ValType::F32 => self.f32(),
+            ValType::F64 => self.f64(),
+            ValType::String => self.string(),
+            ValType::Externref => self.externref(),
+            ValType::I32 => self.i32(),
+            ValType::I64 => self.i64(),
+        }
+    }
+}
+
 /// Writer for the list of imports in an import subsection.
 pub struct Imports<'a> {
     dst: &'a mut Writer,
@@ -208,8 +255,7 @@ impl Imports<'_> {
 
 impl Drop for Imports<'_> {
     fn drop(&mut self) {
-        self.dst
-            .finish_section(0x01, mem::replace(&mut self.tmp, Vec::new()));
+        self.dst.finish_section(0x01, mem::take(&mut self.tmp));
     }
 }
 
@@ -233,8 +279,7 @@ impl<'a> Funcs<'a> {
 
 impl Drop for Funcs<'_> {
     fn drop(&mut self) {
-        self.dst
-            .finish_section(0x02, mem::replace(&mut self.tmp, Vec::new()));
+        self.dst.finish_section(0x02, mem::take(&mut self.tmp));
     }
 }
 
@@ -244,88 +289,103 @@ pub struct Instructions<'a, 'b> {
     funcs: &'b mut Funcs<'a>,
 }
 
-#[allow(missing_docs)]
-#[rustfmt::skip]
 impl Instructions<'_, '_> {
-    pub fn arg_get(&mut self, arg: u32) {
-        self.tmp.push(0x00);
-        arg.encode(&mut self.tmp);
-    }
-
-    pub fn call_core(&mut self, func: u32) {
-        self.tmp.push(0x01);
-        func.encode(&mut self.tmp);
-    }
-
-    pub fn memory_to_string(&mut self, mem: u32) {
-        self.tmp.push(0x03);
-        mem.encode(&mut self.tmp);
+    /// Appends `instr` to this function.
+    ///
+    /// This can be used to copy instructions straight out of a parsed
+    /// function. Functions are terminated automatically, so an `End`
+    /// instruction is ignored.
+    pub fn instr(&mut self, instr: &Instruction) {
+        use Instruction::*;
+
+        if let End = instr {
+            return;
+        }
+        self.tmp.push(instr.opcode());
+        match instr {
+            ArgGet(idx) | CallCore(idx) | MemoryToString(idx) | CallAdapter(idx)
+            | DeferCallCore(idx) => idx.encode(&mut self.tmp),
+            StringToMemory(args) => {
+                args.malloc.encode(&mut self.tmp);
+                args.mem.encode(&mut self.tmp);
+            }
+            Extension(ext) => {
+                ext.opcode.encode(&mut self.tmp);
+                ext.immediates.encode(&mut self.tmp);
+            }
+
+            // Everything else has no immediates. These are listed explicitly
+            // so a new instruction fails to compile here until it's handled.
+            End | I32ToS8 | I32ToS8X | I32ToU8 | I32ToS16 | I32ToS16X | I32ToU16 | I32ToS32
+            | I32ToU32 | I32ToS64 | I32ToU64 | I64ToS8 | I64ToS8X | I64ToU8 | I64ToS16
+            | I64ToS16X | I64ToU16 | I64ToS32 | I64ToS32X | I64ToU32 | I64ToS64 | I64ToU64
+            | S8ToI32 | U8ToI32 | S16ToI32 | U16ToI32 | S32ToI32 | U32ToI32 | S64ToI32
+            | S64ToI32X | U64ToI32 | U64ToI32X | S8ToI64 | U8ToI64 | S16ToI64 | U16ToI64
+            | S32ToI64 | U32ToI64 | S64ToI64 | U64ToI64 => {}
+        }
     }
+}
 
+#[allow(missing_docs)]
+#[rustfmt::skip]
+impl Instructions<'_, '_> {
+    pub fn arg_get(&mut self, arg: u32) { self.instr(&Instruction::ArgGet(arg)) }
+    pub fn call_core(&mut self, func: u32) { self.instr(&Instruction::CallCore(func)) }
+    pub fn memory_to_string(&mut self, mem: u32) { self.instr(&Instruction::MemoryToString(mem)) }
     pub fn string_to_memory(&mut self, malloc: u32, mem: u32) {
-        self.tmp.push(0x04);
-        malloc.encode(&mut self.tmp);
-        mem.encode(&mut self.tmp);
-    }
-
-    pub fn call_adapter(&mut self, func: u32) {
-        self.tmp.push(0x05);
-        func.encode(&mut self.tmp);
-    }
-
-    pub fn defer_call_core(&mut self, func: u32) {
-        self.tmp.push(0x06);
-        func.encode(&mut self.tmp);
-    }
-
-    pub fn i32_to_s8(&mut self) { self.tmp.push(0x07) }
-    pub fn i32_to_s8x(&mut self) { self.tmp.push(0x08) }
-    pub fn i32_to_u8(&mut self) { self.tmp.push(0x09) }
-    pub fn i32_to_s16(&mut self) { self.tmp.push(0x0a) }
-    pub fn i32_to_s16x(&mut self) { self.tmp.push(0x0b) }
-    pub fn i32_to_u16(&mut self) { self.tmp.push(0x0c) }
-    pub fn i32_to_s32(&mut self) { self.tmp.push(0x0d) }
-    pub fn i32_to_u32(&mut self) { self.tmp.push(0x0e) }
-    pub fn i32_to_s64(&mut self) { self.tmp.push(0x0f) }
-    pub fn i32_to_u64(&mut self) { self.tmp.push(0x10) }
-
-    pub fn i64_to_s8(&mut self) { self.tmp.push(0x11) }
-    pub fn i64_to_s8x(&mut self) { self.tmp.push(0x12) }
-    pub fn i64_to_u8(&mut self) { self.tmp.push(0x13) }
-    pub fn i64_to_s16(&mut self) { self.tmp.push(0x14) }
-    pub fn i64_to_s16x(&mut self) { self.tmp.push(0x15) }
-    pub fn i64_to_u16(&mut self) { self.tmp.push(0x16) }
-    pub fn i64_to_s32(&mut self) { self.tmp.push(0x17) }
-    pub fn i64_to_s32x(&mut self) { self.tmp.push(0x18) }
-    pub fn i64_to_u32(&mut self) { self.tmp.push(0x19) }
-    pub fn i64_to_s64(&mut self) { self.tmp.push(0x1a) }
-    pub fn i64_to_u64(&mut self) { self.tmp.push(0x1b) }
-
-    pub fn s8_to_i32(&mut self) { self.tmp.push(0x1c) }
-    pub fn u8_to_i32(&mut self) { self.tmp.push(0x1d) }
-    pub fn s16_to_i32(&mut self) { self.tmp.push(0x1e) }
-    pub fn u16_to_i32(&mut self) { self.tmp.push(0x1f) }
-    pub fn s32_to_i32(&mut self) { self.tmp.push(0x20) }
-    pub fn u32_to_i32(&mut self) { self.tmp.push(0x21) }
-    pub fn s64_to_i32(&mut self) { self.tmp.push(0x22) }
-    pub fn s64_to_i32x(&mut self) { self.tmp.push(0x23) }
-    pub fn u64_to_i32(&mut self) { self.tmp.push(0x24) }
-    pub fn u64_to_i32x(&mut self) { self.tmp.push(0x25) }
-
-    pub fn s8_to_i64(&mut self) { self.tmp.push(0x26) }
-    pub fn u8_to_i64(&mut self) { self.tmp.push(0x27) }
-    pub fn s16_to_i64(&mut self) { self.tmp.push(0x28) }
-    pub fn u16_to_i64(&mut self) { self.tmp.push(0x29) }
-    pub fn s32_to_i64(&mut self) { self.tmp.push(0x2a) }
-    pub fn u32_to_i64(&mut self) { self.tmp.push(0x2b) }
-    pub fn s64_to_i64(&mut self) { self.tmp.push(0x2c) }
-    pub fn u64_to_i64(&mut self) { self.tmp.push(0x2d) }
+        self.instr(&Instruction::StringToMemory(StringToMemory { malloc, mem }))
+    }
+    pub fn call_adapter(&mut self, func: u32) { self.instr(&Instruction::CallAdapter(func)) }
+    pub fn defer_call_core(&mut self, func: u32) { self.instr(&Instruction::DeferCallCore(func)) }
+
+    pub fn i32_to_s8(&mut self) { self.instr(&Instruction::I32ToS8) }
+    pub fn i32_to_s8x(&mut self) { self.instr(&Instruction::I32ToS8X) }
+    pub fn i32_to_u8(&mut self) { self.instr(&Instruction::I32ToU8) }
+    pub fn i32_to_s16(&mut self) { self.instr(&Instruction::I32ToS16) }
+    pub fn i32_to_s16x(&mut self) { self.instr(&Instruction::I32ToS16X) }
+    pub fn i32_to_u16(&mut self) { self.instr(&Instruction::I32ToU16) }
+    pub fn i32_to_s32(&mut self) { self.instr(&Instruction::I32ToS32) }
+    pub fn i32_to_u32(&mut self) { self.instr(&Instruction::I32ToU32) }
+    pub fn i32_to_s64(&mut self) { self.instr(&Instruction::I32ToS64) }
+    pub fn i32_to_u64(&mut self) { self.instr(&Instruction::I32ToU64) }
+
+    pub fn i64_to_s8(&mut self) { self.instr(&Instruction::I64ToS8) }
+    pub fn i64_to_s8x(&mut self) { self.instr(&Instruction::I64ToS8X) }
+    pub fn i64_to_u8(&mut self) { self.instr(&Instruction::I64ToU8) }
+    pub fn i64_to_s16(&mut self) { self.instr(&Instruction::I64ToS16) }
+    pub fn i64_to_s16x(&mut self) { self.instr(&Instruction::I64ToS16X) }
+    pub fn i64_to_u16(&mut self) { self.instr(&Instruction::I64ToU16) }
+    pub fn i64_to_s32(&mut self) { self.instr(&Instruction::I64ToS32) }
+    pub fn i64_to_s32x(&mut self) { self.instr(&Instruction::I64ToS32X) }
+    pub fn i64_to_u32(&mut self) { self.instr(&Instruction::I64ToU32) }
+    pub fn i64_to_s64(&mut self) { self.instr(&Instruction::I64ToS64) }
+    pub fn i64_to_u64(&mut self) { self.instr(&Instruction::I64ToU64) }
+
+    pub fn s8_to_i32(&mut self) { self.instr(&Instruction::S8ToI32) }
+    pub fn u8_to_i32(&mut self) { self.instr(&Instruction::U8ToI32) }
+    pub fn s16_to_i32(&mut self) { self.instr(&Instruction::S16ToI32) }
+    pub fn u16_to_i32(&mut self) { self.instr(&Instruction::U16ToI32) }
+    pub fn s32_to_i32(&mut self) { self.instr(&Instruction::S32ToI32) }
+    pub fn u32_to_i32(&mut self) { self.instr(&Instruction::U32ToI32) }
+    pub fn s64_to_i32(&mut self) { self.instr(&Instruction::S64ToI32) }
+    pub fn s64_to_i32x(&mut self) { self.instr(&Instruction::S64ToI32X) }
+    pub fn u64_to_i32(&mut self) { self.instr(&Instruction::U64ToI32) }
+    pub fn u64_to_i32x(&mut self) { self.instr(&Instruction::U64ToI32X) }
+
+    pub fn s8_to_i64(&mut self) { self.instr(&Instruction::S8ToI64) }
+    pub fn u8_to_i64(&mut self) { self.instr(&Instruction::U8ToI64) }
+    pub fn s16_to_i64(&mut self) { self.instr(&Instruction::S16ToI64) }
+    pub fn u16_to_i64(&mut self) { self.instr(&Instruction::U16ToI64) }
+    pub fn s32_to_i64(&mut self) { self.instr(&Instruction::S32ToI64) }
+    pub fn u32_to_i64(&mut self) { self.instr(&Instruction::U32ToI64) }
+    pub fn s64_to_i64(&mut self) { self.instr(&Instruction::S64ToI64) }
+    pub fn u64_to_i64(&mut self) { self.instr(&Instruction::U64ToI64) }
 }
 
 impl Drop for Instructions<'_, '_> {
     fn drop(&mut self) {
-        self.tmp.push(0x02);
-        let buf = mem::replace(&mut self.tmp, Vec::new());
+        self.tmp.push(Instruction::End.opcode());
+        let buf = mem::take(&mut self.tmp);
         buf.encode(&mut self.funcs.tmp);
         self.funcs.dst.push_tmp(buf);
     }
@@ -347,8 +407,7 @@ impl Exports<'_> {
 
 impl Drop for Exports<'_> {
     fn drop(&mut self) {
-        self.dst
-            .finish_section(0x03, mem::replace(&mut self.tmp, Vec::new()));
+        self.dst.finish_section(0x03, mem::take(&mut self.tmp));
     }
 }
 
@@ -368,8 +427,28 @@ impl Implements<'_> {
 
 impl Drop for Implements<'_> {
     fn drop(&mut self) {
-        self.dst
-            .finish_section(0x04, mem::replace(&mut self.tmp, Vec::new()));
+        self.dst.finish_section(0x04, mem::take(&mut self.tmp));
+    }
+}
+
+/// Writer for the list of names of parameters of adapter functions.
+pub struct ParamNames<'a> {
+    dst: &'a mut Writer,
+    tmp: Vec<u8>,
+}
+
+impl ParamNames<'_> {
+    /// Adds a new name for the `param`th parameter of the adapter `func`.
+    pub fn add(&mut self, func: u32, param: u32, name: &str) {
+        func.encode(&mut self.tmp);
+        param.encode(&mut self.tmp);
+        name.encode(&mut self.tmp);
+    }
+}
+
+impl Drop for ParamNames<'_> {
+    fn drop(&mut self) {
+        self.dst.finish_section(0x05, mem::take(&mut self.tmp));
     }
 }
 
@@ -392,7 +471,7 @@ impl Encode for str {
 
 impl Encode for usize {
     fn encode(&self, e: &mut Vec<u8>) {
-        assert!(*self <= u32::max_value() as usize);
+        assert!(*self <= u32::MAX as usize);
         (*self as u32).encode(e)
     }
 }
@@ -0,0 +1,12 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let wasm = wit_fuzz::module(data);
+    drop(
+        walrus::ModuleConfig::new()
+            .on_parse(wit_walrus::on_parse)
+            .parse(&wasm),
+    );
+});
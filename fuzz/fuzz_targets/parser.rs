@@ -0,0 +1,51 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use wit_parser::{ParserConfig, Result, Section};
+
+fuzz_target!(|data: &[u8]| {
+    let payload = wit_fuzz::payload(data);
+    drop(parse(&payload));
+});
+
+fn parse(payload: &[u8]) -> Result<()> {
+    let mut parser = ParserConfig::new().parser(0, payload)?;
+    while !parser.is_empty() {
+        match parser.section()? {
+            Section::Type(s) => {
+                for ty in s {
+                    ty?;
+                }
+            }
+            Section::Import(s) => {
+                for import in s {
+                    import?;
+                }
+            }
+            Section::Func(s) => {
+                for func in s {
+                    for instr in func?.instrs() {
+                        instr?;
+                    }
+                }
+            }
+            Section::Export(s) => {
+                for export in s {
+                    export?;
+                }
+            }
+            Section::Implement(s) => {
+                for implement in s {
+                    implement?;
+                }
+            }
+            Section::ParamNames(s) => {
+                for name in s {
+                    name?;
+                }
+            }
+            Section::Unknown { .. } => {}
+        }
+    }
+    Ok(())
+}
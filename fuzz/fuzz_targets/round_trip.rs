@@ -0,0 +1,42 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use wasmparser::Payload;
+// Links in the bounded allocator.
+use wit_fuzz as _;
+
+// Generated modules are always valid, and should be unchanged by printing and
+// parsing them again, or by loading them into and emitting them from `walrus`.
+fuzz_target!(|module: wit_smith::Module| {
+    let wasm = module.to_bytes();
+    wit_validator::validate(&wasm).unwrap();
+
+    let text = wit_printer::print_bytes(&wasm).unwrap();
+    let wasm2 = wit_text::parse_str(&text).unwrap();
+    assert!(wasm == wasm2, "printing and parsing changed:\n{}", text);
+
+    let mut walrus = walrus::ModuleConfig::new()
+        .on_parse(wit_walrus::on_parse)
+        .parse(&wasm)
+        .unwrap();
+    let emitted = walrus.emit_wasm();
+    assert!(
+        section(&wasm) == section(&emitted),
+        "walrus changed the section of:\n{}",
+        text
+    );
+});
+
+fn section(wasm: &[u8]) -> Option<&[u8]> {
+    for payload in wasmparser::Parser::new(0).parse_all(wasm) {
+        if let Payload::CustomSection {
+            name: wit_schema_version::SECTION_NAME,
+            data,
+            ..
+        } = payload.unwrap()
+        {
+            return Some(data);
+        }
+    }
+    None
+}
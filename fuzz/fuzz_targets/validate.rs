@@ -0,0 +1,14 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let wasm = wit_fuzz::module(data);
+    let strict = wit_validator::validate(&wasm);
+    let lenient = wit_validator::Config::new().strict(false).validate(&wasm);
+
+    // Anything valid in strict mode must also be valid otherwise.
+    if strict.is_ok() {
+        lenient.unwrap();
+    }
+});
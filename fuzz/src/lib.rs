@@ -0,0 +1,89 @@
+//! Helpers shared by the fuzz targets in this directory.
+//!
+//! Fuzz inputs are the body of a `wasm-interface-types` section after the
+//! schema version, which is filled in here so the fuzzer doesn't have to find
+//! it. Linking this crate also installs a global allocator which fails any
+//! allocation that would take the amount of live memory above
+//! [`MAX_ALLOCATED`], turning unbounded allocations into crashes.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering::SeqCst};
+
+/// The most memory that may be allocated at any one time while handling an
+/// input.
+pub const MAX_ALLOCATED: usize = 64 << 20;
+
+struct Bounded;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+#[global_allocator]
+static ALLOCATOR: Bounded = Bounded;
+
+unsafe impl GlobalAlloc for Bounded {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let prev = ALLOCATED.fetch_add(layout.size(), SeqCst);
+        if prev + layout.size() > MAX_ALLOCATED {
+            ALLOCATED.fetch_sub(layout.size(), SeqCst);
+            // This aborts with a message describing the failed allocation.
+            return std::ptr::null_mut();
+        }
+        let ptr = System.alloc(layout);
+        if ptr.is_null() {
+            ALLOCATED.fetch_sub(layout.size(), SeqCst);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        ALLOCATED.fetch_sub(layout.size(), SeqCst);
+    }
+}
+
+/// Returns the payload of a `wasm-interface-types` section whose subsections
+/// are `data`.
+pub fn payload(data: &[u8]) -> Vec<u8> {
+    let version = wit_schema_version::VERSION;
+    let mut ret = Vec::new();
+    leb(&mut ret, version.len());
+    ret.extend_from_slice(version.as_bytes());
+    ret.extend_from_slice(data);
+    ret
+}
+
+/// Returns a core wasm module with an imported function, a local function, a
+/// memory and a `wasm-interface-types` section whose subsections are `data`.
+pub fn module(data: &[u8]) -> Vec<u8> {
+    let mut wasm = wat::parse_str(
+        r#"
+            (module
+                (import "" "f" (func (param i32 i32)))
+                (func (param i32) (result i32) local.get 0)
+                (memory 1))
+        "#,
+    )
+    .unwrap();
+    let name = wit_schema_version::SECTION_NAME;
+    let payload = payload(data);
+    let mut section = Vec::new();
+    leb(&mut section, name.len());
+    section.extend_from_slice(name.as_bytes());
+    section.extend_from_slice(&payload);
+    wasm.push(0);
+    leb(&mut wasm, section.len());
+    wasm.extend_from_slice(&section);
+    wasm
+}
+
+fn leb(dst: &mut Vec<u8>, mut n: usize) {
+    loop {
+        let byte = (n & 0x7f) as u8;
+        n >>= 7;
+        if n == 0 {
+            dst.push(byte);
+            break;
+        }
+        dst.push(byte | 0x80);
+    }
+}
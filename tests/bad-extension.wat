@@ -0,0 +1,16 @@
+;; validate-fail
+
+(module
+  (@interface func (param s32) (result s32)
+    arg.get 0
+    extension 7 "\01\02"
+  )
+)
+
+(; CHECK-ALL:
+failed to validate interface types section
+
+Caused by:
+    0: failed to validate adapter func 0
+    1: unknown extension instruction: 7
+;)
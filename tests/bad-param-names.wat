@@ -0,0 +1,12 @@
+;; parse-fail
+
+(module
+  (@interface func (param $a s32) (param $a s32)))
+
+(; CHECK-ALL:
+duplicate parameter named `$a`
+     --> tests/bad-param-names.wat:4:42
+      |
+    4 |   (@interface func (param $a s32) (param $a s32)))
+      |                                          ^
+;)
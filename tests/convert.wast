@@ -0,0 +1,123 @@
+;; Integer conversions between core and interface types.
+
+(module
+  (func $i32 (param i32) (result i32) local.get 0)
+  (func $i64 (param i64) (result i64) local.get 0)
+
+  (@interface func (export "i32-to-s8") (param $x s32) (result s8)
+    arg.get $x
+    s32-to-i32
+    call-core $i32
+    i32-to-s8)
+  (@interface func (export "i32-to-s8x") (param $x s32) (result s8)
+    arg.get $x
+    s32-to-i32
+    call-core $i32
+    i32-to-s8x)
+  (@interface func (export "i32-to-u8") (param $x s32) (result u8)
+    arg.get $x
+    s32-to-i32
+    call-core $i32
+    i32-to-u8)
+  (@interface func (export "i32-to-s16x") (param $x s32) (result s16)
+    arg.get $x
+    s32-to-i32
+    call-core $i32
+    i32-to-s16x)
+  (@interface func (export "i32-to-u32") (param $x s32) (result u32)
+    arg.get $x
+    s32-to-i32
+    call-core $i32
+    i32-to-u32)
+  (@interface func (export "i32-to-u64") (param $x s32) (result u64)
+    arg.get $x
+    s32-to-i32
+    call-core $i32
+    i32-to-u64)
+  (@interface func (export "u8-to-i32") (param $x u8) (result s32)
+    arg.get $x
+    u8-to-i32
+    call-core $i32
+    i32-to-s32)
+  (@interface func (export "s16-to-i64") (param $x s16) (result s64)
+    arg.get $x
+    s16-to-i64
+    call-core $i64
+    i64-to-s64)
+  (@interface func (export "u32-to-i64") (param $x u32) (result u64)
+    arg.get $x
+    u32-to-i64
+    call-core $i64
+    i64-to-u64)
+  (@interface func (export "s64-to-i32x") (param $x s64) (result s32)
+    arg.get $x
+    s64-to-i32x
+    call-core $i32
+    i32-to-s32)
+  (@interface func (export "u64-to-i32") (param $x u64) (result u32)
+    arg.get $x
+    u64-to-i32
+    call-core $i32
+    i32-to-u32)
+  (@interface func (export "u64-to-i32x") (param $x u64) (result u32)
+    arg.get $x
+    u64-to-i32x
+    call-core $i32
+    i32-to-u32)
+  (@interface func (export "i64-to-s32x") (param $x s64) (result s32)
+    arg.get $x
+    s64-to-i64
+    call-core $i64
+    i64-to-s32x)
+  (@interface func (export "i64-to-u16") (param $x s64) (result u16)
+    arg.get $x
+    s64-to-i64
+    call-core $i64
+    i64-to-u16)
+)
+
+(assert_return (invoke "i32-to-s8" (s32.const 1)) (s8.const 1))
+(assert_return (invoke "i32-to-s8" (s32.const 255)) (s8.const -1))
+(assert_return (invoke "i32-to-s8" (s32.const 0x180)) (s8.const -128))
+(assert_return (invoke "i32-to-s8x" (s32.const -128)) (s8.const -128))
+(assert_trap (invoke "i32-to-s8x" (s32.const 128)) "integer overflow")
+(assert_return (invoke "i32-to-u8" (s32.const -1)) (u8.const 255))
+(assert_return (invoke "i32-to-s16x" (s32.const 0x7fff)) (s16.const 0x7fff))
+(assert_trap (invoke "i32-to-s16x" (s32.const -0x8001)) "integer overflow")
+(assert_return (invoke "i32-to-u32" (s32.const -1)) (u32.const 0xffffffff))
+(assert_return (invoke "i32-to-u64" (s32.const -1)) (u64.const 0xffffffff))
+(assert_return (invoke "u8-to-i32" (u8.const 200)) (s32.const 200))
+(assert_return (invoke "s16-to-i64" (s16.const -2)) (s64.const -2))
+(assert_return (invoke "u32-to-i64" (u32.const 0xffffffff)) (u64.const 0xffffffff))
+(assert_return (invoke "s64-to-i32x" (s64.const -0x80000000)) (s32.const -0x80000000))
+(assert_trap (invoke "s64-to-i32x" (s64.const 0x80000000)) "integer overflow")
+(assert_return (invoke "u64-to-i32" (u64.const 0x1_0000_0001)) (u32.const 1))
+(assert_return (invoke "u64-to-i32x" (u64.const 0xffffffff)) (u32.const 0xffffffff))
+(assert_trap (invoke "u64-to-i32x" (u64.const 0x1_0000_0000)) "integer overflow")
+(assert_return (invoke "i64-to-s32x" (s64.const 0x7fffffff)) (s32.const 0x7fffffff))
+(assert_trap (invoke "i64-to-s32x" (s64.const -0x80000001)) "integer overflow")
+(assert_return (invoke "i64-to-u16" (s64.const 0x12345)) (u16.const 0x2345))
+
+;; Arguments must match the signature of the adapter.
+(assert_trap (invoke "i32-to-s8" (u32.const 1)) "expected arguments of type [S32]")
+(assert_trap (invoke "i32-to-s8") "expected arguments of type [S32]")
+
+(assert_invalid
+  (module
+    (func $i32 (param i32) (result i32) local.get 0)
+    (@interface func (param $x s32) (result s8)
+      arg.get $x
+      call-core $i32
+      i32-to-s8))
+  "expected I32 on type stack, found S32")
+(assert_invalid
+  (module
+    (func $i32 (param i32))
+    (@interface func (param $x s64)
+      arg.get $x
+      s32-to-i32
+      call-core $i32))
+  "expected S32 on type stack, found S64")
+(assert_malformed
+  (module quote "(@interface func (param s32) arg.get 0 s32-to-i8)")
+  "unknown operator")
@@ -0,0 +1,16 @@
+;; no-validate
+
+(module
+  (@interface func (param s32) (result s32)
+    arg.get 0
+    extension 7 "\01\02"
+  )
+)
+
+(; CHECK-ALL:
+(module
+  (@interface type (;0;) (func (param s32) (result s32)))
+  (@interface func (;0;) (type 0)
+    arg.get 0
+    extension 7 "\01\02"))
+;)
@@ -0,0 +1,40 @@
+(module
+  (func $foo (param i32 f32) (result i32)
+    i32.const 0)
+  (func $bar (param i32))
+  (@interface func $adapter_foo (export "foo") (param s32) (param f32) (result s32)
+    (i32-to-s32
+      (call-core $foo
+        (s32-to-i32 (arg.get 0))
+        (arg.get 1))))
+  (@interface func (export "bar") (param s32) (param f32)
+    arg.get 0
+    (call-adapter $adapter_foo (arg.get 1))
+    (defer-call-core $bar (s32-to-i32))
+    call-core $bar))
+
+(; CHECK-ALL:
+(module
+  (type (;0;) (func (param i32 f32) (result i32)))
+  (type (;1;) (func (param i32)))
+  (func $foo (type 0) (param i32 f32) (result i32)
+    i32.const 0)
+  (func $bar (type 1) (param i32))
+  (@interface type (;0;) (func (param s32) (param f32) (result s32)))
+  (@interface type (;1;) (func (param s32) (param f32)))
+  (@interface func (;0;) (type 0)
+    arg.get 0
+    s32-to-i32
+    arg.get 1
+    call-core $foo
+    i32-to-s32)
+  (@interface func (;1;) (type 1)
+    arg.get 0
+    arg.get 1
+    call-adapter 0
+    s32-to-i32
+    defer-call-core $bar
+    call-core $bar)
+  (@interface export "foo" (func 0))
+  (@interface export "bar" (func 1)))
+;)
@@ -0,0 +1,46 @@
+;; Core imports implemented by adapters call into those adapters.
+
+(module
+  (import "host" "double" (func $double (param i32) (result i32)))
+  (import "host" "missing" (func $missing))
+  (@interface import "host" "log" (func $log (param s32)))
+
+  (func $quadruple (param i32) (result i32)
+    local.get 0
+    call $double
+    call $double)
+  (func $call-missing call $missing)
+
+  (@interface func $double (param $x i32) (result i32)
+    arg.get $x
+    arg.get $x
+    call-core $add)
+  (@interface implement (import "host" "double") (func $double))
+
+  (func $add (param i32 i32) (result i32)
+    local.get 0
+    local.get 1
+    i32.add)
+
+  (@interface func (export "double") (param $x s32) (result s32)
+    arg.get $x
+    s32-to-i32
+    call-adapter $double
+    i32-to-s32)
+  (@interface func (export "quadruple") (param $x s32) (result s32)
+    arg.get $x
+    s32-to-i32
+    call-core $quadruple
+    i32-to-s32)
+  (@interface func (export "call-missing")
+    call-core $call-missing)
+  (@interface func (export "log") (param $x s32)
+    arg.get $x
+    call-adapter $log)
+)
+
+(assert_return (invoke "double" (s32.const 21)) (s32.const 42))
+(assert_return (invoke "quadruple" (s32.const 3)) (s32.const 12))
+(assert_return (invoke "quadruple" (s32.const 0x40000000)) (s32.const 0))
+(assert_trap (invoke "call-missing") "unimplemented import `host::missing`")
+(assert_trap (invoke "log" (s32.const 1)) "unimplemented import `host::log`")
@@ -0,0 +1,24 @@
+(module
+  (func $pair (result i32 f32) unreachable)
+  (func $defer (param i32 f32))
+  (@interface func (import "\01" "a\"b") (param $x s32) (result s32))
+  (@interface func (export "\c3\a9\7f") (result i32) (result f32)
+    call-core $pair
+    defer-call-core $defer)
+)
+
+(; CHECK-ALL:
+(module
+  (type (;0;) (func (result i32 f32)))
+  (type (;1;) (func (param i32 f32)))
+  (func $pair (type 0) (result i32 f32)
+    unreachable)
+  (func $defer (type 1) (param i32 f32))
+  (@interface type (;0;) (func (param s32) (result s32)))
+  (@interface type (;1;) (func (result i32) (result f32)))
+  (@interface import "\01" "a\"b" (func (;0;) (type 0) (param $x s32) (result s32)))
+  (@interface func (;1;) (type 1)
+    call-core $pair
+    defer-call-core $defer)
+  (@interface export "\c3\a9\7f" (func 1)))
+;)
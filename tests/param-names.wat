@@ -0,0 +1,38 @@
+(module
+  (import "" "f" (func $f (param i32)))
+  (@interface type $t (func (param $x s32) (param f32)))
+  (@interface func (import "a" "b") (type $t))
+  (@interface func (export "c") (type $t)
+    arg.get $x
+    s32-to-i32
+    call-core $f)
+  (@interface func $g (param s32) (param $z u8))
+  (@interface implement (import "" "f") (param $y i32)
+    arg.get $y
+    i32-to-s32
+    arg.get 0
+    i32-to-u8
+    call-adapter $g))
+
+(; CHECK-ALL:
+(module
+  (type (;0;) (func (param i32)))
+  (import "" "f" (func $f (type 0)))
+  (@interface type (;0;) (func (param s32) (param f32)))
+  (@interface type (;1;) (func (param s32) (param u8)))
+  (@interface type (;2;) (func (param i32)))
+  (@interface import "a" "b" (func (;0;) (type 0) (param $x s32) (param f32)))
+  (@interface func (;1;) (type 0) (param $x s32) (param f32)
+    arg.get $x
+    s32-to-i32
+    call-core $f)
+  (@interface func (;2;) (type 1) (param s32) (param $z u8))
+  (@interface func (;3;) (type 2) (param $y i32)
+    arg.get $y
+    i32-to-s32
+    arg.get $y
+    i32-to-u8
+    call-adapter 2)
+  (@interface export "c" (func 1))
+  (@interface implement (func $f) (func 3)))
+;)
@@ -44,24 +44,24 @@
   (func $log (type 2) (param i32 i32))
   (memory (;0;) 1)
   (@interface type (;0;) (func (param string)))
-  (@interface func (;0;) (type 0)
-    arg.get 0
+  (@interface func (;0;) (type 0) (param $str string)
+    arg.get $str
     string-to-memory $malloc
     call-core $log)
-  (@interface func (;1;) (type 0)
-    arg.get 0
+  (@interface func (;1;) (type 0) (param $str string)
+    arg.get $str
     string-to-memory $malloc
     call-core $log)
-  (@interface func (;2;) (type 0)
-    arg.get 0
+  (@interface func (;2;) (type 0) (param $str string)
+    arg.get $str
     string-to-memory $malloc
     call-core $log)
-  (@interface func (;3;) (type 0)
-    arg.get 0
+  (@interface func (;3;) (type 0) (param $str string)
+    arg.get $str
     string-to-memory $malloc
     call-core $log)
-  (@interface func (;4;) (type 0)
-    arg.get 0
+  (@interface func (;4;) (type 0) (param $str string)
+    arg.get $str
     string-to-memory $malloc
     call-core $log)
   (@interface export "log" (func 0)))
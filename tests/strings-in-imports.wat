@@ -18,10 +18,10 @@
   (memory (;0;) 1)
   (@interface type (;0;) (func (param string)))
   (@interface type (;1;) (func (param i32) (param i32)))
-  (@interface import "" "log" (func (;0;) (type 0)))
-  (@interface func (;1;) (type 1)
-    arg.get 0
-    arg.get 1
+  (@interface import "" "log" (func (;0;) (type 0) (param $arg string)))
+  (@interface func (;1;) (type 1) (param $ptr i32) (param $len i32)
+    arg.get $ptr
+    arg.get $len
     memory-to-string
     call-adapter 0)
   (@interface implement (func 0) (func 1)))
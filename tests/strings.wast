@@ -0,0 +1,105 @@
+;; Strings are read from and written to linear memory.
+
+(module
+  (memory $mem 1)
+  (data (i32.const 0) "hello")
+  (data (i32.const 8) "\ff")
+  (global $next (mut i32) (i32.const 16))
+  (global $freed (mut i32) (i32.const 0))
+  (func $malloc (param i32) (result i32)
+    global.get $next
+    global.get $next
+    local.get 0
+    i32.add
+    global.set $next)
+  (func $free (param i32)
+    local.get 0
+    global.set $freed)
+  (func $freed (result i32) global.get $freed)
+  (func $greeting (result i32 i32) i32.const 0 i32.const 5)
+  (func $bad-utf8 (result i32 i32) i32.const 8 i32.const 1)
+  (func $out-of-bounds (result i32 i32) i32.const 65535 i32.const 2)
+  (func $len (param i32 i32) (result i32) local.get 1)
+  (func $unreachable (result i32 i32) unreachable)
+
+  (@interface func (export "greeting") (result string)
+    call-core $greeting
+    memory-to-string $mem)
+  (@interface func (export "bad-utf8") (result string)
+    call-core $bad-utf8
+    memory-to-string $mem)
+  (@interface func (export "out-of-bounds") (result string)
+    call-core $out-of-bounds
+    memory-to-string $mem)
+  (@interface func (export "unreachable") (result string)
+    call-core $unreachable
+    memory-to-string $mem)
+  (@interface func (export "len") (param $s string) (result s32)
+    arg.get $s
+    string-to-memory $malloc $mem
+    call-core $len
+    i32-to-s32)
+  (@interface func (export "echo") (param $s string) (result string)
+    arg.get $s
+    string-to-memory $malloc $mem
+    memory-to-string $mem)
+  (@interface func (export "greeting-and-free") (result string)
+    call-core $greeting
+    defer-call-core $free
+    memory-to-string $mem)
+  (@interface func (export "bad-utf8-and-free") (result string)
+    call-core $bad-utf8
+    defer-call-core $free
+    memory-to-string $mem)
+  (@interface func (export "freed") (result s32)
+    call-core $freed
+    i32-to-s32)
+)
+
+(assert_return (invoke "greeting") (string.const "hello"))
+(assert_trap (invoke "bad-utf8") "invalid utf-8")
+(assert_trap (invoke "out-of-bounds") "out of bounds memory access")
+(assert_trap (invoke "unreachable") "unreachable")
+(assert_return (invoke "len" (string.const "")) (s32.const 0))
+(assert_return (invoke "len" (string.const "\u{1f600}")) (s32.const 4))
+(assert_return (invoke "echo" (string.const "round trip")) (string.const "round trip"))
+
+;; Deferred calls take their arguments without consuming them, and run once
+;; the adapter is done.
+(assert_return (invoke "freed") (s32.const 0))
+(assert_return (invoke "greeting-and-free") (string.const "hello"))
+(assert_return (invoke "freed") (s32.const 5))
+
+;; They also run if the adapter traps.
+(assert_trap (invoke "bad-utf8-and-free") "invalid utf-8")
+(assert_return (invoke "freed") (s32.const 1))
+
+;; Later invocations run against the most recently defined module.
+(module
+  (memory 1)
+  (data (i32.const 0) "bye")
+  (func $greeting (result i32 i32) i32.const 0 i32.const 3)
+  (@interface func (export "greeting") (result string)
+    call-core $greeting
+    memory-to-string)
+)
+
+(invoke "greeting")
+(assert_return (invoke "greeting") (string.const "bye"))
+(assert_trap (invoke "echo" (string.const "")) "no adapter exported as `echo`")
+
+(assert_malformed
+  (module quote
+    "(func $greeting (result i32 i32) i32.const 0 i32.const 3)"
+    "(@interface func (result string)"
+    "  call-core $greeting"
+    "  memory-to-string $mem)")
+  "failed to find memory named `$mem`")
+(assert_invalid
+  (module
+    (memory 1)
+    (func $greeting (result i32))
+    (@interface func (result string)
+      call-core $greeting
+      memory-to-string))
+  "expected I32 on type stack, found nothing")